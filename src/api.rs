@@ -0,0 +1,241 @@
+//! JSON API running the `fm_`, `cln_` and `ln_` commands for scripts, with the
+//! same handlers as the slash commands.
+//!
+//! `POST /commands/<name>` takes the command's options as a JSON object and
+//! needs `Authorization: Bearer <API_TOKEN>`. The token grants every command
+//! and skips the confirmations Discord users are asked for.
+//!
+//! `POST /cln/notifications` takes `{"node", "topic", "payload"}` from the
+//! `botimint-cln-plugin` running in a CLN node and posts it as an alert. It
+//! needs `Authorization: Bearer <CLN_NOTIFY_TOKEN>` instead, so the nodes
+//! aren't given a token that runs commands.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, UserId};
+use tracing::info;
+
+use crate::commands::cln::ClnCommand;
+use crate::commands::fed::FmCommand;
+use crate::commands::ln::LnCommand;
+use crate::commands::{cln, fed, ln, CommandError, CommandOptions, CommandResponse};
+use crate::federations::Federations;
+use crate::lightning::{ClnNodes, LightningBackend};
+use crate::notifications::cln_alerts;
+use crate::utils::progress::Progress;
+
+#[derive(Clone)]
+pub struct ApiState {
+    pub cln_nodes: ClnNodes,
+    pub lightning: Arc<dyn LightningBackend>,
+    pub federations: Federations,
+    pub token: Arc<String>,
+    pub notify_token: Option<Arc<String>>,
+    // The ledger account `fm_` commands credit and debit
+    pub user_id: UserId,
+    pub http: Arc<Http>,
+    pub alert_channels: Arc<HashMap<String, ChannelId>>,
+}
+
+pub async fn serve(addr: SocketAddr, state: ApiState) -> anyhow::Result<()> {
+    let app = Router::new()
+        .route("/commands/:name", post(run_command))
+        .route("/cln/notifications", post(post_cln_notification))
+        .with_state(state);
+
+    info!("Serving the API on {}", addr);
+    axum::Server::bind(&addr)
+        .serve(app.into_make_service())
+        .await?;
+
+    Ok(())
+}
+
+async fn run_command(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(options): Json<Map<String, Value>>,
+) -> Result<Json<Value>, ApiError> {
+    authorize(&headers, &state.token)?;
+
+    let options: CommandOptions = options
+        .into_iter()
+        .map(|(name, value)| (name, Some(value)))
+        .collect();
+    let progress = Progress::default();
+
+    info!("Running {} for the API", name);
+    let res = match name.as_str() {
+        name if !matches!(ClnCommand::from(name), ClnCommand::Unknown) => {
+            cln::handle_run(name, &options, &state.cln_nodes).await
+        }
+        name if !matches!(LnCommand::from(name), LnCommand::Unknown) => {
+            ln::handle_run(name, &options, state.lightning.as_ref()).await
+        }
+        name if !matches!(FmCommand::from(name), FmCommand::Unknown) => {
+            fed::handle_run(name, &options, &state.federations, state.user_id, &progress).await
+        }
+        name => {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("Unknown command: {}", name),
+            ))
+        }
+    };
+
+    match res? {
+        CommandResponse::Text(content) => Ok(Json(json!({ "result": parse_content(&content) }))),
+        CommandResponse::List(list) => Ok(Json(json!({
+            "title": list.title(),
+            "items": list.items().iter().map(|(_, item)| item).collect::<Vec<_>>(),
+        }))),
+    }
+}
+
+#[derive(Deserialize)]
+struct ClnNotification {
+    node: String,
+    topic: String,
+    #[serde(default)]
+    payload: Value,
+}
+
+async fn post_cln_notification(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    Json(notification): Json<ClnNotification>,
+) -> Result<Json<Value>, ApiError> {
+    match &state.notify_token {
+        Some(token) => authorize(&headers, token)?,
+        None => {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                "CLN notifications are off, set CLN_NOTIFY_TOKEN",
+            ))
+        }
+    }
+    if !cln_alerts::TOPICS.contains(&notification.topic.as_str()) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            format!("Unknown CLN notification: {}", notification.topic),
+        ));
+    }
+
+    let posted = cln_alerts::post(
+        &state.http,
+        &state.alert_channels,
+        &notification.node,
+        &notification.topic,
+        &notification.payload,
+    )
+    .await;
+
+    Ok(Json(json!({ "posted": posted })))
+}
+
+fn authorize(headers: &HeaderMap, token: &str) -> Result<(), ApiError> {
+    let provided = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match provided {
+        Some(provided) if constant_time_eq(provided.as_bytes(), token.as_bytes()) => Ok(()),
+        _ => Err(ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid bearer token",
+        )),
+    }
+}
+
+// Compares without returning early, so response times don't leak how much of
+// the token a guess got right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// Command replies are meant for Discord, mostly JSON in a code block. Returns
+// the JSON itself when there is some, or the reply as a string.
+fn parse_content(content: &str) -> Value {
+    let trimmed = content.trim();
+    let inner = trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|code| code.trim_start_matches("json").trim())
+        .unwrap_or(trimmed);
+
+    serde_json::from_str(inner).unwrap_or_else(|_| Value::String(content.to_string()))
+}
+
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<CommandError> for ApiError {
+    fn from(e: CommandError) -> Self {
+        let status = match e {
+            CommandError::MissingOption(_)
+            | CommandError::MissingOneOf(_)
+            | CommandError::ConflictingOptions(_)
+            | CommandError::InvalidOption { .. } => StatusCode::BAD_REQUEST,
+            CommandError::Ledger(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            CommandError::Rpc(_) | CommandError::Federation(_) => StatusCode::BAD_GATEWAY,
+        };
+        Self::new(status, e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_json_replies() {
+        assert_eq!(
+            parse_content("```json\n{\"id\": \"abc\"}\n```"),
+            json!({ "id": "abc" })
+        );
+        assert_eq!(
+            parse_content("Your federation id is: abc"),
+            json!("Your federation id is: abc")
+        );
+    }
+
+    #[test]
+    fn checks_the_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert!(authorize(&headers, "secret").is_err());
+
+        headers.insert("authorization", "Bearer wrong".parse().unwrap());
+        assert!(authorize(&headers, "secret").is_err());
+
+        headers.insert("authorization", "Bearer secret".parse().unwrap());
+        assert!(authorize(&headers, "secret").is_ok());
+    }
+}
@@ -0,0 +1,264 @@
+//! Runs the bot's `fm_`, `cln_` and `ln_` commands from a shell, for debugging
+//! and ops:
+//!
+//! ```sh
+//! botimint-cli [--yes] <command> [--<option> <value>]...
+//! ```
+//!
+//! Options are typed like Discord types them from the command's registered
+//! options. Commands that ask Discord users for confirmation prompt on stdin
+//! unless `--yes` is given. `fm_` commands credit and debit ledger account 0.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::process::ExitCode;
+
+use anyhow::{bail, Context, Result};
+use botimint::commands::cln::ClnCommand;
+use botimint::commands::fed::FmCommand;
+use botimint::commands::ln::LnCommand;
+use botimint::commands::{cln, fed, ln, CommandOptions, CommandResponse, CommandResult};
+use botimint::federations::Federations;
+use botimint::ledger::Ledger;
+use botimint::lightning::{self, ClnNodes};
+use botimint::utils::progress::Progress;
+use botimint::CONFIG;
+use serde_json::Value;
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+use serenity::model::id::UserId;
+use tracing::warn;
+
+// The ledger account of the operator running the CLI
+const CLI_USER_ID: UserId = UserId(0);
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Only warnings, so the command's output isn't drowned in connection logs
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_writer(std::io::stderr)
+        .finish();
+    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let yes = args.first().map_or(false, |arg| arg == "--yes");
+    if yes {
+        args.remove(0);
+    }
+
+    let commands = [cln::commands(), ln::commands(), fed::commands()].concat();
+    let (name, flags) = match args.split_first() {
+        Some((name, flags)) if !name.starts_with("--") => (name, flags),
+        _ => {
+            print_usage(&commands);
+            return ExitCode::FAILURE;
+        }
+    };
+    let command = match commands
+        .iter()
+        .find(|command| command_name(command) == name)
+    {
+        Some(command) => command,
+        None => {
+            eprintln!("Unknown command: {}", name);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let res = match parse_options(command, flags) {
+        Ok(options) => run(name, &options, yes).await,
+        Err(e) => Err(e),
+    };
+    match res {
+        Ok(Some(Ok(response))) => {
+            print_response(response);
+            ExitCode::SUCCESS
+        }
+        Ok(Some(Err(e))) => {
+            eprintln!("`{}` failed: {}: {}", name, e.title(), e);
+            ExitCode::FAILURE
+        }
+        Ok(None) => {
+            eprintln!("Cancelled");
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("{:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Connects to what the command needs and runs it, or returns `None` when the
+// user doesn't confirm it
+async fn run(name: &str, options: &CommandOptions, yes: bool) -> Result<Option<CommandResult>> {
+    let (progress, mut updates) = Progress::channel();
+    let printer = tokio::spawn(async move {
+        while let Some(update) = updates.recv().await {
+            eprintln!("{}", update);
+        }
+    });
+
+    let res = if let ClnCommand::Unknown = ClnCommand::from(name) {
+        if let LnCommand::Unknown = LnCommand::from(name) {
+            warn!(
+                "fm_ commands run from the CLI use ledger account {}",
+                CLI_USER_ID
+            );
+            let ledger = Ledger::open(&CONFIG.ledger_db_path)?;
+            let federations = Federations::load(ledger).await?;
+            if !yes && FmCommand::from(name).needs_confirmation() {
+                match fed::handle_summary(name, options, &federations).await {
+                    Ok(summary) if !confirm(&summary)? => return Ok(None),
+                    Ok(_) => {}
+                    Err(e) => return Ok(Some(Err(e))),
+                }
+            }
+            fed::handle_run(name, options, &federations, CLI_USER_ID, &progress).await
+        } else {
+            let cln_nodes = ClnNodes::connect(&CONFIG.cln_nodes).await;
+            let lightning = lightning::backend(&CONFIG.lightning, &cln_nodes)?;
+            if !yes && LnCommand::from(name).needs_confirmation() {
+                match ln::handle_summary(name, options, lightning.as_ref()).await {
+                    Ok(summary) if !confirm(&summary)? => return Ok(None),
+                    Ok(_) => {}
+                    Err(e) => return Ok(Some(Err(e))),
+                }
+            }
+            ln::handle_run(name, options, lightning.as_ref()).await
+        }
+    } else {
+        let cln_nodes = ClnNodes::connect(&CONFIG.cln_nodes).await;
+        if !yes && ClnCommand::from(name).needs_confirmation() {
+            match cln::handle_summary(name, options, &cln_nodes).await {
+                Ok(summary) if !confirm(&summary)? => return Ok(None),
+                Ok(_) => {}
+                Err(e) => return Ok(Some(Err(e))),
+            }
+        }
+        cln::handle_run(name, options, &cln_nodes).await
+    };
+
+    drop(progress);
+    printer.await?;
+    Ok(Some(res))
+}
+
+fn confirm(summary: &str) -> Result<bool> {
+    print!("{}\nContinue? [y/N] ", summary);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+// Reads `--<option> <value>` pairs, typing each value after the option's
+// registered type. A boolean option without a value is set to true.
+fn parse_options(command: &CreateApplicationCommand, flags: &[String]) -> Result<CommandOptions> {
+    let kinds = option_kinds(command);
+    let mut options = CommandOptions::new();
+    let mut flags = flags.iter().peekable();
+    while let Some(flag) = flags.next() {
+        let name = flag
+            .strip_prefix("--")
+            .with_context(|| format!("Expected an option, got {}", flag))?;
+        let kind = match kinds.get(name) {
+            Some(kind) => *kind,
+            None => bail!(
+                "Unknown option --{} for {}, the options are: {}",
+                name,
+                command_name(command),
+                kinds
+                    .keys()
+                    .map(|name| format!("--{}", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+        let value = match flags.next_if(|value| !value.starts_with("--")) {
+            Some(value) => {
+                parse_value(kind, value).with_context(|| format!("Invalid value for --{}", name))?
+            }
+            None if kind == CommandOptionType::Boolean as u64 => Value::Bool(true),
+            None => bail!("Missing value for --{}", name),
+        };
+        options.insert(name.to_string(), Some(value));
+    }
+
+    Ok(options)
+}
+
+fn parse_value(kind: u64, value: &str) -> Result<Value> {
+    Ok(match kind {
+        k if k == CommandOptionType::Integer as u64 => {
+            Value::from(value.parse::<i64>().context("expected an integer")?)
+        }
+        k if k == CommandOptionType::Number as u64 => {
+            Value::from(value.parse::<f64>().context("expected a number")?)
+        }
+        k if k == CommandOptionType::Boolean as u64 => {
+            Value::Bool(value.parse::<bool>().context("expected true or false")?)
+        }
+        // Discord has no list options, but some handlers take JSON ones
+        _ if value.starts_with('[') || value.starts_with('{') => {
+            serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+        }
+        _ => Value::String(value.to_string()),
+    })
+}
+
+// The registered type of each of the command's options, by name
+fn option_kinds(command: &CreateApplicationCommand) -> HashMap<String, u64> {
+    command
+        .0
+        .get("options")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|option| {
+            Some((
+                option["name"].as_str()?.to_string(),
+                option["type"].as_u64()?,
+            ))
+        })
+        .collect()
+}
+
+fn command_name(command: &CreateApplicationCommand) -> &str {
+    command
+        .0
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+fn print_usage(commands: &[CreateApplicationCommand]) {
+    eprintln!("Usage: botimint-cli [--yes] <command> [--<option> <value>]...\n\nCommands:");
+    for command in commands {
+        let description = command.0.get("description").and_then(Value::as_str);
+        eprintln!(
+            "  {:<28} {}",
+            command_name(command),
+            description.unwrap_or_default()
+        );
+    }
+}
+
+// Prints what the bot would post, with lists expanded instead of paginated
+fn print_response(response: CommandResponse) {
+    match response {
+        CommandResponse::Text(content) => println!("{}", content),
+        CommandResponse::List(list) => {
+            println!("{} ({})", list.title(), list.len());
+            for (name, item) in list.items() {
+                println!(
+                    "\n{}\n{}",
+                    name,
+                    serde_json::to_string_pretty(item).unwrap()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! CLN plugin forwarding the node's channel, peer, payment and coin movement
+//! notifications to the bot, which posts them as alerts. Load it into the node
+//! with the bot's API settings:
+//!
+//! ```sh
+//! lightningd --plugin=/path/to/botimint-cln-plugin \
+//!     --botimint-url=http://127.0.0.1:3000 --botimint-token=<CLN_NOTIFY_TOKEN> \
+//!     --botimint-node=main
+//! ```
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use botimint::notifications::cln_alerts;
+use cln_plugin::options::{ConfigOption, Value as OptionValue};
+use cln_plugin::{Builder, Plugin};
+use serde_json::{json, Value};
+
+#[derive(Clone)]
+struct State {
+    http: reqwest::Client,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut builder = Builder::new(tokio::io::stdin(), tokio::io::stdout())
+        .option(ConfigOption::new(
+            "botimint-url",
+            OptionValue::OptString,
+            "Base URL of the botimint API",
+        ))
+        .option(ConfigOption::new(
+            "botimint-token",
+            OptionValue::OptString,
+            "The bot's CLN_NOTIFY_TOKEN",
+        ))
+        .option(ConfigOption::new(
+            "botimint-node",
+            OptionValue::String("main".to_string()),
+            "Name of this node in the bot's CLN_NODES",
+        ));
+    for topic in cln_alerts::TOPICS {
+        builder = builder.subscribe(topic, move |plugin, payload| {
+            forward(plugin, topic, payload)
+        });
+    }
+
+    let state = State {
+        http: reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?,
+    };
+    let plugin = match builder.start(state).await? {
+        Some(plugin) => plugin,
+        // lightningd only asked for the manifest
+        None => return Ok(()),
+    };
+    plugin.join().await
+}
+
+async fn forward(plugin: Plugin<State>, topic: &'static str, payload: Value) -> Result<()> {
+    let (url, token) = match (
+        option(&plugin, "botimint-url"),
+        option(&plugin, "botimint-token"),
+    ) {
+        (Some(url), Some(token)) => (url, token),
+        // Without the API there is nowhere to forward to
+        _ => return Ok(()),
+    };
+    let node = option(&plugin, "botimint-node").unwrap_or_else(|| "main".to_string());
+
+    let res = plugin
+        .state()
+        .http
+        .post(format!("{}/cln/notifications", url.trim_end_matches('/')))
+        .bearer_auth(token)
+        .json(&json!({ "node": node, "topic": topic, "payload": payload }))
+        .send()
+        .await?;
+    if !res.status().is_success() {
+        return Err(anyhow!(
+            "The bot rejected the {} notification: {} {}",
+            topic,
+            res.status(),
+            res.text().await.unwrap_or_default()
+        ));
+    }
+
+    Ok(())
+}
+
+fn option(plugin: &Plugin<State>, name: &str) -> Option<String> {
+    match plugin.option(name) {
+        Some(OptionValue::String(value)) if !value.is_empty() => Some(value),
+        _ => None,
+    }
+}
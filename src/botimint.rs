@@ -1,60 +1,518 @@
+use std::borrow::Cow;
 use std::sync::Arc;
 
-use cln_rpc::ClnRpc;
-use fedimint_client::ClientArc;
+use fedimint_core::config::FederationId;
+use fedimint_core::core::OperationId;
 use serenity::async_trait;
+use serenity::builder::{CreateApplicationCommand, CreateEmbed};
 use serenity::client::{Context, EventHandler};
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
 use serenity::model::application::interaction::{Interaction, InteractionResponseType};
-use serenity::model::channel::Message;
+use serenity::model::channel::{AttachmentType, Message, Reaction};
 use serenity::model::gateway::Ready;
-use tokio::sync::Mutex;
+use serenity::Error as SerenityError;
+use tokio::sync::mpsc;
 use tracing::{error, info};
 
-use crate::commands::{cln, custom, fed};
+use crate::commands::account::AccountCommand;
+use crate::commands::cln::ClnCommand;
+use crate::commands::fed::FmCommand;
+use crate::commands::ln::LnCommand;
+use crate::commands::{
+    account, autocomplete, cln, custom, discord_command_options_to_map, fed, ln, report, status,
+    subcommands, CommandError, CommandResponse, CommandResult,
+};
+use crate::components::Components;
+use crate::config::CONFIG;
+use crate::federations::Federations;
+use crate::lightning::{ClnNodes, LightningBackend};
+use crate::notifications::invoices;
+use crate::pending::{self, PendingOperation, StartedOperation};
+use crate::permissions::{authorize, Permission};
+use crate::utils::discord_utils::register_commands;
+use crate::utils::progress::Progress;
+use crate::utils::response::{prepare_reply, Reply};
 
 // Botimint Structure
+#[derive(Clone)]
 pub struct Botimint {
-    cln_client: Arc<Mutex<ClnRpc>>,
-    fm_client: ClientArc,
+    cln_nodes: ClnNodes,
+    lightning: Arc<dyn LightningBackend>,
+    federations: Federations,
+    components: Components,
 }
 
 impl Botimint {
-    pub fn new(cln_client: Arc<Mutex<ClnRpc>>, fm_client: ClientArc) -> Self {
+    pub fn new(
+        cln_nodes: ClnNodes,
+        lightning: Arc<dyn LightningBackend>,
+        federations: Federations,
+    ) -> Self {
         Self {
-            cln_client,
-            fm_client,
+            cln_nodes,
+            lightning,
+            federations,
+            components: Components::default(),
         }
     }
+
+    fn authorize(
+        &self,
+        command_name: &str,
+        required: Permission,
+        command: &ApplicationCommandInteraction,
+    ) -> Result<(), String> {
+        let role_ids = command
+            .member
+            .as_ref()
+            .map(|member| member.roles.as_slice())
+            .unwrap_or_default();
+        authorize(command_name, required, command.user.id, role_ids)
+    }
+
+    async fn run_command(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+        progress: &Progress,
+    ) -> CommandResult {
+        let options = discord_command_options_to_map(&command.data.options);
+        match command.data.name.as_str() {
+            name if name.starts_with("cln_") => {
+                let res = cln::handle_run(name, &options, &self.cln_nodes).await;
+                if res.is_ok() && matches!(ClnCommand::from(name), ClnCommand::ClnInvoice) {
+                    invoices::remember_creator(&self.cln_nodes, &options, command.user.id).await;
+                }
+                res
+            }
+            name if name.starts_with("ln_") => {
+                ln::handle_run(name, &options, self.lightning.as_ref()).await
+            }
+            name if name.starts_with("fm_") => {
+                fed::handle_run(name, &options, &self.federations, command.user.id, progress).await
+            }
+            "report" => report::run(&self.cln_nodes, &self.federations).await,
+            "status" => status::run(&self.cln_nodes),
+            name @ ("balance" | "deposit" | "withdraw" | "tip" | "Tip this message") => {
+                // Accounts are kept in the default federation
+                let federation = self.federations.default_federation();
+                account::handle_run(
+                    name,
+                    &command.data,
+                    &ctx.http,
+                    &federation.client,
+                    &federation.ledger,
+                    command.user.id,
+                    progress,
+                )
+                .await
+            }
+            _ => custom::handle_run(&command.data.name, &command.data).await,
+        }
+    }
+
+    // Runs a deferred command to completion, posting its progress updates as
+    // follow-ups and replacing the deferred response with the final result
+    async fn complete_command(
+        &self,
+        ctx: Context,
+        command: ApplicationCommandInteraction,
+        ephemeral: bool,
+    ) {
+        let (progress, mut updates) = Progress::channel();
+        let (progress, operations) = progress.with_operations();
+        let recorder = tokio::spawn(record_operations(
+            ctx.clone(),
+            command.clone(),
+            ephemeral,
+            self.federations.clone(),
+            operations,
+        ));
+        let http = ctx.http.clone();
+        let followup_command = command.clone();
+        let followups = tokio::spawn(async move {
+            while let Some(update) = updates.recv().await {
+                if let Err(why) = followup_command
+                    .create_followup_message(&http, |followup| {
+                        followup.content(update).ephemeral(ephemeral)
+                    })
+                    .await
+                {
+                    error!("Cannot send progress update: {:?}", why);
+                }
+            }
+        });
+
+        let response = self.run_command(&ctx, &command, &progress).await;
+        drop(progress);
+        if let Err(why) = followups.await {
+            error!("Progress updates task failed: {:?}", why);
+        }
+        let started = recorder.await.unwrap_or_default();
+
+        match response {
+            Ok(CommandResponse::Text(content)) => reply(&ctx, &command, ephemeral, content).await,
+            Ok(CommandResponse::List(list)) => {
+                self.components.paginator.send(&ctx, &command, list).await
+            }
+            Err(e) => {
+                info!("Command {} failed: {}", command.data.name, e);
+                reply_error(&ctx, &command, ephemeral, &e).await
+            }
+        }
+
+        // The user has the outcome, so there is nothing left to resume
+        for (federation_id, operation_id) in started {
+            let federation = match self.federations.get(federation_id) {
+                Some(federation) => federation,
+                None => continue,
+            };
+            let pending = federation.ledger.pending_operations();
+            if let Err(e) = pending.remove(operation_id).await {
+                error!("Cannot remove pending operation {}: {:?}", operation_id, e);
+            }
+        }
+    }
+
+    // Shows the user what a command is about to do and waits for them to
+    // confirm it before running it
+    async fn request_confirmation(&self, ctx: Context, command: ApplicationCommandInteraction) {
+        let options = discord_command_options_to_map(&command.data.options);
+        let summary = match command.data.name.as_str() {
+            name if name.starts_with("cln_") => {
+                cln::handle_summary(name, &options, &self.cln_nodes).await
+            }
+            name if name.starts_with("ln_") => {
+                ln::handle_summary(name, &options, self.lightning.as_ref()).await
+            }
+            name => fed::handle_summary(name, &options, &self.federations).await,
+        };
+
+        match summary {
+            Ok(summary) => {
+                self.components
+                    .confirmations
+                    .ask(&ctx, command, summary)
+                    .await
+            }
+            // Confirmations are only shown to the user
+            Err(e) => reply_error(&ctx, &command, true, &e).await,
+        }
+    }
+}
+
+// The permission a command needs, if any, and whether it asks for confirmation
+fn requirements(name: &str) -> (Option<Permission>, bool) {
+    match name {
+        name if name.starts_with("cln_") => {
+            let cln_command = ClnCommand::from(name);
+            (
+                Some(cln_command.permission()),
+                cln_command.needs_confirmation(),
+            )
+        }
+        name if name.starts_with("ln_") => {
+            let ln_command = LnCommand::from(name);
+            (
+                Some(ln_command.permission()),
+                ln_command.needs_confirmation(),
+            )
+        }
+        name if name.starts_with("fm_") => {
+            let fm_command = FmCommand::from(name);
+            (
+                Some(fm_command.permission()),
+                fm_command.needs_confirmation(),
+            )
+        }
+        // The report shows the nodes' and federations' balances
+        "report" => (Some(Permission::ReadOnly), false),
+        "status" => (Some(Permission::ReadOnly), false),
+        _ => (None, false),
+    }
+}
+
+// Keeps the operations the command waits on until it completes, with where to
+// deliver their outcome if the bot restarts before that. Returns the ids of
+// those that aren't left to finish in the background.
+async fn record_operations(
+    ctx: Context,
+    command: ApplicationCommandInteraction,
+    ephemeral: bool,
+    federations: Federations,
+    mut operations: mpsc::UnboundedReceiver<StartedOperation>,
+) -> Vec<(FederationId, OperationId)> {
+    let mut started = Vec::new();
+    while let Some(operation) = operations.recv().await {
+        let pending = match federations.get(operation.federation_id) {
+            Some(federation) => federation.ledger.pending_operations(),
+            None => continue,
+        };
+        // Responses only the user sees can't be replied to in the channel
+        let (channel_id, message_id) = if ephemeral {
+            (None, None)
+        } else {
+            match command.get_interaction_response(&ctx.http).await {
+                Ok(message) => (Some(command.channel_id.0), Some(message.id.0)),
+                Err(why) => {
+                    error!(
+                        "Cannot get the response to {}: {:?}",
+                        command.data.name, why
+                    );
+                    (Some(command.channel_id.0), None)
+                }
+            }
+        };
+        let record = PendingOperation {
+            federation_id: operation.federation_id,
+            kind: operation.kind,
+            user_id: command.user.id.0,
+            channel_id,
+            message_id,
+            refund: operation.refund,
+        };
+        let id = (operation.federation_id, operation.operation_id);
+        match pending.insert(operation.operation_id, &record).await {
+            // Reporting a started operation again as background hands it over
+            Ok(()) if operation.background => started.retain(|started| *started != id),
+            Ok(()) => started.push(id),
+            Err(e) => error!(
+                "Cannot record pending operation {}: {:?}",
+                operation.operation_id, e
+            ),
+        }
+    }
+
+    started
+}
+
+// Replaces the deferred response with the command's result, splitting it over
+// several messages or attaching it as a file when it is too long for one
+async fn reply(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    ephemeral: bool,
+    content: String,
+) {
+    let prepared = prepare_reply(&command.data.name, content);
+    let late_pages = match &prepared {
+        Reply::Pages(pages) => pages.clone(),
+        Reply::Attachment { summary, .. } => vec![summary.clone()],
+    };
+    let (first, followups) = match prepared {
+        Reply::Pages(pages) => {
+            let mut pages = pages.into_iter();
+            let first = pages.next().unwrap_or_default();
+            let followups = pages.map(|page| (page, None)).collect::<Vec<_>>();
+            (first, followups)
+        }
+        Reply::Attachment {
+            summary,
+            filename,
+            data,
+        } => (summary, vec![(String::new(), Some((filename, data)))]),
+    };
+
+    if let Err(why) = command
+        .edit_original_interaction_response(&ctx.http, |response| response.content(first))
+        .await
+    {
+        if is_expired(&why) {
+            deliver_late(ctx, command, ephemeral, &late_pages, None).await;
+        } else {
+            error!("Cannot respond to slash command: {:?}", why);
+        }
+        return;
+    }
+
+    for (content, file) in followups {
+        if let Err(why) = command
+            .create_followup_message(&ctx.http, |followup| {
+                if let Some((filename, data)) = file {
+                    followup.add_file(AttachmentType::Bytes {
+                        data: Cow::from(data),
+                        filename,
+                    });
+                }
+                if !content.is_empty() {
+                    followup.content(content);
+                }
+                followup.ephemeral(ephemeral)
+            })
+            .await
+        {
+            error!("Cannot send follow-up message: {:?}", why);
+        }
+    }
+}
+
+// Replaces the deferred response with an embed explaining why the command failed
+async fn reply_error(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    ephemeral: bool,
+    e: &CommandError,
+) {
+    let embed = e.embed(&command.data.name);
+    if let Err(why) = command
+        .edit_original_interaction_response(&ctx.http, |response| {
+            response
+                .content("")
+                .set_embed(embed.clone())
+                .components(|c| c)
+        })
+        .await
+    {
+        if is_expired(&why) {
+            deliver_late(ctx, command, ephemeral, &[], Some(embed)).await;
+        } else {
+            error!("Cannot respond to slash command: {:?}", why);
+        }
+    }
+}
+
+// Discord stops accepting responses to an interaction 15 minutes after it was
+// created
+fn is_expired(why: &SerenityError) -> bool {
+    match why {
+        SerenityError::Http(e) => matches!(e.status_code().map(|s| s.as_u16()), Some(401 | 404)),
+        _ => false,
+    }
+}
+
+// Posts the result of a command that outlived its interaction to the channel
+// it was run in, or to the user when only they were to see it
+async fn deliver_late(
+    ctx: &Context,
+    command: &ApplicationCommandInteraction,
+    ephemeral: bool,
+    pages: &[String],
+    embed: Option<CreateEmbed>,
+) {
+    let channel_id = if ephemeral {
+        match command.user.create_dm_channel(&ctx.http).await {
+            Ok(dm) => dm.id,
+            Err(why) => {
+                error!("Cannot message user {}: {:?}", command.user.id, why);
+                return;
+            }
+        }
+    } else {
+        command.channel_id
+    };
+    let intro = format!(
+        "<@{}> `{}` finished after its response expired",
+        command.user.id, command.data.name
+    );
+    if let Err(why) = pending::send(&ctx.http, channel_id, None, &intro, pages, embed).await {
+        error!(
+            "Cannot deliver the result of {}: {:?}",
+            command.data.name, why
+        );
+    }
 }
 
 // EventHandler implementation for Botimint
 #[async_trait]
 impl EventHandler for Botimint {
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if let Interaction::ApplicationCommand(command) = interaction {
-            info!("Received command interaction: {:#?}", command.data.name);
-
-            let content = match command.data.name.as_str() {
-                name if name.starts_with("cln_") => {
-                    cln::handle_run(name, &command.data, &self.cln_client).await
+        let mut command = match interaction {
+            Interaction::ApplicationCommand(command) => command,
+            Interaction::MessageComponent(component) => {
+                if let Some(command) = self.components.handle_component(&ctx, &component).await {
+                    info!("User {} confirmed {}", command.user.id, command.data.name);
+                    let botimint = self.clone();
+                    tokio::spawn(async move {
+                        botimint.complete_command(ctx, command, true).await;
+                    });
                 }
-                name if name.starts_with("fm_") => {
-                    fed::handle_run(name, &command.data, &self.fm_client).await
+                return;
+            }
+            Interaction::ModalSubmit(modal) => {
+                return self.components.handle_modal(&ctx, &modal).await;
+            }
+            Interaction::Autocomplete(mut autocomplete) => {
+                subcommands::flatten(&mut autocomplete.data);
+                // Suggestions show peers, channels and operations, so they
+                // are only given to users who could run the command
+                let (required, _) = requirements(&autocomplete.data.name);
+                let role_ids = autocomplete
+                    .member
+                    .as_ref()
+                    .map(|member| member.roles.as_slice())
+                    .unwrap_or_default();
+                let authorized = required.map_or(true, |required| {
+                    authorize(
+                        &autocomplete.data.name,
+                        required,
+                        autocomplete.user.id,
+                        role_ids,
+                    )
+                    .is_ok()
+                });
+                if !authorized {
+                    autocomplete::respond(&ctx, &autocomplete, "", Vec::new()).await;
+                } else if autocomplete.data.name.starts_with("cln_") {
+                    cln::autocomplete(&ctx, &autocomplete, &self.cln_nodes).await;
+                } else {
+                    fed::autocomplete(&ctx, &autocomplete, &self.federations).await;
                 }
-                _ => custom::handle_run(&command.data.name, &command.data).await,
-            };
+                return;
+            }
+            _ => return,
+        };
 
-            if let Err(why) = command
-                .create_interaction_response(&ctx.http, |response| {
-                    response
-                        .kind(InteractionResponseType::ChannelMessageWithSource)
-                        .interaction_response_data(|message| message.content(content))
-                })
-                .await
-            {
-                error!("Cannot respond to slash command: {:?}", why);
+        // From here on `/fm ln pay` is handled as `fm_ln_pay`, confirmations
+        // included, as they keep the interaction
+        subcommands::flatten(&mut command.data);
+        info!("Received command interaction: {:#?}", command.data.name);
+
+        let (required, needs_confirmation) = requirements(&command.data.name);
+        if let Some(required) = required {
+            if let Err(denied) = self.authorize(&command.data.name, required, &command) {
+                info!("Denied {} to user {}", command.data.name, command.user.id);
+                if let Err(why) = command
+                    .create_interaction_response(&ctx.http, |response| {
+                        response
+                            .kind(InteractionResponseType::ChannelMessageWithSource)
+                            .interaction_response_data(|message| {
+                                message.content(denied).ephemeral(true)
+                            })
+                    })
+                    .await
+                {
+                    error!("Cannot respond to slash command: {:?}", why);
+                }
+                return;
             }
         }
+
+        // Confirmation prompts are only shown to the user who has to answer them
+        let ephemeral =
+            needs_confirmation || AccountCommand::from(command.data.name.as_str()).is_ephemeral();
+
+        // Discord drops the interaction if it isn't answered within 3 seconds,
+        // so acknowledge it right away and finish the command in the background
+        if let Err(why) = command
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::DeferredChannelMessageWithSource)
+                    .interaction_response_data(|message| message.ephemeral(ephemeral))
+            })
+            .await
+        {
+            error!("Cannot defer slash command: {:?}", why);
+            return;
+        }
+
+        let botimint = self.clone();
+        tokio::spawn(async move {
+            if needs_confirmation {
+                botimint.request_confirmation(ctx, command).await;
+            } else {
+                botimint.complete_command(ctx, command, ephemeral).await;
+            }
+        });
     }
 
     async fn message(&self, ctx: Context, msg: Message) {
@@ -65,11 +523,28 @@ impl EventHandler for Botimint {
         }
     }
 
+    async fn reaction_add(&self, ctx: Context, reaction: Reaction) {
+        let ledger = self.federations.default_federation().ledger;
+        account::tip::handle_reaction(&ctx.http, &ledger, &reaction).await;
+    }
+
     async fn ready(&self, ctx: Context, ready: Ready) {
         info!("{} is connected!", ready.user.name);
 
-        cln::ready(&ctx).await;
-        fed::ready(&ctx).await;
-        custom::ready(&ctx).await;
+        let mut commands = Vec::new();
+        if !self.cln_nodes.is_empty() {
+            commands.extend(cln::trees());
+        }
+        commands.push(ln::tree());
+        commands.push(fed::tree());
+        commands.extend(account::commands());
+        commands.extend(custom::commands());
+        let mut report_command = CreateApplicationCommand::default();
+        report::register(&mut report_command);
+        commands.push(report_command);
+        let mut status_command = CreateApplicationCommand::default();
+        status::register(&mut status_command);
+        commands.push(status_command);
+        register_commands(&ctx.http, commands, &CONFIG.guild_ids).await;
     }
 }
@@ -0,0 +1,21 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::interaction::application_command::CommandDataOption;
+use serenity::model::id::UserId;
+
+use crate::commands::CommandResult;
+use crate::ledger::Ledger;
+
+pub async fn run(
+    _options: &[CommandDataOption],
+    ledger: &Ledger,
+    user_id: UserId,
+) -> CommandResult {
+    let balance = ledger.balance(user_id).await;
+    Ok(format!("Your balance is {}", balance).into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("balance")
+        .description("Show your ecash balance held by the bot")
+}
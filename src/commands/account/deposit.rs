@@ -0,0 +1,102 @@
+use std::str::FromStr;
+
+use fedimint_client::ClientArc;
+use fedimint_core::Amount;
+use fedimint_mint_client::OOBNotes;
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+use serenity::model::application::interaction::application_command::CommandDataOption;
+use serenity::model::id::UserId;
+
+use crate::commands::fed::ln::invoice::{create_invoice, credit_on_claim};
+use crate::commands::fed::mint::reissue::reissue_notes;
+use crate::commands::{
+    discord_command_options_to_map, CommandError, CommandOptionInfo, CommandResult,
+};
+use crate::ledger::Ledger;
+use crate::utils::get_option_as::get_option_as;
+
+pub async fn run(
+    options: &[CommandDataOption],
+    fm_client: &ClientArc,
+    ledger: &Ledger,
+    user_id: UserId,
+) -> CommandResult {
+    let options_map = discord_command_options_to_map(options);
+    let notes = get_option_as::<String>(&options_map, "notes")?;
+    let amount_msat = get_option_as::<u64>(&options_map, "amount_msat")?;
+
+    match (notes, amount_msat) {
+        (Some(notes), None) => {
+            let notes = OOBNotes::from_str(&notes)
+                .map_err(|e| CommandError::invalid_option("notes", "OOBNotes", e))?;
+            let amount_msat = reissue_notes(fm_client, notes).await?;
+            let balance = ledger.credit(user_id, amount_msat).await?;
+            Ok(format!("Deposited {}, your balance is {}", amount_msat, balance).into())
+        }
+        (None, Some(amount_msat)) => {
+            let amount_msat = Amount::from_msats(amount_msat);
+            let description = get_option_as::<String>(&options_map, "description")?
+                .unwrap_or_else(|| "Botimint deposit".to_string());
+            let res = create_invoice(fm_client, amount_msat, description, 3600).await?;
+            credit_on_claim(
+                fm_client.clone(),
+                ledger.clone(),
+                user_id,
+                res.operation_id,
+                amount_msat,
+            )
+            .await?;
+            Ok(format!(
+                "Pay this invoice to deposit {}:\n```\n{}\n```",
+                amount_msat, res.invoice
+            )
+            .into())
+        }
+        (Some(_), Some(_)) => Err(CommandError::ConflictingOptions(vec![
+            "notes".to_string(),
+            "amount_msat".to_string(),
+        ])),
+        (None, None) => Err(CommandError::MissingOneOf(vec![
+            "notes".to_string(),
+            "amount_msat".to_string(),
+        ])),
+    }
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![
+        CommandOptionInfo {
+            name: "amount_msat",
+            description: "The amount in msat to deposit over lightning",
+            kind: CommandOptionType::Integer,
+            required: false,
+        },
+        CommandOptionInfo {
+            name: "description",
+            description: "The description for the deposit invoice",
+            kind: CommandOptionType::String,
+            required: false,
+        },
+        CommandOptionInfo {
+            name: "notes",
+            description: "Ecash notes to deposit",
+            kind: CommandOptionType::String,
+            required: false,
+        },
+    ];
+    command
+        .name("deposit")
+        .description("Deposit to your balance with a lightning invoice or ecash notes");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
@@ -0,0 +1,85 @@
+use fedimint_client::ClientArc;
+use serenity::builder::CreateApplicationCommand;
+use serenity::http::Http;
+use serenity::model::id::UserId;
+use serenity::model::prelude::application_command::CommandData;
+
+use crate::commands::CommandResult;
+use crate::ledger::Ledger;
+use crate::utils::progress::Progress;
+
+pub mod balance;
+pub mod deposit;
+pub mod tip;
+pub mod withdraw;
+
+pub enum AccountCommand {
+    Balance,
+    Deposit,
+    Withdraw,
+    Tip,
+    TipMessage,
+    Unknown,
+}
+
+impl From<&str> for AccountCommand {
+    fn from(s: &str) -> Self {
+        match s {
+            "balance" => Self::Balance,
+            "deposit" => Self::Deposit,
+            "withdraw" => Self::Withdraw,
+            "tip" => Self::Tip,
+            "Tip this message" => Self::TipMessage,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl AccountCommand {
+    // Balances and withdrawn ecash notes are only shown to the invoking user
+    pub fn is_ephemeral(&self) -> bool {
+        matches!(self, Self::Balance | Self::Withdraw)
+    }
+}
+
+pub fn commands() -> Vec<CreateApplicationCommand> {
+    let registers = vec![
+        balance::register,
+        deposit::register,
+        withdraw::register,
+        tip::register,
+        tip::register_message,
+    ];
+
+    registers
+        .into_iter()
+        .map(|register| {
+            let mut command = CreateApplicationCommand::default();
+            register(&mut command);
+            command
+        })
+        .collect()
+}
+
+pub async fn handle_run(
+    command_name: &str,
+    command_data: &CommandData,
+    http: &Http,
+    fm_client: &ClientArc,
+    ledger: &Ledger,
+    user_id: UserId,
+    progress: &Progress,
+) -> CommandResult {
+    match AccountCommand::from(command_name) {
+        AccountCommand::Balance => balance::run(&command_data.options, ledger, user_id).await,
+        AccountCommand::Deposit => {
+            deposit::run(&command_data.options, fm_client, ledger, user_id).await
+        }
+        AccountCommand::Withdraw => {
+            withdraw::run(&command_data.options, fm_client, ledger, user_id, progress).await
+        }
+        AccountCommand::Tip => tip::run(&command_data.options, http, ledger, user_id).await,
+        AccountCommand::TipMessage => tip::run_message(command_data, http, ledger, user_id).await,
+        AccountCommand::Unknown => Ok(format!("Unknown command: {}", command_name).into()),
+    }
+}
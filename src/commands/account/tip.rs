@@ -0,0 +1,218 @@
+use anyhow::bail;
+use fedimint_core::Amount;
+use serenity::builder::CreateApplicationCommand;
+use serenity::http::Http;
+use serenity::model::application::command::{CommandOptionType, CommandType};
+use serenity::model::application::interaction::application_command::{
+    CommandData, CommandDataOption, CommandDataOptionValue,
+};
+use serenity::model::channel::{Reaction, ReactionType};
+use serenity::model::id::UserId;
+use tracing::{error, info};
+
+use crate::commands::{
+    discord_command_options_to_map, CommandError, CommandOptionInfo, CommandResult,
+};
+use crate::config::CONFIG;
+use crate::ledger::Ledger;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
+
+pub async fn run(
+    options: &[CommandDataOption],
+    http: &Http,
+    ledger: &Ledger,
+    user_id: UserId,
+) -> CommandResult {
+    let options_map = discord_command_options_to_map(options);
+    let recipient = options
+        .iter()
+        .find(|opt| opt.name == "user")
+        .and_then(|opt| opt.resolved.as_ref());
+    let recipient = match recipient {
+        Some(CommandDataOptionValue::User(user, _member)) => user,
+        _ => return Err(CommandError::MissingOption("user".to_string())),
+    };
+    if recipient.bot {
+        return Err(CommandError::invalid_option(
+            "user",
+            "user",
+            "bots can't receive tips",
+        ));
+    }
+    let amount_msat = get_required_option_as::<u64>(&options_map, "amount_msat")?;
+    let amount_msat = Amount::from_msats(amount_msat);
+    let memo = get_option_as::<String>(&options_map, "memo")?;
+
+    let announcement = tip(
+        http,
+        ledger,
+        user_id,
+        recipient.id,
+        amount_msat,
+        memo.as_deref(),
+    )
+    .await?;
+
+    Ok(announcement.into())
+}
+
+// Handles the "Tip this message" context menu, tipping the message author the
+// configured fixed amount
+pub async fn run_message(
+    command_data: &CommandData,
+    http: &Http,
+    ledger: &Ledger,
+    user_id: UserId,
+) -> CommandResult {
+    let message = command_data.target_id.and_then(|target_id| {
+        command_data
+            .resolved
+            .messages
+            .get(&target_id.to_message_id())
+    });
+    let message = match message {
+        Some(message) => message,
+        None => return Err(CommandError::MissingOption("message".to_string())),
+    };
+    if message.author.bot {
+        return Err(CommandError::invalid_option(
+            "message",
+            "message",
+            "bots can't receive tips",
+        ));
+    }
+    let amount_msat = Amount::from_msats(CONFIG.tip_message_amount_msat);
+
+    let announcement = tip(http, ledger, user_id, message.author.id, amount_msat, None).await?;
+
+    Ok(announcement.into())
+}
+
+// Tips the author of a message when someone reacts with one of the configured
+// tip emojis
+pub async fn handle_reaction(http: &Http, ledger: &Ledger, reaction: &Reaction) {
+    let emoji = match &reaction.emoji {
+        ReactionType::Unicode(emoji) => emoji.as_str(),
+        ReactionType::Custom {
+            name: Some(name), ..
+        } => name.as_str(),
+        _ => return,
+    };
+    let amount_msat = match CONFIG.tip_reactions.get(emoji) {
+        Some(amount_msat) => Amount::from_msats(*amount_msat),
+        None => return,
+    };
+    let tipper = match reaction.user_id {
+        Some(tipper) => tipper,
+        None => return,
+    };
+    let message = match reaction.message(http).await {
+        Ok(message) => message,
+        Err(why) => {
+            error!("Cannot fetch tipped message: {:?}", why);
+            return;
+        }
+    };
+    if message.author.bot || message.author.id == tipper {
+        return;
+    }
+
+    info!(
+        "Reaction tip {} from {} to {}",
+        emoji, tipper, message.author.id
+    );
+    match tip(http, ledger, tipper, message.author.id, amount_msat, None).await {
+        Ok(announcement) => {
+            if let Err(why) = reaction.channel_id.say(http, announcement).await {
+                error!("Error sending message: {:?}", why);
+            }
+        }
+        Err(e) => {
+            let content = format!(
+                "Your {} tip to <@{}> failed: {}",
+                amount_msat, message.author.id, e
+            );
+            direct_message(http, tipper, content).await;
+        }
+    }
+}
+
+/// Moves `amount_msat` between the users' balances, lets the recipient know
+/// with a DM and returns the announcement for the channel
+async fn tip(
+    http: &Http,
+    ledger: &Ledger,
+    from: UserId,
+    to: UserId,
+    amount_msat: Amount,
+    memo: Option<&str>,
+) -> anyhow::Result<String> {
+    if amount_msat == Amount::ZERO {
+        bail!("Tip amount must be greater than zero");
+    }
+    ledger.transfer(from, to, amount_msat).await?;
+
+    let memo = memo.map(|memo| format!(": {}", memo)).unwrap_or_default();
+    direct_message(
+        http,
+        to,
+        format!("<@{}> tipped you {}{}", from, amount_msat, memo),
+    )
+    .await;
+
+    Ok(format!(
+        "<@{}> tipped <@{}> {}{}",
+        from, to, amount_msat, memo
+    ))
+}
+
+async fn direct_message(http: &Http, user_id: UserId, content: String) {
+    let res = match user_id.create_dm_channel(http).await {
+        Ok(channel) => channel.say(http, content).await.map(|_| ()),
+        Err(why) => Err(why),
+    };
+    if let Err(why) = res {
+        error!("Cannot send direct message to {}: {:?}", user_id, why);
+    }
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![
+        CommandOptionInfo {
+            name: "user",
+            description: "The user to tip",
+            kind: CommandOptionType::User,
+            required: true,
+        },
+        CommandOptionInfo {
+            name: "amount_msat",
+            description: "The amount in msat to tip",
+            kind: CommandOptionType::Integer,
+            required: true,
+        },
+        CommandOptionInfo {
+            name: "memo",
+            description: "A note to send along with the tip",
+            kind: CommandOptionType::String,
+            required: false,
+        },
+    ];
+    command
+        .name("tip")
+        .description("Tip another user from your balance");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
+
+pub fn register_message(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command.name("Tip this message").kind(CommandType::Message)
+}
@@ -0,0 +1,108 @@
+use fedimint_client::ClientArc;
+use fedimint_core::Amount;
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+use serenity::model::application::interaction::application_command::CommandDataOption;
+use serenity::model::id::UserId;
+
+use crate::commands::fed::ln::pay::pay_from_balance;
+use crate::commands::fed::ln::LnPayRequest;
+use crate::commands::fed::mint::spend::spend_notes;
+use crate::commands::{
+    discord_command_options_to_map, CommandError, CommandOptionInfo, CommandResult,
+};
+use crate::ledger::Ledger;
+use crate::utils::get_option_as::get_option_as;
+use crate::utils::progress::Progress;
+use crate::utils::to_codeblock;
+
+// Withdrawn notes that are never redeemed return to the bot's wallet after a
+// week
+const NOTES_TIMEOUT_SECS: u64 = 60 * 60 * 24 * 7;
+
+pub async fn run(
+    options: &[CommandDataOption],
+    fm_client: &ClientArc,
+    ledger: &Ledger,
+    user_id: UserId,
+    progress: &Progress,
+) -> CommandResult {
+    let options_map = discord_command_options_to_map(options);
+    let payment_info = get_option_as::<String>(&options_map, "payment_info")?;
+    let amount_msat = get_option_as::<u64>(&options_map, "amount_msat")?.map(Amount::from_msats);
+
+    match (payment_info, amount_msat) {
+        (Some(payment_info), amount_msat) => {
+            let req = LnPayRequest {
+                payment_info,
+                amount_msat,
+                finish_in_background: false,
+                lnurl_comment: get_option_as::<String>(&options_map, "lnurl_comment")?,
+            };
+            Ok(pay_from_balance(fm_client, ledger, user_id, req, progress)
+                .await?
+                .into())
+        }
+        (None, Some(amount_msat)) => {
+            let allow_overpay =
+                get_option_as::<bool>(&options_map, "allow_overpay")?.unwrap_or(false);
+            let res = spend_notes(
+                fm_client,
+                ledger,
+                user_id,
+                amount_msat,
+                allow_overpay,
+                NOTES_TIMEOUT_SECS,
+            )
+            .await?;
+            Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+        }
+        (None, None) => Err(CommandError::MissingOneOf(vec![
+            "payment_info".to_string(),
+            "amount_msat".to_string(),
+        ])),
+    }
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![
+        CommandOptionInfo {
+            name: "payment_info",
+            description: "The invoice, lnurl or lightning address to pay",
+            kind: CommandOptionType::String,
+            required: false,
+        },
+        CommandOptionInfo {
+            name: "amount_msat",
+            description: "The amount in msat to withdraw",
+            kind: CommandOptionType::Integer,
+            required: false,
+        },
+        CommandOptionInfo {
+            name: "lnurl_comment",
+            description: "The comment to use for the lnurl",
+            kind: CommandOptionType::String,
+            required: false,
+        },
+        CommandOptionInfo {
+            name: "allow_overpay",
+            description: "Allow withdrawing more ecash than requested",
+            kind: CommandOptionType::Boolean,
+            required: false,
+        },
+    ];
+    command
+        .name("withdraw")
+        .description("Withdraw from your balance over lightning or as ecash notes");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
@@ -0,0 +1,419 @@
+//! Suggestions for options that take ids users would otherwise copy from
+//! another command's output: peers, channels, invoice labels, operations and
+//! gateways, each shown with a label a human can pick from
+
+use std::collections::HashMap;
+use std::time::UNIX_EPOCH;
+
+use cln_rpc::model::requests::{
+    ListinvoicesRequest, ListnodesRequest, ListpeerchannelsRequest, ListpeersRequest,
+};
+use cln_rpc::{Request, Response};
+use fedimint_client::ClientArc;
+use fedimint_ln_client::LightningClientModule;
+use serde_json::{json, Value};
+use serenity::model::application::interaction::application_command::CommandDataOption;
+use serenity::model::application::interaction::autocomplete::AutocompleteInteraction;
+use serenity::prelude::Context;
+use time::OffsetDateTime;
+use tracing::error;
+
+use crate::lightning::ClnClient;
+use crate::utils::display_value;
+
+// Discord shows at most 25 choices, with names and values of up to 100
+// characters
+const MAX_CHOICES: usize = 25;
+const MAX_CHOICE_LEN: usize = 100;
+
+/// A value to suggest and the label Discord shows for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice {
+    pub label: String,
+    pub value: String,
+}
+
+impl Choice {
+    pub fn new(label: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            value: value.into(),
+        }
+    }
+}
+
+/// The option the user is typing in and what they typed so far
+pub fn focused(options: &[CommandDataOption]) -> Option<(&str, &str)> {
+    options.iter().find(|opt| opt.focused).map(|opt| {
+        let typed = opt.value.as_ref().and_then(Value::as_str);
+        (opt.name.as_str(), typed.unwrap_or_default())
+    })
+}
+
+/// The choices whose label or value contains what was typed, as many as
+/// Discord shows. Values too long for Discord are left out, as they couldn't be
+/// sent back intact.
+pub fn matching(choices: Vec<Choice>, typed: &str) -> Vec<Choice> {
+    let typed = typed.to_lowercase();
+    choices
+        .into_iter()
+        .filter(|choice| choice.value.len() <= MAX_CHOICE_LEN)
+        .filter(|choice| {
+            choice.label.to_lowercase().contains(&typed)
+                || choice.value.to_lowercase().contains(&typed)
+        })
+        .take(MAX_CHOICES)
+        .map(|choice| Choice {
+            label: choice.label.chars().take(MAX_CHOICE_LEN).collect(),
+            value: choice.value,
+        })
+        .collect()
+}
+
+/// Answers the autocomplete request with the choices matching `typed`
+pub async fn respond(
+    ctx: &Context,
+    autocomplete: &AutocompleteInteraction,
+    typed: &str,
+    choices: Vec<Choice>,
+) {
+    let choices = matching(choices, typed);
+    if let Err(why) = autocomplete
+        .create_autocomplete_response(&ctx.http, |response| {
+            for choice in choices {
+                response.add_string_choice(choice.label, choice.value);
+            }
+            response
+        })
+        .await
+    {
+        error!("Cannot respond to autocomplete: {:?}", why);
+    }
+}
+
+// Node and operation ids are long, so labels show enough of them to tell apart
+fn short(id: &str) -> String {
+    match id.get(..12) {
+        Some(prefix) if prefix.len() < id.len() => format!("{}…", prefix),
+        _ => id.to_string(),
+    }
+}
+
+// Suggestions are best effort, so a failed call suggests nothing
+async fn call(client: &ClnClient, req: Request) -> Option<Response> {
+    match client.call(req).await {
+        Ok(res) => Some(res),
+        Err(e) => {
+            error!("Cannot fetch autocomplete choices: {}", e);
+            None
+        }
+    }
+}
+
+// The aliases of the nodes in the graph by id, fetched in one call rather than
+// one per peer
+async fn aliases(client: &ClnClient) -> HashMap<String, String> {
+    let req = ListnodesRequest { id: None };
+    let nodes = match call(client, Request::ListNodes(req)).await {
+        Some(Response::ListNodes(res)) => {
+            json!(res)["nodes"].as_array().cloned().unwrap_or_default()
+        }
+        _ => return HashMap::new(),
+    };
+
+    nodes
+        .iter()
+        .filter_map(|node| {
+            let alias = node["alias"].as_str()?;
+            Some((display_value(&node["nodeid"]), alias.to_string()))
+        })
+        .collect()
+}
+
+async fn list_peers(client: &ClnClient) -> Vec<Value> {
+    let req = ListpeersRequest {
+        id: None,
+        level: None,
+    };
+    match call(client, Request::ListPeers(req)).await {
+        Some(Response::ListPeers(res)) => {
+            json!(res)["peers"].as_array().cloned().unwrap_or_default()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The node's peers, by id
+pub async fn cln_peers(client: &ClnClient) -> Vec<Choice> {
+    let peers = list_peers(client).await;
+    let aliases = aliases(client).await;
+
+    peers
+        .iter()
+        .map(|peer| {
+            let id = display_value(&peer["id"]);
+            let state = if peer["connected"].as_bool().unwrap_or_default() {
+                "connected"
+            } else {
+                "disconnected"
+            };
+            let label = match aliases.get(&id) {
+                Some(alias) => format!("{} ({}, {})", alias, state, short(&id)),
+                None => format!("{} ({})", id, state),
+            };
+            Choice::new(label, id)
+        })
+        .collect()
+}
+
+/// The connection strings of the peers whose address the node knows, to
+/// reconnect to them
+pub async fn cln_peer_addresses(client: &ClnClient) -> Vec<Choice> {
+    let peers = list_peers(client).await;
+    let aliases = aliases(client).await;
+
+    peers
+        .iter()
+        .filter_map(|peer| {
+            let id = display_value(&peer["id"]);
+            let addr = peer["netaddr"][0].as_str()?;
+            let connection_string = format!("{}@{}", id, addr);
+            let label = match aliases.get(&id) {
+                Some(alias) => format!("{} at {}", alias, addr),
+                None => connection_string.clone(),
+            };
+            Some(Choice::new(label, connection_string))
+        })
+        .collect()
+}
+
+/// The node's channels, by short channel id or, before they are confirmed,
+/// channel id
+pub async fn cln_channels(client: &ClnClient) -> Vec<Choice> {
+    let req = ListpeerchannelsRequest { id: None };
+    let channels = match call(client, Request::ListPeerChannels(req)).await {
+        Some(Response::ListPeerChannels(res)) => json!(res)["channels"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default(),
+        _ => return Vec::new(),
+    };
+
+    channels
+        .iter()
+        .filter_map(|channel| {
+            let id = channel["short_channel_id"]
+                .as_str()
+                .or_else(|| channel["channel_id"].as_str())?;
+            let label = format!(
+                "{} with {}, {} of {} msat ours ({})",
+                id,
+                short(&display_value(&channel["peer_id"])),
+                display_value(&channel["to_us_msat"]),
+                display_value(&channel["total_msat"]),
+                display_value(&channel["state"]),
+            );
+            Some(Choice::new(label, id))
+        })
+        .collect()
+}
+
+/// The labels of the node's invoices, newest first
+pub async fn cln_invoice_labels(client: &ClnClient) -> Vec<Choice> {
+    let req = ListinvoicesRequest {
+        label: None,
+        invstring: None,
+        payment_hash: None,
+        offer_id: None,
+        index: None,
+        start: None,
+        limit: None,
+    };
+    let invoices = match call(client, Request::ListInvoices(req)).await {
+        Some(Response::ListInvoices(res)) => json!(res)["invoices"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default(),
+        _ => return Vec::new(),
+    };
+
+    invoices
+        .iter()
+        .rev()
+        .filter_map(|invoice| {
+            let label = invoice["label"].as_str()?;
+            let description = invoice["description"].as_str().unwrap_or_default();
+            let amount = match invoice["amount_msat"].as_u64() {
+                Some(amount_msat) => format!("{} msat", amount_msat),
+                None => "any amount".to_string(),
+            };
+            Some(Choice::new(
+                format!(
+                    "{}: {}, {} {}",
+                    label,
+                    display_value(&invoice["status"]),
+                    amount,
+                    description
+                )
+                .trim_end()
+                .to_string(),
+                label,
+            ))
+        })
+        .collect()
+}
+
+/// The client's latest operations of the `module` kind, e.g. "ln" or
+/// "wallet", newest first
+pub async fn fm_operations(fm_client: &ClientArc, module: &str) -> Vec<Choice> {
+    fm_client
+        .operation_log()
+        .list_operations(MAX_CHOICES * 4, None)
+        .await
+        .into_iter()
+        .filter(|(_, operation)| operation.operation_module_kind() == module)
+        .map(|(key, _)| {
+            let created = key
+                .creation_time
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .and_then(|d| OffsetDateTime::from_unix_timestamp(d.as_secs() as i64).ok())
+                .map(|time| {
+                    format!(
+                        "{}-{:02}-{:02} {:02}:{:02}",
+                        time.year(),
+                        time.month() as u8,
+                        time.day(),
+                        time.hour(),
+                        time.minute()
+                    )
+                })
+                .unwrap_or_default();
+            let id = key.operation_id.to_string();
+            Choice::new(
+                format!("{} {} operation {}", created, module, short(&id)),
+                id,
+            )
+        })
+        .collect()
+}
+
+/// The gateways registered with the federation, by gateway id
+pub async fn fm_gateways(fm_client: &ClientArc) -> Vec<Choice> {
+    let gateways = match fm_client
+        .get_first_module::<LightningClientModule>()
+        .fetch_registered_gateways()
+        .await
+    {
+        Ok(gateways) => json!(gateways),
+        Err(e) => {
+            error!("Cannot fetch autocomplete choices: {:?}", e);
+            return Vec::new();
+        }
+    };
+
+    gateways
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|gateway| {
+            // Announcements wrap the gateway's info
+            let info = gateway.get("info").unwrap_or(gateway);
+            let id = info["gateway_id"].as_str()?;
+            let label = format!(
+                "{} at {} ({})",
+                display_value(&info["lightning_alias"]),
+                display_value(&info["api"]),
+                id
+            );
+            Some(Choice::new(label, id))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::cln::MockCln;
+
+    #[test]
+    fn matches_labels_and_values() {
+        let choices = vec![
+            Choice::new("alice (connected)", "02aa"),
+            Choice::new("bob (connected)", "03bb"),
+            Choice::new("carol", "x".repeat(MAX_CHOICE_LEN + 1)),
+        ];
+
+        assert_eq!(matching(choices.clone(), "ALICE"), vec![choices[0].clone()]);
+        assert_eq!(matching(choices.clone(), "03"), vec![choices[1].clone()]);
+        assert_eq!(matching(choices, "").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn peers_are_labelled_with_their_alias() {
+        let id = "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619";
+        let (_cln, client) = MockCln::new()
+            .respond(
+                "listpeers",
+                json!({ "peers": [{
+                    "id": id,
+                    "connected": true,
+                    "num_channels": 1,
+                    "netaddr": ["127.0.0.1:9735"],
+                }] }),
+            )
+            .respond(
+                "listnodes",
+                json!({ "nodes": [{ "nodeid": id, "alias": "alice" }] }),
+            )
+            .start()
+            .await;
+
+        assert_eq!(
+            cln_peers(&client).await,
+            vec![Choice::new("alice (connected, 02eec7245d6b…)", id)]
+        );
+        assert_eq!(
+            cln_peer_addresses(&client).await,
+            vec![Choice::new(
+                "alice at 127.0.0.1:9735",
+                format!("{}@127.0.0.1:9735", id)
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn every_peer_can_be_found() {
+        use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+        let secp = Secp256k1::new();
+        let ids = (1..=MAX_CHOICES as u8 + 5)
+            .map(|i| {
+                let key = SecretKey::from_slice(&[i; 32]).unwrap();
+                PublicKey::from_secret_key(&secp, &key).to_string()
+            })
+            .collect::<Vec<_>>();
+        let peers = ids
+            .iter()
+            .map(|id| json!({ "id": id, "connected": true, "num_channels": 1 }))
+            .collect::<Vec<_>>();
+        let nodes = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| json!({ "nodeid": id, "alias": format!("peer-{:02}", i) }))
+            .collect::<Vec<_>>();
+        let (cln, client) = MockCln::new()
+            .respond("listpeers", json!({ "peers": peers }))
+            .respond("listnodes", json!({ "nodes": nodes }))
+            .start()
+            .await;
+
+        let choices = cln_peers(&client).await;
+        assert_eq!(choices.len(), ids.len());
+        let found = matching(choices, "peer-29");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].value, ids[29]);
+        assert_eq!(cln.requests("listnodes").len(), 1);
+    }
+}
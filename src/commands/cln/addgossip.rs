@@ -1,26 +1,20 @@
-use std::sync::Arc;
-
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::AddGossip;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::get_required_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let message: String = get_option_as(&options_map, "message").unwrap();
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let message: String = get_required_option_as(options, "message")?;
 
     let req = cln_rpc::model::requests::AddgossipRequest { message };
 
-    match cln_client.lock().await.call(AddGossip(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(AddGossip(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,22 +1,17 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::PublicKey;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::CheckMessage;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let message: String = get_option_as(&options_map, "message").unwrap();
-    let zbase: String = get_option_as(&options_map, "zbase").unwrap();
-    let pubkey: Option<PublicKey> = get_option_as(&options_map, "pubkey");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let message: String = get_required_option_as(options, "message")?;
+    let zbase: String = get_required_option_as(options, "zbase")?;
+    let pubkey: Option<PublicKey> = get_option_as(options, "pubkey")?;
 
     let req = cln_rpc::model::requests::CheckmessageRequest {
         message,
@@ -24,10 +19,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         pubkey,
     };
 
-    match cln_client.lock().await.call(CheckMessage(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(CheckMessage(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,26 +1,23 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::{Feerate, Outpoint};
-use cln_rpc::ClnRpc;
-use cln_rpc::Request::Close;
+use cln_rpc::Request::{Close, ListPeerChannels};
+use cln_rpc::Response;
+use serde_json::json;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
+use crate::utils::{display_value, format_json};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let id: String = get_option_as(&options_map, "id").unwrap();
-    let unilateraltimeout: Option<u32> = get_option_as(&options_map, "unilateraltimeout");
-    let destination: Option<String> = get_option_as(&options_map, "destination");
-    let fee_negotiation_step: Option<String> = get_option_as(&options_map, "fee_negotiation_step");
-    let wrong_funding: Option<Outpoint> = get_option_as(&options_map, "wrong_funding");
-    let force_lease_closed: Option<bool> = get_option_as(&options_map, "force_lease_closed");
-    let feerange: Option<Vec<Feerate>> = get_option_as(&options_map, "feerange");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let id: String = get_required_option_as(options, "id")?;
+    let unilateraltimeout: Option<u32> = get_option_as(options, "unilateraltimeout")?;
+    let destination: Option<String> = get_option_as(options, "destination")?;
+    let fee_negotiation_step: Option<String> = get_option_as(options, "fee_negotiation_step")?;
+    let wrong_funding: Option<Outpoint> = get_option_as(options, "wrong_funding")?;
+    let force_lease_closed: Option<bool> = get_option_as(options, "force_lease_closed")?;
+    let feerange: Option<Vec<Feerate>> = get_option_as(options, "feerange")?;
 
     let req = cln_rpc::model::requests::CloseRequest {
         id,
@@ -32,10 +29,53 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         feerange,
     };
 
-    match cln_client.lock().await.call(Close(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
+    let res = cln_client.call(Close(req)).await?;
+
+    Ok(format_json(res).into())
+}
+
+pub async fn summarize(
+    options: &CommandOptions,
+    cln_client: &ClnClient,
+) -> Result<String, CommandError> {
+    let id: String = get_required_option_as(options, "id")?;
+    let destination: Option<String> = get_option_as(options, "destination")?;
+
+    // The id can be a peer id, channel id or short channel id
+    let req = cln_rpc::model::requests::ListpeerchannelsRequest { id: None };
+    let channel = match cln_client.call(ListPeerChannels(req)).await? {
+        Response::ListPeerChannels(res) => json!(res)["channels"]
+            .as_array()
+            .and_then(|channels| {
+                channels.iter().find(|channel| {
+                    ["peer_id", "channel_id", "short_channel_id"]
+                        .iter()
+                        .any(|key| channel[key].as_str() == Some(id.as_str()))
+                })
+            })
+            .cloned(),
+        _ => None,
+    };
+
+    let mut summary = match channel {
+        Some(channel) => format!(
+            "Close channel `{}` with peer `{}`\nState: {}\nOur balance: {}\nCapacity: {}",
+            display_value(&channel["short_channel_id"]),
+            display_value(&channel["peer_id"]),
+            display_value(&channel["state"]),
+            display_value(&channel["to_us_msat"]),
+            display_value(&channel["total_msat"])
+        ),
+        None => format!(
+            "Close the channel with `{}`\nNo channel matching this id was found.",
+            id
+        ),
+    };
+    if let Some(destination) = destination {
+        summary.push_str(&format!("\nFunds are sent to `{}`", destination));
     }
+
+    Ok(summary)
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -94,6 +134,7 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
                 .description(opt_info.description)
                 .kind(opt_info.kind)
                 .required(opt_info.required)
+                .set_autocomplete(opt_info.name == "id")
         });
     }
 
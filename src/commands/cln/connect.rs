@@ -1,35 +1,36 @@
 use std::str::FromStr;
-use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use cln_rpc::primitives::PublicKey;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::Connect;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::discord_command_options_to_map;
+use crate::commands::{CommandError, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::get_required_option_as;
 
-struct ConnectionString {
-    id: String,
-    host: String,
-    port: u16,
+pub struct ConnectionString {
+    pub id: String,
+    pub host: String,
+    pub port: u16,
 }
 
 impl ConnectionString {
-    fn from_string(s: &str) -> Result<ConnectionString> {
-        let mut parts = s.split('@');
-        let id = parts.next().unwrap().to_string();
-        assert!(PublicKey::from_str(&id).is_ok());
-        let mut parts = parts.next().unwrap().split(':');
-        let host = parts.next().unwrap().to_string();
-        let port = parts.next().unwrap().parse::<u16>()?;
+    pub fn from_string(s: &str) -> Result<ConnectionString> {
+        let (id, address) = s.split_once('@').context("expected <id>@<host>:<port>")?;
+        PublicKey::from_str(id).context("invalid node id")?;
+        let (host, port) = address
+            .split_once(':')
+            .context("expected <id>@<host>:<port>")?;
+        let port = port.parse::<u16>().context("invalid port")?;
 
-        Ok(ConnectionString { id, host, port })
+        Ok(ConnectionString {
+            id: id.to_string(),
+            host: host.to_string(),
+            port,
+        })
     }
 
     // fn to_string(&self) -> String {
@@ -37,24 +38,19 @@ impl ConnectionString {
     // }
 }
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let connection_string: Option<String> = get_option_as(&options_map, "connection_string");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let connection_string: String = get_required_option_as(options, "connection_string")?;
+    let cs = ConnectionString::from_string(&connection_string)
+        .map_err(|e| CommandError::invalid_option("connection_string", "connection string", e))?;
 
-    match ConnectionString::from_string(&connection_string.unwrap()) {
-        Ok(cs) => {
-            let req = cln_rpc::model::requests::ConnectRequest {
-                id: cs.id,
-                host: Some(cs.host),
-                port: Some(cs.port),
-            };
-            let res = cln_client.lock().await.call(Connect(req)).await.unwrap();
-            format_json(res)
-        }
-        Err(e) => {
-            format!("Error: {}", e)
-        }
-    }
+    let req = cln_rpc::model::requests::ConnectRequest {
+        id: cs.id,
+        host: Some(cs.host),
+        port: Some(cs.port),
+    };
+    let res = cln_client.call(Connect(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -66,5 +62,6 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
                 .description("The connection string of the peer")
                 .kind(CommandOptionType::String)
                 .required(true)
+                .set_autocomplete(true)
         })
 }
@@ -1,33 +1,28 @@
-use std::sync::Arc;
-
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::CreateInvoice;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
+use crate::commands::{CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = crate::commands::discord_command_options_to_map(options);
-    let invstring: Option<String> =
-        crate::utils::get_option_as::get_option_as(&options_map, "invstring");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let invstring: String =
+        crate::utils::get_option_as::get_required_option_as(options, "invstring")?;
     // random label if not provided
-    let label: Option<String> = crate::utils::get_option_as::get_option_as(&options_map, "label")
-        .or(Some(format!("botimint-{}", uuid::Uuid::new_v4())));
-    let preimage: Option<String> =
-        crate::utils::get_option_as::get_option_as(&options_map, "preimage");
+    let label: String = crate::utils::get_option_as::get_option_as(options, "label")?
+        .unwrap_or_else(|| format!("botimint-{}", uuid::Uuid::new_v4()));
+    let preimage: String =
+        crate::utils::get_option_as::get_required_option_as(options, "preimage")?;
 
     let req = cln_rpc::model::requests::CreateinvoiceRequest {
-        invstring: invstring.unwrap(),
-        label: label.unwrap(),
-        preimage: preimage.unwrap(),
+        invstring,
+        label,
+        preimage,
     };
-    match cln_client.lock().await.call(CreateInvoice(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(CreateInvoice(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
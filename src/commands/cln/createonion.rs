@@ -1,24 +1,19 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::CreateonionHops;
 use cln_rpc::primitives::Secret;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::CreateOnion;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let hops: Vec<CreateonionHops> = get_option_as(&options_map, "hops").unwrap();
-    let assocdata: String = get_option_as(&options_map, "assocdata").unwrap();
-    let session_key: Option<Secret> = get_option_as(&options_map, "session_key");
-    let onion_size: Option<u16> = get_option_as(&options_map, "onion_size");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let hops: Vec<CreateonionHops> = get_required_option_as(options, "hops")?;
+    let assocdata: String = get_required_option_as(options, "assocdata")?;
+    let session_key: Option<Secret> = get_option_as(options, "session_key")?;
+    let onion_size: Option<u16> = get_option_as(options, "onion_size")?;
 
     let req = cln_rpc::model::requests::CreateonionRequest {
         hops,
@@ -27,10 +22,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         onion_size,
     };
 
-    match cln_client.lock().await.call(CreateOnion(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(CreateOnion(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,24 +1,19 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::DatastoreMode;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::Datastore;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let key: Vec<String> = get_option_as(&options_map, "key").unwrap();
-    let string: Option<String> = get_option_as(&options_map, "string");
-    let hex: Option<String> = get_option_as(&options_map, "hex");
-    let mode: Option<DatastoreMode> = get_option_as(&options_map, "mode");
-    let generation: Option<u64> = get_option_as(&options_map, "generation");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let key: Vec<String> = get_required_option_as(options, "key")?;
+    let string: Option<String> = get_option_as(options, "string")?;
+    let hex: Option<String> = get_option_as(options, "hex")?;
+    let mode: Option<DatastoreMode> = get_option_as(options, "mode")?;
+    let generation: Option<u64> = get_option_as(options, "generation")?;
 
     let req = cln_rpc::model::requests::DatastoreRequest {
         key,
@@ -28,10 +23,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         generation,
     };
 
-    match cln_client.lock().await.call(Datastore(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(Datastore(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
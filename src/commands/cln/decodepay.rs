@@ -1,28 +1,23 @@
-use std::sync::Arc;
-
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::DecodePay;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let bolt11: String = get_option_as(&options_map, "bolt11").unwrap();
-    let description: Option<String> = get_option_as(&options_map, "description");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let bolt11: String = get_required_option_as(options, "bolt11")?;
+    let description: Option<String> = get_option_as(options, "description")?;
 
     let req = cln_rpc::model::requests::DecodepayRequest {
         bolt11,
         description,
     };
-    let res = cln_client.lock().await.call(DecodePay(req)).await.unwrap();
+    let res = cln_client.call(DecodePay(req)).await?;
 
-    format_json(res)
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
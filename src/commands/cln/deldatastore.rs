@@ -1,27 +1,32 @@
-use std::sync::Arc;
-
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::DelDatastore;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let key: Vec<String> = get_option_as(&options_map, "key").unwrap();
-    let generation: Option<u64> = get_option_as(&options_map, "generation");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let key: Vec<String> = get_required_option_as(options, "key")?;
+    let generation: Option<u64> = get_option_as(options, "generation")?;
 
     let req = cln_rpc::model::requests::DeldatastoreRequest { key, generation };
 
-    match cln_client.lock().await.call(DelDatastore(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
+    let res = cln_client.call(DelDatastore(req)).await?;
+
+    Ok(format_json(res).into())
+}
+
+pub fn summarize(options: &CommandOptions) -> Result<String, CommandError> {
+    let key: Vec<String> = get_required_option_as(options, "key")?;
+    let generation: Option<u64> = get_option_as(options, "generation")?;
+
+    let mut summary = format!("Delete the datastore entry `{}`", key.join("/"));
+    if let Some(generation) = generation {
+        summary.push_str(&format!(" at generation {}", generation));
     }
+    Ok(summary)
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,22 +1,19 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::DelinvoiceStatus;
-use cln_rpc::ClnRpc;
-use cln_rpc::Request::DelInvoice;
+use cln_rpc::Request::{DelInvoice, ListInvoices};
+use cln_rpc::Response;
+use serde_json::json;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
+use crate::utils::{display_value, format_json};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let label: String = get_option_as(&options_map, "label").unwrap();
-    let status: DelinvoiceStatus = get_option_as(&options_map, "status").unwrap();
-    let desconly: Option<bool> = get_option_as(&options_map, "desconly");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let label: String = get_required_option_as(options, "label")?;
+    let status: DelinvoiceStatus = get_required_option_as(options, "status")?;
+    let desconly: Option<bool> = get_option_as(options, "desconly")?;
 
     let req = cln_rpc::model::requests::DelinvoiceRequest {
         label,
@@ -24,10 +21,51 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         desconly,
     };
 
-    match cln_client.lock().await.call(DelInvoice(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(DelInvoice(req)).await?;
+
+    Ok(format_json(res).into())
+}
+
+pub async fn summarize(
+    options: &CommandOptions,
+    cln_client: &ClnClient,
+) -> Result<String, CommandError> {
+    let label: String = get_required_option_as(options, "label")?;
+    let desconly: bool = get_option_as(options, "desconly")?.unwrap_or(false);
+
+    let req = cln_rpc::model::requests::ListinvoicesRequest {
+        label: Some(label.clone()),
+        invstring: None,
+        payment_hash: None,
+        offer_id: None,
+        index: None,
+        start: None,
+        limit: None,
+    };
+    let invoice = match cln_client.call(ListInvoices(req)).await? {
+        Response::ListInvoices(res) => res.invoices.into_iter().next().map(|i| json!(i)),
+        _ => None,
+    };
+
+    let action = if desconly {
+        "Remove the description of invoice"
+    } else {
+        "Delete invoice"
+    };
+    Ok(match invoice {
+        Some(invoice) => format!(
+            "{} `{}`\nStatus: {}\nAmount: {}\nDescription: {}",
+            action,
+            label,
+            display_value(&invoice["status"]),
+            display_value(&invoice["amount_msat"]),
+            display_value(&invoice["description"])
+        ),
+        None => format!(
+            "{} `{}`\nNo invoice with this label was found.",
+            action, label
+        ),
+    })
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -62,6 +100,7 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
                 .description(opt_info.description)
                 .kind(opt_info.kind)
                 .required(opt_info.required)
+                .set_autocomplete(opt_info.name == "label")
         });
     }
 
@@ -1,26 +1,21 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::PublicKey;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::Disconnect;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let id: PublicKey = get_option_as(&options_map, "id").unwrap();
-    let force: Option<bool> = get_option_as(&options_map, "force");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let id: PublicKey = get_required_option_as(options, "id")?;
+    let force: Option<bool> = get_option_as(options, "force")?;
 
     let req = cln_rpc::model::requests::DisconnectRequest { id, force };
-    let res = cln_client.lock().await.call(Disconnect(req)).await.unwrap();
+    let res = cln_client.call(Disconnect(req)).await?;
 
-    format_json(res)
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
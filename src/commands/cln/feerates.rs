@@ -1,25 +1,20 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::FeeratesStyle;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::Feerates;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::get_required_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let style: FeeratesStyle = get_option_as(&options_map, "style").unwrap();
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let style: FeeratesStyle = get_required_option_as(options, "style")?;
 
     let req = cln_rpc::model::requests::FeeratesRequest { style };
-    let res = cln_client.lock().await.call(Feerates(req)).await.unwrap();
+    let res = cln_client.call(Feerates(req)).await?;
 
-    format_json(res)
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,31 +1,26 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::{Amount, AmountOrAll, Feerate, Outpoint, PublicKey};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::FundChannel;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let id: PublicKey = get_option_as(&options_map, "id").unwrap();
-    let amount: AmountOrAll = get_option_as(&options_map, "amount").unwrap();
-    let feerate: Feerate = get_option_as(&options_map, "feerate").unwrap_or(Feerate::PerKb(1000));
-    let announce: Option<bool> = get_option_as(&options_map, "announce");
-    let minconf: Option<u32> = get_option_as(&options_map, "minconf");
-    let push_msat: Option<Amount> = get_option_as(&options_map, "push_msat");
-    let close_to: Option<String> = get_option_as(&options_map, "close_to");
-    let request_amt: Option<Amount> = get_option_as(&options_map, "request_amt");
-    let compact_lease: Option<String> = get_option_as(&options_map, "compact_lease");
-    let utxos: Option<Vec<Outpoint>> = get_option_as(&options_map, "utxos");
-    let mindepth: Option<u32> = get_option_as(&options_map, "mindepth");
-    let reserve: Option<Amount> = get_option_as(&options_map, "reserve");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let id: PublicKey = get_required_option_as(options, "id")?;
+    let amount: AmountOrAll = get_required_option_as(options, "amount")?;
+    let feerate: Feerate = get_option_as(options, "feerate")?.unwrap_or(Feerate::PerKb(1000));
+    let announce: Option<bool> = get_option_as(options, "announce")?;
+    let minconf: Option<u32> = get_option_as(options, "minconf")?;
+    let push_msat: Option<Amount> = get_option_as(options, "push_msat")?;
+    let close_to: Option<String> = get_option_as(options, "close_to")?;
+    let request_amt: Option<Amount> = get_option_as(options, "request_amt")?;
+    let compact_lease: Option<String> = get_option_as(options, "compact_lease")?;
+    let utxos: Option<Vec<Outpoint>> = get_option_as(options, "utxos")?;
+    let mindepth: Option<u32> = get_option_as(options, "mindepth")?;
+    let reserve: Option<Amount> = get_option_as(options, "reserve")?;
 
     let req = cln_rpc::model::requests::FundchannelRequest {
         id,
@@ -42,10 +37,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         reserve,
     };
 
-    match cln_client.lock().await.call(FundChannel(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(FundChannel(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,31 +1,25 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::FundpsbtRequest;
 use cln_rpc::primitives::{AmountOrAll, Feerate};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::FundPsbt;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let satoshi: AmountOrAll = get_option_as(&options_map, "satoshi").unwrap();
-    let feerate: Feerate = get_option_as(&options_map, "feerate").unwrap();
-    let startweight: u32 = get_option_as(&options_map, "startweight").unwrap();
-    let minconf: Option<u32> = get_option_as(&options_map, "minconf");
-    let reserve: Option<u32> = get_option_as(&options_map, "reserve");
-    let locktime: Option<u32> = get_option_as(&options_map, "locktime");
-    let min_witness_weight: Option<u32> = get_option_as(&options_map, "min_witness_weight");
-    let excess_as_change: Option<bool> = get_option_as(&options_map, "excess_as_change");
-    let nonwrapped: Option<bool> = get_option_as(&options_map, "nonwrapped");
-    let opening_anchor_channel: Option<bool> =
-        get_option_as(&options_map, "opening_anchor_channel");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let satoshi: AmountOrAll = get_required_option_as(options, "satoshi")?;
+    let feerate: Feerate = get_required_option_as(options, "feerate")?;
+    let startweight: u32 = get_required_option_as(options, "startweight")?;
+    let minconf: Option<u32> = get_option_as(options, "minconf")?;
+    let reserve: Option<u32> = get_option_as(options, "reserve")?;
+    let locktime: Option<u32> = get_option_as(options, "locktime")?;
+    let min_witness_weight: Option<u32> = get_option_as(options, "min_witness_weight")?;
+    let excess_as_change: Option<bool> = get_option_as(options, "excess_as_change")?;
+    let nonwrapped: Option<bool> = get_option_as(options, "nonwrapped")?;
+    let opening_anchor_channel: Option<bool> = get_option_as(options, "opening_anchor_channel")?;
 
     let req = FundpsbtRequest {
         satoshi,
@@ -40,10 +34,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         opening_anchor_channel,
     };
 
-    match cln_client.lock().await.call(FundPsbt(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(FundPsbt(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,27 +1,22 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::{Amount, PublicKey};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::GetRoute;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let id: PublicKey = get_option_as(&options_map, "id").unwrap();
-    let amount_msat: Amount = get_option_as(&options_map, "amount_msat").unwrap();
-    let riskfactor: u64 = get_option_as(&options_map, "riskfactor").unwrap();
-    let cltv: Option<u32> = get_option_as(&options_map, "cltv");
-    let fromid: Option<PublicKey> = get_option_as(&options_map, "fromid");
-    let fuzzpercent: Option<u32> = get_option_as(&options_map, "fuzzpercent");
-    let exclude: Option<Vec<String>> = get_option_as(&options_map, "exclude");
-    let maxhops: Option<u32> = get_option_as(&options_map, "maxhops");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let id: PublicKey = get_required_option_as(options, "id")?;
+    let amount_msat: Amount = get_required_option_as(options, "amount_msat")?;
+    let riskfactor: u64 = get_required_option_as(options, "riskfactor")?;
+    let cltv: Option<u32> = get_option_as(options, "cltv")?;
+    let fromid: Option<PublicKey> = get_option_as(options, "fromid")?;
+    let fuzzpercent: Option<u32> = get_option_as(options, "fuzzpercent")?;
+    let exclude: Option<Vec<String>> = get_option_as(options, "exclude")?;
+    let maxhops: Option<u32> = get_option_as(options, "maxhops")?;
 
     let req = cln_rpc::model::requests::GetrouteRequest {
         id,
@@ -33,9 +28,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         exclude,
         maxhops,
     };
-    let res = cln_client.lock().await.call(GetRoute(req)).await.unwrap();
+    let res = cln_client.call(GetRoute(req)).await?;
 
-    format_json(res)
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,27 +1,22 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::AmountOrAny;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::Invoice;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let amount_msat: AmountOrAny = get_option_as(&options_map, "amount_msat").unwrap();
-    let label: String = get_option_as(&options_map, "label").unwrap();
-    let description: String = get_option_as(&options_map, "description").unwrap();
-    let expiry: Option<u64> = get_option_as(&options_map, "expiry");
-    let fallbacks: Option<Vec<String>> = get_option_as(&options_map, "fallbacks");
-    let preimage: Option<String> = get_option_as(&options_map, "preimage");
-    let cltv: Option<u32> = get_option_as(&options_map, "cltv");
-    let deschashonly: Option<bool> = get_option_as(&options_map, "deschashonly");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let amount_msat: AmountOrAny = get_required_option_as(options, "amount_msat")?;
+    let label: String = get_required_option_as(options, "label")?;
+    let description: String = get_required_option_as(options, "description")?;
+    let expiry: Option<u64> = get_option_as(options, "expiry")?;
+    let fallbacks: Option<Vec<String>> = get_option_as(options, "fallbacks")?;
+    let preimage: Option<String> = get_option_as(options, "preimage")?;
+    let cltv: Option<u32> = get_option_as(options, "cltv")?;
+    let deschashonly: Option<bool> = get_option_as(options, "deschashonly")?;
 
     let req = cln_rpc::model::requests::InvoiceRequest {
         amount_msat,
@@ -34,10 +29,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         deschashonly,
     };
 
-    match cln_client.lock().await.call(Invoice(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(Invoice(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
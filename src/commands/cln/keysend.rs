@@ -1,28 +1,23 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::{Amount, PublicKey, RoutehintList, TlvStream};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::KeySend;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let destination: PublicKey = get_option_as(&options_map, "destination").unwrap();
-    let amount_msat: Amount = get_option_as(&options_map, "amount_msat").unwrap();
-    let label: Option<String> = get_option_as(&options_map, "label");
-    let maxfeepercent: Option<f64> = get_option_as(&options_map, "maxfeepercent");
-    let retry_for: Option<u32> = get_option_as(&options_map, "retry_for");
-    let exemptfee: Option<Amount> = get_option_as(&options_map, "exemptfee");
-    let maxdelay: Option<u32> = get_option_as(&options_map, "maxdelay");
-    let routehints: Option<RoutehintList> = get_option_as(&options_map, "routehints");
-    let extratlvs: Option<TlvStream> = get_option_as(&options_map, "extratlvs");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let destination: PublicKey = get_required_option_as(options, "destination")?;
+    let amount_msat: Amount = get_required_option_as(options, "amount_msat")?;
+    let label: Option<String> = get_option_as(options, "label")?;
+    let maxfeepercent: Option<f64> = get_option_as(options, "maxfeepercent")?;
+    let retry_for: Option<u32> = get_option_as(options, "retry_for")?;
+    let exemptfee: Option<Amount> = get_option_as(options, "exemptfee")?;
+    let maxdelay: Option<u32> = get_option_as(options, "maxdelay")?;
+    let routehints: Option<RoutehintList> = get_option_as(options, "routehints")?;
+    let extratlvs: Option<TlvStream> = get_option_as(options, "extratlvs")?;
 
     let req = cln_rpc::model::requests::KeysendRequest {
         destination,
@@ -36,10 +31,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         extratlvs,
     };
 
-    match cln_client.lock().await.call(KeySend(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(KeySend(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
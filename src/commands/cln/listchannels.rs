@@ -1,22 +1,17 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::{PublicKey, ShortChannelId};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::ListChannels;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 use crate::utils::get_option_as::get_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let short_channel_id: Option<ShortChannelId> = get_option_as(&options_map, "short_channel_id");
-    let source: Option<PublicKey> = get_option_as(&options_map, "source");
-    let destination: Option<PublicKey> = get_option_as(&options_map, "destination");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let short_channel_id: Option<ShortChannelId> = get_option_as(options, "short_channel_id")?;
+    let source: Option<PublicKey> = get_option_as(options, "source")?;
+    let destination: Option<PublicKey> = get_option_as(options, "destination")?;
 
     let req = cln_rpc::model::requests::ListchannelsRequest {
         short_channel_id,
@@ -24,10 +19,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         destination,
     };
 
-    match cln_client.lock().await.call(ListChannels(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(ListChannels(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
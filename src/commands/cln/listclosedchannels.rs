@@ -1,30 +1,27 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::PublicKey;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::ListClosedChannels;
+use cln_rpc::Response;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::components::pagination::PagedList;
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 use crate::utils::get_option_as::get_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let id: Option<PublicKey> = get_option_as(&options_map, "id");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let id: Option<PublicKey> = get_option_as(options, "id")?;
 
     let req = cln_rpc::model::requests::ListclosedchannelsRequest { id };
-    let res = cln_client
-        .lock()
-        .await
-        .call(ListClosedChannels(req))
-        .await
-        .unwrap();
-
-    format_json(res)
+    let response = match cln_client.call(ListClosedChannels(req)).await? {
+        Response::ListClosedChannels(res) => PagedList::new("Closed channels")
+            .with_items("Channel", "channel_id", res.closedchannels)
+            .into(),
+        res => format_json(res).into(),
+    };
+
+    Ok(response)
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
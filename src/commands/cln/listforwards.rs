@@ -1,37 +1,27 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::ListforwardsStatus;
 use cln_rpc::primitives::ShortChannelId;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::ListForwards;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::discord_command_options_to_map;
+use crate::commands::{CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 use crate::utils::get_option_as::get_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let status: Option<ListforwardsStatus> = get_option_as(&options_map, "status");
-    let in_channel: Option<ShortChannelId> = get_option_as(&options_map, "in_channel");
-    let out_channel: Option<ShortChannelId> = get_option_as(&options_map, "out_channel");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let status: Option<ListforwardsStatus> = get_option_as(options, "status")?;
+    let in_channel: Option<ShortChannelId> = get_option_as(options, "in_channel")?;
+    let out_channel: Option<ShortChannelId> = get_option_as(options, "out_channel")?;
 
     let req = cln_rpc::model::requests::ListforwardsRequest {
         status,
         in_channel,
         out_channel,
     };
-    let res = cln_client
-        .lock()
-        .await
-        .call(ListForwards(req))
-        .await
-        .unwrap();
+    let res = cln_client.call(ListForwards(req)).await?;
 
-    format_json(res)
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
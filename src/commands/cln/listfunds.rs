@@ -1,23 +1,26 @@
-use std::sync::Arc;
-
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::ListFunds;
+use cln_rpc::Response;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::components::pagination::PagedList;
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 use crate::utils::get_option_as::get_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let spent: bool = get_option_as(&options_map, "spent").unwrap_or(false);
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let spent: bool = get_option_as(options, "spent")?.unwrap_or(false);
     let req = cln_rpc::model::requests::ListfundsRequest { spent: Some(spent) };
-    let res = cln_client.lock().await.call(ListFunds(req)).await.unwrap();
+    let response = match cln_client.call(ListFunds(req)).await? {
+        Response::ListFunds(res) => PagedList::new("Funds")
+            .with_items("Output", "txid", res.outputs)
+            .with_items("Channel", "peer_id", res.channels)
+            .into(),
+        res => format_json(res).into(),
+    };
 
-    format_json(res)
+    Ok(response)
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,26 +1,23 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::ListinvoicesIndex;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::ListInvoices;
+use cln_rpc::Response;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::components::pagination::PagedList;
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 use crate::utils::get_option_as::get_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let label: Option<String> = get_option_as(&options_map, "label");
-    let invstring: Option<String> = get_option_as(&options_map, "invstring");
-    let payment_hash: Option<String> = get_option_as(&options_map, "payment_hash");
-    let offer_id: Option<String> = get_option_as(&options_map, "offer_id");
-    let index: Option<ListinvoicesIndex> = get_option_as(&options_map, "index");
-    let start: Option<u64> = get_option_as(&options_map, "start");
-    let limit: Option<u32> = get_option_as(&options_map, "limit");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let label: Option<String> = get_option_as(options, "label")?;
+    let invstring: Option<String> = get_option_as(options, "invstring")?;
+    let payment_hash: Option<String> = get_option_as(options, "payment_hash")?;
+    let offer_id: Option<String> = get_option_as(options, "offer_id")?;
+    let index: Option<ListinvoicesIndex> = get_option_as(options, "index")?;
+    let start: Option<u64> = get_option_as(options, "start")?;
+    let limit: Option<u32> = get_option_as(options, "limit")?;
 
     let req = cln_rpc::model::requests::ListinvoicesRequest {
         label,
@@ -32,10 +29,14 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         limit,
     };
 
-    match cln_client.lock().await.call(ListInvoices(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let response = match cln_client.call(ListInvoices(req)).await? {
+        Response::ListInvoices(res) => PagedList::new("Invoices")
+            .with_items("Invoice", "label", res.invoices)
+            .into(),
+        res => format_json(res).into(),
+    };
+
+    Ok(response)
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
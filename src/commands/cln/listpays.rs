@@ -1,32 +1,27 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::ListpaysStatus;
 use cln_rpc::primitives::Sha256;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::ListPays;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::discord_command_options_to_map;
+use crate::commands::{CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 use crate::utils::get_option_as::get_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let bolt11: Option<String> = get_option_as(&options_map, "bolt11");
-    let payment_hash: Option<Sha256> = get_option_as(&options_map, "payment_hash");
-    let status: Option<ListpaysStatus> = get_option_as(&options_map, "status");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let bolt11: Option<String> = get_option_as(options, "bolt11")?;
+    let payment_hash: Option<Sha256> = get_option_as(options, "payment_hash")?;
+    let status: Option<ListpaysStatus> = get_option_as(options, "status")?;
 
     let req = cln_rpc::model::requests::ListpaysRequest {
         bolt11,
         payment_hash,
         status,
     };
-    let res = cln_client.lock().await.call(ListPays(req)).await.unwrap();
+    let res = cln_client.call(ListPays(req)).await?;
 
-    format_json(res)
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
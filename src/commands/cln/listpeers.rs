@@ -1,26 +1,28 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::PublicKey;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::ListPeers;
+use cln_rpc::Response;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::discord_command_options_to_map;
+use crate::commands::{CommandOptions, CommandResult};
+use crate::components::pagination::PagedList;
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 use crate::utils::get_option_as::get_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let id: Option<PublicKey> = get_option_as(&options_map, "id");
-    let level: Option<String> = get_option_as(&options_map, "level");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let id: Option<PublicKey> = get_option_as(options, "id")?;
+    let level: Option<String> = get_option_as(options, "level")?;
 
     let req = cln_rpc::model::requests::ListpeersRequest { id, level };
-    let res = cln_client.lock().await.call(ListPeers(req)).await.unwrap();
+    let response = match cln_client.call(ListPeers(req)).await? {
+        Response::ListPeers(res) => PagedList::new("Peers")
+            .with_items("Peer", "id", res.peers)
+            .into(),
+        res => format_json(res).into(),
+    };
 
-    format_json(res)
+    Ok(response)
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,23 +1,20 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::ListsendpaysStatus;
 use cln_rpc::primitives::Sha256;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::ListSendPays;
+use cln_rpc::Response;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::components::pagination::PagedList;
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 use crate::utils::get_option_as::get_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let bolt11: Option<String> = get_option_as(&options_map, "bolt11");
-    let payment_hash: Option<Sha256> = get_option_as(&options_map, "payment_hash");
-    let status: Option<ListsendpaysStatus> = get_option_as(&options_map, "status");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let bolt11: Option<String> = get_option_as(options, "bolt11")?;
+    let payment_hash: Option<Sha256> = get_option_as(options, "payment_hash")?;
+    let status: Option<ListsendpaysStatus> = get_option_as(options, "status")?;
 
     let req = cln_rpc::model::requests::ListsendpaysRequest {
         bolt11,
@@ -25,10 +22,14 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         status,
     };
 
-    match cln_client.lock().await.call(ListSendPays(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let response = match cln_client.call(ListSendPays(req)).await? {
+        Response::ListSendPays(res) => PagedList::new("Payments")
+            .with_items("Payment", "payment_hash", res.payments)
+            .into(),
+        res => format_json(res).into(),
+    };
+
+    Ok(response)
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,20 +1,23 @@
-use std::sync::Arc;
-
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::ListTransactions;
+use cln_rpc::Response;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
+use crate::commands::{CommandOptions, CommandResult};
+use crate::components::pagination::PagedList;
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 
-pub async fn run(_options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
+pub async fn run(_options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
     let req = cln_rpc::model::requests::ListtransactionsRequest {};
 
-    match cln_client.lock().await.call(ListTransactions(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let response = match cln_client.call(ListTransactions(req)).await? {
+        Response::ListTransactions(res) => PagedList::new("Transactions")
+            .with_items("Transaction", "hash", res.transactions)
+            .into(),
+        res => format_json(res).into(),
+    };
+
+    Ok(response)
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
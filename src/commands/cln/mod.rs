@@ -1,11 +1,15 @@
-use std::sync::Arc;
-
-use cln_rpc::ClnRpc;
-use serenity::model::prelude::application_command::CommandData;
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+use serenity::model::application::interaction::autocomplete::AutocompleteInteraction;
 use serenity::prelude::Context;
-use tokio::sync::Mutex;
 
-use crate::utils::discord_utils::create_and_log_command;
+use crate::commands::autocomplete::{self, Choice};
+use crate::commands::{
+    discord_command_options_to_map, subcommands, CommandError, CommandOptions, CommandResult,
+};
+use crate::lightning::{ClnNode, ClnNodes};
+use crate::permissions::Permission;
+use crate::utils::get_option_as::get_option_as;
 
 pub mod addgossip;
 pub mod autoclean;
@@ -43,6 +47,7 @@ pub mod listpeers;
 pub mod listsendpays;
 pub mod listtransactions;
 pub mod newaddr;
+pub mod nodes;
 pub mod pay;
 pub mod ping;
 pub mod preapproveinvoice;
@@ -124,6 +129,7 @@ pub enum ClnCommand {
     ClnSignInvoice,
     ClnSignMessage,
     ClnStaticBackup,
+    ClnNodes,
     Unknown,
 }
 
@@ -187,13 +193,158 @@ impl From<&str> for ClnCommand {
             "cln_signinvoice" => Self::ClnSignInvoice,
             "cln_signmessage" => Self::ClnSignMessage,
             "cln_staticbackup" => Self::ClnStaticBackup,
+            "cln_nodes" => Self::ClnNodes,
             _ => Self::Unknown,
         }
     }
 }
 
-pub async fn ready(ctx: &Context) {
-    let commands = vec![
+impl ClnCommand {
+    pub fn permission(&self) -> Permission {
+        match self {
+            Self::ClnInfo
+            | Self::ClnListPeers
+            | Self::ClnListFunds
+            | Self::ClnPing
+            | Self::ClnListChannels
+            | Self::ClnCheckMessage
+            | Self::ClnListDatastore
+            | Self::ClnListInvoices
+            | Self::ClnListSendPays
+            | Self::ClnListTransactions
+            | Self::ClnListNodes
+            | Self::ClnWaitAnyInvoice
+            | Self::ClnWaitInvoice
+            | Self::ClnWaitSendPay
+            | Self::ClnListClosedChannels
+            | Self::ClnListPeerChannels
+            | Self::ClnDecode
+            | Self::ClnDecodePay
+            | Self::ClnFeerates
+            | Self::ClnGetRoute
+            | Self::ClnListForwards
+            | Self::ClnListHtlcs
+            | Self::ClnListPays
+            | Self::ClnNodes => Permission::ReadOnly,
+            Self::ClnNewAddr
+            | Self::ClnInvoice
+            | Self::ClnFundChannel
+            | Self::ClnSendPay
+            | Self::ClnPay
+            | Self::ClnCreateOnion
+            | Self::ClnSendOnion
+            | Self::ClnWithdraw
+            | Self::ClnKeySend
+            | Self::ClnFundPsbt
+            | Self::ClnSendPsbt
+            | Self::ClnSignPsbt
+            | Self::ClnUtxoPsbt
+            | Self::ClnTxPrepare
+            | Self::ClnTxSend
+            | Self::ClnPreApproveInvoice
+            | Self::ClnPreApproveKeySend => Permission::Spending,
+            Self::ClnConnect
+            | Self::ClnCreateInvoice
+            | Self::ClnAddGossip
+            | Self::ClnAutoClean
+            | Self::ClnClose
+            | Self::ClnDatastore
+            | Self::ClnDelDatastore
+            | Self::ClnDelExpiredInvoice
+            | Self::ClnDelInvoice
+            | Self::ClnTxDiscard
+            | Self::ClnDisconnect
+            | Self::ClnStop
+            | Self::ClnSendCustomMsg
+            | Self::ClnSetChannel
+            | Self::ClnSignInvoice
+            | Self::ClnSignMessage
+            | Self::ClnStaticBackup
+            | Self::Unknown => Permission::Admin,
+        }
+    }
+
+    /// Commands that can't be undone, which the invoking user has to confirm
+    /// after seeing a summary of what they will do
+    pub fn needs_confirmation(&self) -> bool {
+        matches!(
+            self,
+            Self::ClnStop
+                | Self::ClnClose
+                | Self::ClnWithdraw
+                | Self::ClnTxSend
+                | Self::ClnSendPsbt
+                | Self::ClnDelInvoice
+                | Self::ClnDelDatastore
+        )
+    }
+
+    /// The `/cln-*` command the command is listed under
+    pub fn group(&self) -> &'static str {
+        match self {
+            Self::ClnInfo
+            | Self::ClnNodes
+            | Self::ClnStop
+            | Self::ClnFeerates
+            | Self::ClnStaticBackup
+            | Self::ClnAutoClean
+            | Self::ClnSignMessage
+            | Self::ClnCheckMessage
+            | Self::Unknown => "node",
+            Self::ClnListPeers
+            | Self::ClnConnect
+            | Self::ClnDisconnect
+            | Self::ClnPing
+            | Self::ClnSendCustomMsg => "peer",
+            Self::ClnFundChannel
+            | Self::ClnClose
+            | Self::ClnListPeerChannels
+            | Self::ClnListClosedChannels
+            | Self::ClnSetChannel
+            | Self::ClnListForwards
+            | Self::ClnListHtlcs => "channel",
+            Self::ClnListChannels | Self::ClnListNodes | Self::ClnGetRoute | Self::ClnAddGossip => {
+                "network"
+            }
+            Self::ClnInvoice
+            | Self::ClnCreateInvoice
+            | Self::ClnSignInvoice
+            | Self::ClnDelInvoice
+            | Self::ClnDelExpiredInvoice
+            | Self::ClnListInvoices
+            | Self::ClnWaitInvoice
+            | Self::ClnWaitAnyInvoice
+            | Self::ClnDecode
+            | Self::ClnDecodePay
+            | Self::ClnPreApproveInvoice => "invoice",
+            Self::ClnPay
+            | Self::ClnKeySend
+            | Self::ClnSendPay
+            | Self::ClnWaitSendPay
+            | Self::ClnListPays
+            | Self::ClnListSendPays
+            | Self::ClnCreateOnion
+            | Self::ClnSendOnion
+            | Self::ClnPreApproveKeySend => "pay",
+            Self::ClnNewAddr
+            | Self::ClnListFunds
+            | Self::ClnListTransactions
+            | Self::ClnWithdraw
+            | Self::ClnTxPrepare
+            | Self::ClnTxSend
+            | Self::ClnTxDiscard
+            | Self::ClnFundPsbt
+            | Self::ClnUtxoPsbt
+            | Self::ClnSignPsbt
+            | Self::ClnSendPsbt => "wallet",
+            Self::ClnDatastore | Self::ClnDelDatastore | Self::ClnListDatastore => "datastore",
+        }
+    }
+}
+
+/// The `cln_*` commands under their flat names, as the API and the CLI run them
+pub fn commands() -> Vec<CreateApplicationCommand> {
+    let registers = vec![
         info::register,
         listpeers::register,
         listfunds::register,
@@ -253,88 +404,405 @@ pub async fn ready(ctx: &Context) {
         staticbackup::register,
     ];
 
-    for command in commands {
-        create_and_log_command(&ctx.http, command).await;
+    let mut commands = registers
+        .into_iter()
+        .map(|register| {
+            let mut command = CreateApplicationCommand::default();
+            add_node_option(register(&mut command));
+            command
+        })
+        .collect::<Vec<_>>();
+
+    let mut command = CreateApplicationCommand::default();
+    nodes::register(&mut command);
+    commands.push(command);
+
+    commands
+}
+
+/// The `/cln-*` commands Discord lists, one for each group of `cln_*`
+/// commands, e.g. `/cln-channel close`. A single `/cln` command would be over
+/// Discord's limit of 8000 characters per command.
+pub fn trees() -> Vec<CreateApplicationCommand> {
+    let groups = [
+        ("node", "Run a CLN RPC command on the node itself"),
+        ("peer", "Run a CLN RPC command on connected peers"),
+        ("channel", "Run a CLN RPC command on channels with peers"),
+        (
+            "network",
+            "Run a CLN RPC command on the gossiped network graph",
+        ),
+        ("invoice", "Run a CLN RPC command on invoices to get paid"),
+        ("pay", "Run a CLN RPC command on outgoing payments"),
+        ("wallet", "Run a CLN RPC command on on-chain funds"),
+        (
+            "datastore",
+            "Run a CLN RPC command on the node's key-value store",
+        ),
+    ];
+    let commands = commands();
+
+    groups
+        .iter()
+        .map(|(group, description)| {
+            let grouped = commands
+                .iter()
+                .filter(|command| {
+                    let name = command.0.get("name").and_then(|name| name.as_str());
+                    ClnCommand::from(name.unwrap_or_default()).group() == *group
+                })
+                .cloned()
+                .collect();
+            subcommands::tree(
+                &format!("cln-{}", group),
+                description,
+                &[],
+                grouped,
+                |name| (None, name.trim_start_matches("cln_").to_string()),
+            )
+        })
+        .collect()
+}
+
+/// The flat name of a `/cln-*` subcommand. Groups are only for listing, so
+/// they aren't part of it.
+pub fn command_name(subcommand: &str) -> String {
+    format!("cln_{}", subcommand)
+}
+
+// Lets a command run on any of the configured CLN nodes
+fn add_node_option(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command.create_option(|opt| {
+        opt.name("node")
+            .description("The CLN node to use, defaults to the first configured node")
+            .kind(CommandOptionType::String)
+            .required(false)
+            .set_autocomplete(true)
+    })
+}
+
+// Finds the node picked with the `node` option
+fn select_node<'a>(
+    options: &CommandOptions,
+    nodes: &'a ClnNodes,
+) -> Result<&'a ClnNode, CommandError> {
+    if nodes.is_empty() {
+        return Err(CommandError::Rpc(
+            "No CLN node is configured, use the ln_* commands instead".to_string(),
+        ));
+    }
+    let name = get_option_as::<String>(options, "node")?;
+    nodes
+        .select(name.as_deref())
+        .map_err(|e| CommandError::invalid_option("node", "CLN node name", e))
+}
+
+/// Suggests values for the option the user is typing in: the configured CLN
+/// nodes for `node`, and peers, channels or invoice labels of the selected
+/// node for the options that take them
+pub async fn autocomplete(ctx: &Context, autocomplete: &AutocompleteInteraction, nodes: &ClnNodes) {
+    let (option, typed) = match autocomplete::focused(&autocomplete.data.options) {
+        Some(focused) => focused,
+        None => return,
+    };
+    if option == "node" {
+        let choices = nodes
+            .names()
+            .into_iter()
+            .map(|name| Choice::new(name, name))
+            .collect();
+        return autocomplete::respond(ctx, autocomplete, typed, choices).await;
+    }
+
+    // The other suggestions come from the node the command runs on
+    let options = discord_command_options_to_map(&autocomplete.data.options);
+    let client = match select_node(&options, nodes) {
+        Ok(node) => &node.client,
+        Err(_) => return autocomplete::respond(ctx, autocomplete, typed, Vec::new()).await,
+    };
+    let choices = match (ClnCommand::from(autocomplete.data.name.as_str()), option) {
+        (ClnCommand::ClnConnect, "connection_string") => {
+            autocomplete::cln_peer_addresses(client).await
+        }
+        (ClnCommand::ClnClose, "id") => autocomplete::cln_peers(client).await,
+        (ClnCommand::ClnSetChannel, "id") => autocomplete::cln_channels(client).await,
+        (ClnCommand::ClnDelInvoice | ClnCommand::ClnWaitInvoice, "label") => {
+            autocomplete::cln_invoice_labels(client).await
+        }
+        _ => Vec::new(),
+    };
+
+    autocomplete::respond(ctx, autocomplete, typed, choices).await
+}
+
+pub async fn handle_summary(
+    command_name: &str,
+    options: &CommandOptions,
+    nodes: &ClnNodes,
+) -> Result<String, CommandError> {
+    let node = select_node(options, nodes)?;
+    let summary = summarize(command_name, options, node).await?;
+
+    // Say which node the command runs on when it could be any of several
+    if nodes.list().len() > 1 {
+        Ok(format!("On CLN node `{}`: {}", node.name, summary))
+    } else {
+        Ok(summary)
+    }
+}
+
+async fn summarize(
+    command_name: &str,
+    options: &CommandOptions,
+    node: &ClnNode,
+) -> Result<String, CommandError> {
+    let cln_client = &node.client;
+    let options = options;
+    match ClnCommand::from(command_name) {
+        ClnCommand::ClnStop => Ok(stop::summarize(options)),
+        ClnCommand::ClnClose => close::summarize(options, cln_client).await,
+        ClnCommand::ClnWithdraw => withdraw::summarize(options),
+        ClnCommand::ClnTxSend => txsend::summarize(options),
+        ClnCommand::ClnSendPsbt => sendpsbt::summarize(options),
+        ClnCommand::ClnDelInvoice => delinvoice::summarize(options, cln_client).await,
+        ClnCommand::ClnDelDatastore => deldatastore::summarize(options),
+        _ => Ok(format!("Run `{}`", command_name)),
     }
 }
 
 pub async fn handle_run(
     command_name: &str,
-    command_data: &CommandData,
-    cln_client: &Arc<Mutex<ClnRpc>>,
-) -> String {
+    options: &CommandOptions,
+    nodes: &ClnNodes,
+) -> CommandResult {
+    let node = select_node(options, nodes)?;
+    let cln_client = &node.client;
     match ClnCommand::from(command_name) {
-        ClnCommand::ClnInfo => info::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListPeers => listpeers::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListFunds => listfunds::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnConnect => connect::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnNewAddr => newaddr::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnCreateInvoice => createinvoice::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnFundChannel => fundchannel::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnSendPay => sendpay::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnPay => pay::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnPing => ping::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListChannels => listchannels::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnAddGossip => addgossip::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnAutoClean => autoclean::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnCheckMessage => checkmessage::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnClose => close::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnDatastore => datastore::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnCreateOnion => createonion::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnDelDatastore => deldatastore::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnDelExpiredInvoice => {
-            delexpiredinvoice::run(&command_data.options, cln_client).await
-        }
-        ClnCommand::ClnDelInvoice => delinvoice::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnInvoice => invoice::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListDatastore => listdatastore::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListInvoices => listinvoices::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnSendOnion => sendonion::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListSendPays => listsendpays::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListTransactions => {
-            listtransactions::run(&command_data.options, cln_client).await
-        }
-        ClnCommand::ClnListNodes => listnodes::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnWaitAnyInvoice => {
-            waitanyinvoice::run(&command_data.options, cln_client).await
+        ClnCommand::ClnInfo => info::run(options, cln_client).await,
+        ClnCommand::ClnListPeers => listpeers::run(options, cln_client).await,
+        ClnCommand::ClnListFunds => listfunds::run(options, cln_client).await,
+        ClnCommand::ClnConnect => connect::run(options, cln_client).await,
+        ClnCommand::ClnNewAddr => newaddr::run(options, cln_client).await,
+        ClnCommand::ClnCreateInvoice => createinvoice::run(options, cln_client).await,
+        ClnCommand::ClnFundChannel => fundchannel::run(options, cln_client).await,
+        ClnCommand::ClnSendPay => sendpay::run(options, cln_client).await,
+        ClnCommand::ClnPay => pay::run(options, cln_client).await,
+        ClnCommand::ClnPing => ping::run(options, cln_client).await,
+        ClnCommand::ClnListChannels => listchannels::run(options, cln_client).await,
+        ClnCommand::ClnAddGossip => addgossip::run(options, cln_client).await,
+        ClnCommand::ClnAutoClean => autoclean::run(options, cln_client).await,
+        ClnCommand::ClnCheckMessage => checkmessage::run(options, cln_client).await,
+        ClnCommand::ClnClose => close::run(options, cln_client).await,
+        ClnCommand::ClnDatastore => datastore::run(options, cln_client).await,
+        ClnCommand::ClnCreateOnion => createonion::run(options, cln_client).await,
+        ClnCommand::ClnDelDatastore => deldatastore::run(options, cln_client).await,
+        ClnCommand::ClnDelExpiredInvoice => delexpiredinvoice::run(options, cln_client).await,
+        ClnCommand::ClnDelInvoice => delinvoice::run(options, cln_client).await,
+        ClnCommand::ClnInvoice => invoice::run(options, cln_client).await,
+        ClnCommand::ClnListDatastore => listdatastore::run(options, cln_client).await,
+        ClnCommand::ClnListInvoices => listinvoices::run(options, cln_client).await,
+        ClnCommand::ClnSendOnion => sendonion::run(options, cln_client).await,
+        ClnCommand::ClnListSendPays => listsendpays::run(options, cln_client).await,
+        ClnCommand::ClnListTransactions => listtransactions::run(options, cln_client).await,
+        ClnCommand::ClnListNodes => listnodes::run(options, cln_client).await,
+        ClnCommand::ClnWaitAnyInvoice => waitanyinvoice::run(options, cln_client).await,
+        ClnCommand::ClnWaitInvoice => waitinvoice::run(options, cln_client).await,
+        ClnCommand::ClnWaitSendPay => waitsendpay::run(options, cln_client).await,
+        ClnCommand::ClnWithdraw => withdraw::run(options, cln_client).await,
+        ClnCommand::ClnKeySend => keysend::run(options, cln_client).await,
+        ClnCommand::ClnFundPsbt => fundpsbt::run(options, cln_client).await,
+        ClnCommand::ClnSendPsbt => sendpsbt::run(options, cln_client).await,
+        ClnCommand::ClnSignPsbt => signpsbt::run(options, cln_client).await,
+        ClnCommand::ClnUtxoPsbt => utxopsbt::run(options, cln_client).await,
+        ClnCommand::ClnTxDiscard => txdiscard::run(options, cln_client).await,
+        ClnCommand::ClnTxPrepare => txprepare::run(options, cln_client).await,
+        ClnCommand::ClnTxSend => txsend::run(options, cln_client).await,
+        ClnCommand::ClnListClosedChannels => listclosedchannels::run(options, cln_client).await,
+        ClnCommand::ClnListPeerChannels => listpeerchannels::run(options, cln_client).await,
+        ClnCommand::ClnDecode => decode::run(options, cln_client).await,
+        ClnCommand::ClnDecodePay => decodepay::run(options, cln_client).await,
+        ClnCommand::ClnDisconnect => disconnect::run(options, cln_client).await,
+        ClnCommand::ClnFeerates => feerates::run(options, cln_client).await,
+        ClnCommand::ClnGetRoute => getroute::run(options, cln_client).await,
+        ClnCommand::ClnListForwards => listforwards::run(options, cln_client).await,
+        ClnCommand::ClnListHtlcs => listhtlcs::run(options, cln_client).await,
+        ClnCommand::ClnListPays => listpays::run(options, cln_client).await,
+        ClnCommand::ClnStop => stop::run(options, cln_client).await,
+        ClnCommand::ClnPreApproveInvoice => preapproveinvoice::run(options, cln_client).await,
+        ClnCommand::ClnPreApproveKeySend => preapprovekeysend::run(options, cln_client).await,
+        ClnCommand::ClnSendCustomMsg => sendcustommsg::run(options, cln_client).await,
+        ClnCommand::ClnSetChannel => setchannel::run(options, cln_client).await,
+        ClnCommand::ClnSignInvoice => signinvoice::run(options, cln_client).await,
+        ClnCommand::ClnSignMessage => signmessage::run(options, cln_client).await,
+        ClnCommand::ClnStaticBackup => staticbackup::run(options, cln_client).await,
+        ClnCommand::ClnNodes => nodes::run(nodes).await,
+        ClnCommand::Unknown => Ok(format!("Unknown command: {}", command_name).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::CommandResponse;
+    use crate::lightning::ClnClient;
+    use crate::testing::cln::{getinfo, listfunds, MockCln};
+    use crate::testing::options;
+
+    fn nodes(cln_client: &ClnClient) -> ClnNodes {
+        ClnNodes::new(vec![ClnNode {
+            name: "main".to_string(),
+            client: cln_client.clone(),
+        }])
+    }
+
+    #[tokio::test]
+    async fn info_replies_with_node_json() {
+        let (_mock, cln_client) = MockCln::new().respond("getinfo", getinfo()).start().await;
+
+        let res = handle_run("cln_info", &options(&[]), &nodes(&cln_client)).await;
+
+        match res {
+            Ok(CommandResponse::Text(content)) => assert!(content.contains("botimint-test")),
+            res => panic!("Unexpected response: {:?}", res),
         }
-        ClnCommand::ClnWaitInvoice => waitinvoice::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnWaitSendPay => waitsendpay::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnWithdraw => withdraw::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnKeySend => keysend::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnFundPsbt => fundpsbt::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnSendPsbt => sendpsbt::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnSignPsbt => signpsbt::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnUtxoPsbt => utxopsbt::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnTxDiscard => txdiscard::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnTxPrepare => txprepare::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnTxSend => txsend::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListClosedChannels => {
-            listclosedchannels::run(&command_data.options, cln_client).await
+    }
+
+    #[tokio::test]
+    async fn listfunds_pages_outputs() {
+        let (mock, cln_client) = MockCln::new()
+            .respond("listfunds", listfunds(7))
+            .start()
+            .await;
+
+        let data = options(&[("spent", json!(true))]);
+        let res = handle_run("cln_listfunds", &data, &nodes(&cln_client)).await;
+
+        match res {
+            Ok(CommandResponse::List(list)) => assert_eq!(list.len(), 7),
+            res => panic!("Unexpected response: {:?}", res),
         }
-        ClnCommand::ClnListPeerChannels => {
-            listpeerchannels::run(&command_data.options, cln_client).await
+        assert_eq!(mock.requests("listfunds"), vec![json!({ "spent": true })]);
+    }
+
+    #[tokio::test]
+    async fn connect_sends_the_parsed_connection_string() {
+        let id = "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619";
+        let (mock, cln_client) = MockCln::new()
+            .respond(
+                "connect",
+                json!({
+                    "id": id,
+                    "features": "",
+                    "direction": "out",
+                    "address": { "type": "ipv4", "address": "127.0.0.1", "port": 9735 }
+                }),
+            )
+            .start()
+            .await;
+
+        let connection_string = format!("{}@127.0.0.1:9735", id);
+        let data = options(&[("connection_string", json!(connection_string))]);
+        handle_run("cln_connect", &data, &nodes(&cln_client))
+            .await
+            .unwrap();
+
+        let requests = mock.requests("connect");
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0]["id"], json!(id));
+        assert_eq!(requests[0]["host"], json!("127.0.0.1"));
+        assert_eq!(requests[0]["port"], json!(9735));
+    }
+
+    #[tokio::test]
+    async fn rpc_errors_are_returned() {
+        let (_mock, cln_client) = MockCln::new()
+            .fail("pay", 210, "Ran out of routes to try")
+            .start()
+            .await;
+
+        let data = options(&[("bolt11", json!("lnbcrt1"))]);
+        let res = handle_run("cln_pay", &data, &nodes(&cln_client)).await;
+
+        match res {
+            Err(CommandError::Rpc(message)) => assert!(message.contains("Ran out of routes")),
+            res => panic!("Unexpected response: {:?}", res),
         }
-        ClnCommand::ClnDecode => decode::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnDecodePay => decodepay::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnDisconnect => disconnect::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnFeerates => feerates::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnGetRoute => getroute::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListForwards => listforwards::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListHtlcs => listhtlcs::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnListPays => listpays::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnStop => stop::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnPreApproveInvoice => {
-            preapproveinvoice::run(&command_data.options, cln_client).await
+    }
+
+    #[tokio::test]
+    async fn bad_options_are_rejected_before_calling_the_node() {
+        let (mock, cln_client) = MockCln::new().start().await;
+
+        let res = handle_run("cln_pay", &options(&[]), &nodes(&cln_client)).await;
+        assert!(matches!(res, Err(CommandError::MissingOption(name)) if name == "bolt11"));
+
+        let data = options(&[("connection_string", json!("not-a-node@host"))]);
+        let res = handle_run("cln_connect", &data, &nodes(&cln_client)).await;
+        assert!(
+            matches!(res, Err(CommandError::InvalidOption { name, .. }) if name == "connection_string")
+        );
+
+        assert!(mock.requests("pay").is_empty());
+        assert!(mock.requests("connect").is_empty());
+    }
+
+    #[tokio::test]
+    async fn the_node_option_picks_the_node() {
+        let (main, main_client) = MockCln::new().respond("getinfo", getinfo()).start().await;
+        let mut merchant_info = getinfo();
+        merchant_info["alias"] = json!("merchant-node");
+        let (merchant, merchant_client) = MockCln::new()
+            .respond("getinfo", merchant_info)
+            .start()
+            .await;
+        let nodes = ClnNodes::new(vec![
+            ClnNode {
+                name: "main".to_string(),
+                client: main_client,
+            },
+            ClnNode {
+                name: "merchant".to_string(),
+                client: merchant_client,
+            },
+        ]);
+
+        let data = options(&[("node", json!("Merchant"))]);
+        match handle_run("cln_info", &data, &nodes).await {
+            Ok(CommandResponse::Text(content)) => assert!(content.contains("merchant-node")),
+            res => panic!("Unexpected response: {:?}", res),
         }
-        ClnCommand::ClnPreApproveKeySend => {
-            preapprovekeysend::run(&command_data.options, cln_client).await
+        assert!(main.requests("getinfo").is_empty());
+        assert_eq!(merchant.requests("getinfo").len(), 1);
+
+        let data = options(&[("node", json!("routing"))]);
+        let res = handle_run("cln_info", &data, &nodes).await;
+        assert!(matches!(res, Err(CommandError::InvalidOption { name, .. }) if name == "node"));
+    }
+
+    #[tokio::test]
+    async fn nodes_shows_every_node() {
+        let (_main, main_client) = MockCln::new().respond("getinfo", getinfo()).start().await;
+        let (_down, down_client) = MockCln::new().start().await;
+        let nodes = ClnNodes::new(vec![
+            ClnNode {
+                name: "main".to_string(),
+                client: main_client,
+            },
+            ClnNode {
+                name: "down".to_string(),
+                client: down_client,
+            },
+        ]);
+
+        match handle_run("cln_nodes", &options(&[]), &nodes).await {
+            Ok(CommandResponse::Text(content)) => {
+                assert!(content.contains("botimint-test"));
+                assert!(content.contains("down"));
+                assert!(content.contains("unreachable"));
+            }
+            res => panic!("Unexpected response: {:?}", res),
         }
-        ClnCommand::ClnSendCustomMsg => sendcustommsg::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnSetChannel => setchannel::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnSignInvoice => signinvoice::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnSignMessage => signmessage::run(&command_data.options, cln_client).await,
-        ClnCommand::ClnStaticBackup => staticbackup::run(&command_data.options, cln_client).await,
-        ClnCommand::Unknown => format!("Unknown command: {}", command_name),
     }
 }
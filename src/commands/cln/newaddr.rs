@@ -1,29 +1,24 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::NewaddrAddresstype;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::NewAddr;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::discord_command_options_to_map;
+use crate::commands::{CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::address_utils::AddressString;
 use crate::utils::format_json;
 use crate::utils::get_option_as::get_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
     let addr_type: NewaddrAddresstype =
-        get_option_as(&options_map, "address_type").unwrap_or(NewaddrAddresstype::BECH32);
+        get_option_as(options, "address_type")?.unwrap_or(NewaddrAddresstype::BECH32);
 
     let req = cln_rpc::model::requests::NewaddrRequest {
         addresstype: Some(addr_type),
     };
-    let res = cln_client.lock().await.call(NewAddr(req)).await.unwrap();
+    let res = cln_client.call(NewAddr(req)).await?;
 
-    format_json(res)
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -0,0 +1,93 @@
+use futures::future::join_all;
+use serenity::builder::CreateApplicationCommand;
+
+use crate::commands::CommandResult;
+use crate::lightning::{ClnBackend, ClnNodes, LightningBackend};
+
+// The getinfo fields compared across nodes, one row each
+const ROWS: [&str; 7] = [
+    "id",
+    "alias",
+    "version",
+    "network",
+    "block height",
+    "peers",
+    "active channels",
+];
+
+pub async fn run(nodes: &ClnNodes) -> CommandResult {
+    let infos = join_all(
+        nodes
+            .list()
+            .iter()
+            .map(|node| async move { ClnBackend::new(node.client.clone()).info().await }),
+    )
+    .await;
+
+    // One column per node, unreachable nodes show the error in their id row
+    let columns = nodes
+        .list()
+        .iter()
+        .zip(infos)
+        .map(|(node, info)| {
+            let mut column = vec![node.name.clone()];
+            match info {
+                Ok(info) => column.extend([
+                    info.id,
+                    info.alias,
+                    info.version,
+                    info.network,
+                    info.block_height.to_string(),
+                    info.num_peers.to_string(),
+                    info.num_active_channels.to_string(),
+                ]),
+                Err(e) => {
+                    column.push(format!("unreachable: {}", e));
+                    column.resize(ROWS.len() + 1, String::new());
+                }
+            }
+            column
+        })
+        .collect::<Vec<_>>();
+
+    Ok(format!("```\n{}\n```", format_table(&columns)).into())
+}
+
+// Lays the columns out next to each other, with the row labels on the left
+fn format_table(columns: &[Vec<String>]) -> String {
+    let labels = std::iter::once("node")
+        .chain(ROWS)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let columns = std::iter::once(&labels).chain(columns).collect::<Vec<_>>();
+    let widths = columns
+        .iter()
+        .map(|column| {
+            column
+                .iter()
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    (0..labels.len())
+        .map(|row| {
+            columns
+                .iter()
+                .zip(&widths)
+                .map(|(column, width)| format!("{:width$}", column[row], width = width))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("cln_nodes")
+        .description("Compare the info of every CLN node side by side")
+}
@@ -1,31 +1,26 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::Amount;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::Pay;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let bolt11: String = get_option_as(&options_map, "bolt11").unwrap();
-    let amount_msat: Option<Amount> = get_option_as(&options_map, "amount_msat");
-    let label: Option<String> = get_option_as(&options_map, "label");
-    let riskfactor: Option<f64> = get_option_as(&options_map, "riskfactor");
-    let maxfeepercent: Option<f64> = get_option_as(&options_map, "maxfeepercent");
-    let retry_for: Option<u16> = get_option_as(&options_map, "retry_for");
-    let maxdelay: Option<u16> = get_option_as(&options_map, "maxdelay");
-    let exemptfee: Option<Amount> = get_option_as(&options_map, "exemptfee");
-    let localinvreqid: Option<String> = get_option_as(&options_map, "localinvreqid");
-    let exclude: Option<Vec<String>> = get_option_as(&options_map, "exclude");
-    let maxfee: Option<Amount> = get_option_as(&options_map, "maxfee");
-    let description: Option<String> = get_option_as(&options_map, "description");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let bolt11: String = get_required_option_as(options, "bolt11")?;
+    let amount_msat: Option<Amount> = get_option_as(options, "amount_msat")?;
+    let label: Option<String> = get_option_as(options, "label")?;
+    let riskfactor: Option<f64> = get_option_as(options, "riskfactor")?;
+    let maxfeepercent: Option<f64> = get_option_as(options, "maxfeepercent")?;
+    let retry_for: Option<u16> = get_option_as(options, "retry_for")?;
+    let maxdelay: Option<u16> = get_option_as(options, "maxdelay")?;
+    let exemptfee: Option<Amount> = get_option_as(options, "exemptfee")?;
+    let localinvreqid: Option<String> = get_option_as(options, "localinvreqid")?;
+    let exclude: Option<Vec<String>> = get_option_as(options, "exclude")?;
+    let maxfee: Option<Amount> = get_option_as(options, "maxfee")?;
+    let description: Option<String> = get_option_as(options, "description")?;
 
     let req = cln_rpc::model::requests::PayRequest {
         bolt11,
@@ -42,10 +37,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         description,
     };
 
-    match cln_client.lock().await.call(Pay(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(Pay(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,29 +1,23 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::PublicKey;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::Ping;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let id: PublicKey = get_option_as(&options_map, "id").unwrap();
-    let len: Option<u16> = get_option_as(&options_map, "len");
-    let pongbytes: Option<u16> = get_option_as(&options_map, "pongbytes");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let id: PublicKey = get_required_option_as(options, "id")?;
+    let len: Option<u16> = get_option_as(options, "len")?;
+    let pongbytes: Option<u16> = get_option_as(options, "pongbytes")?;
 
     let req = cln_rpc::model::requests::PingRequest { id, len, pongbytes };
 
-    match cln_client.lock().await.call(Ping(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(Ping(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
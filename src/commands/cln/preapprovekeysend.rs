@@ -1,36 +1,26 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::{Amount, PublicKey};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::PreApproveKeysend;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 use crate::utils::get_option_as::get_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let destination: Option<PublicKey> = get_option_as(&options_map, "destination");
-    let payment_hash: Option<String> = get_option_as(&options_map, "payment_hash");
-    let amount_msat: Option<Amount> = get_option_as(&options_map, "amount_msat");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let destination: Option<PublicKey> = get_option_as(options, "destination")?;
+    let payment_hash: Option<String> = get_option_as(options, "payment_hash")?;
+    let amount_msat: Option<Amount> = get_option_as(options, "amount_msat")?;
 
     let req = cln_rpc::model::requests::PreapprovekeysendRequest {
         destination,
         payment_hash,
         amount_msat,
     };
-    let res = cln_client
-        .lock()
-        .await
-        .call(PreApproveKeysend(req))
-        .await
-        .unwrap();
+    let res = cln_client.call(PreApproveKeysend(req)).await?;
 
-    format_json(res)
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,31 +1,21 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::PublicKey;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::SendCustomMsg;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::get_required_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let node_id: PublicKey = get_option_as(&options_map, "node_id").unwrap();
-    let msg: String = get_option_as(&options_map, "msg").unwrap();
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let node_id: PublicKey = get_required_option_as(options, "node_id")?;
+    let msg: String = get_required_option_as(options, "msg")?;
 
     let req = cln_rpc::model::requests::SendcustommsgRequest { node_id, msg };
-    let res = cln_client
-        .lock()
-        .await
-        .call(SendCustomMsg(req))
-        .await
-        .unwrap();
-
-    format_json(res)
+    let res = cln_client.call(SendCustomMsg(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
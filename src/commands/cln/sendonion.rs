@@ -1,31 +1,26 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::SendonionFirst_hop;
 use cln_rpc::primitives::{Amount, PublicKey, Secret, Sha256};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::SendOnion;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let onion: String = get_option_as(&options_map, "onion").unwrap();
-    let first_hop: SendonionFirst_hop = get_option_as(&options_map, "first_hop").unwrap();
-    let payment_hash: Sha256 = get_option_as(&options_map, "payment_hash").unwrap();
-    let label: Option<String> = get_option_as(&options_map, "label");
-    let shared_secrets: Option<Vec<Secret>> = get_option_as(&options_map, "shared_secrets");
-    let partid: Option<u16> = get_option_as(&options_map, "partid");
-    let bolt11: Option<String> = get_option_as(&options_map, "bolt11");
-    let amount_msat: Option<Amount> = get_option_as(&options_map, "amount_msat");
-    let destination: Option<PublicKey> = get_option_as(&options_map, "destination");
-    let localinvreqid: Option<Sha256> = get_option_as(&options_map, "localinvreqid");
-    let groupid: Option<u64> = get_option_as(&options_map, "groupid");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let onion: String = get_required_option_as(options, "onion")?;
+    let first_hop: SendonionFirst_hop = get_required_option_as(options, "first_hop")?;
+    let payment_hash: Sha256 = get_required_option_as(options, "payment_hash")?;
+    let label: Option<String> = get_option_as(options, "label")?;
+    let shared_secrets: Option<Vec<Secret>> = get_option_as(options, "shared_secrets")?;
+    let partid: Option<u16> = get_option_as(options, "partid")?;
+    let bolt11: Option<String> = get_option_as(options, "bolt11")?;
+    let amount_msat: Option<Amount> = get_option_as(options, "amount_msat")?;
+    let destination: Option<PublicKey> = get_option_as(options, "destination")?;
+    let localinvreqid: Option<Sha256> = get_option_as(options, "localinvreqid")?;
+    let groupid: Option<u64> = get_option_as(options, "groupid")?;
 
     let req = cln_rpc::model::requests::SendonionRequest {
         onion,
@@ -41,10 +36,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         groupid,
     };
 
-    match cln_client.lock().await.call(SendOnion(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(SendOnion(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
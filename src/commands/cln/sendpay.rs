@@ -1,29 +1,24 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::SendpayRoute;
 use cln_rpc::primitives::{Amount, Secret, Sha256};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::SendPay;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::application_command::CommandDataOption;
 use serenity::model::prelude::command::CommandOptionType;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let route: Vec<SendpayRoute> = get_option_as(&options_map, "route").unwrap();
-    let payment_hash: Sha256 = get_option_as(&options_map, "payment_hash").unwrap();
-    let label: Option<String> = get_option_as(&options_map, "label");
-    let amount_msat: Option<Amount> = get_option_as(&options_map, "amount_msat");
-    let bolt11: Option<String> = get_option_as(&options_map, "bolt11");
-    let payment_secret: Option<Secret> = get_option_as(&options_map, "payment_secret");
-    let partid: Option<u16> = get_option_as(&options_map, "partid");
-    let localinvreqid: Option<String> = get_option_as(&options_map, "localinvreqid");
-    let groupid: Option<u64> = get_option_as(&options_map, "groupid");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let route: Vec<SendpayRoute> = get_required_option_as(options, "route")?;
+    let payment_hash: Sha256 = get_required_option_as(options, "payment_hash")?;
+    let label: Option<String> = get_option_as(options, "label")?;
+    let amount_msat: Option<Amount> = get_option_as(options, "amount_msat")?;
+    let bolt11: Option<String> = get_option_as(options, "bolt11")?;
+    let payment_secret: Option<Secret> = get_option_as(options, "payment_secret")?;
+    let partid: Option<u16> = get_option_as(options, "partid")?;
+    let localinvreqid: Option<String> = get_option_as(options, "localinvreqid")?;
+    let groupid: Option<u64> = get_option_as(options, "groupid")?;
 
     let req = cln_rpc::model::requests::SendpayRequest {
         route,
@@ -37,10 +32,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         groupid,
     };
 
-    match cln_client.lock().await.call(SendPay(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(SendPay(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
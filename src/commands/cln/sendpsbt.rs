@@ -1,27 +1,32 @@
-use std::sync::Arc;
-
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::SendPsbt;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::application_command::CommandDataOption;
 use serenity::model::prelude::command::CommandOptionType;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let psbt: String = get_option_as(&options_map, "psbt").unwrap();
-    let reserve: Option<bool> = get_option_as(&options_map, "reserve");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let psbt: String = get_required_option_as(options, "psbt")?;
+    let reserve: Option<bool> = get_option_as(options, "reserve")?;
 
     let req = cln_rpc::model::requests::SendpsbtRequest { psbt, reserve };
 
-    match cln_client.lock().await.call(SendPsbt(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(SendPsbt(req)).await?;
+
+    Ok(format_json(res).into())
+}
+
+pub fn summarize(options: &CommandOptions) -> Result<String, CommandError> {
+    let psbt: String = get_required_option_as(options, "psbt")?;
+    let preview = psbt.chars().take(32).collect::<String>();
+
+    Ok(format!(
+        "Finalize and broadcast the PSBT `{}…` ({} characters)",
+        preview,
+        psbt.len()
+    ))
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,26 +1,21 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::Amount;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::SetChannel;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::command::CommandOptionType;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let id: String = get_option_as(&options_map, "id").unwrap();
-    let feebase: Option<Amount> = get_option_as(&options_map, "feebase");
-    let feeppm: Option<u32> = get_option_as(&options_map, "feeppm");
-    let htlcmin: Option<Amount> = get_option_as(&options_map, "htlcmin");
-    let htlcmax: Option<Amount> = get_option_as(&options_map, "htlcmax");
-    let enforcedelay: Option<u32> = get_option_as(&options_map, "enforcedelay");
-    let ignorefeelimits: Option<bool> = get_option_as(&options_map, "ignorfeelimits");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let id: String = get_required_option_as(options, "id")?;
+    let feebase: Option<Amount> = get_option_as(options, "feebase")?;
+    let feeppm: Option<u32> = get_option_as(options, "feeppm")?;
+    let htlcmin: Option<Amount> = get_option_as(options, "htlcmin")?;
+    let htlcmax: Option<Amount> = get_option_as(options, "htlcmax")?;
+    let enforcedelay: Option<u32> = get_option_as(options, "enforcedelay")?;
+    let ignorefeelimits: Option<bool> = get_option_as(options, "ignorfeelimits")?;
 
     let req = cln_rpc::model::requests::SetchannelRequest {
         id,
@@ -31,9 +26,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         enforcedelay,
         ignorefeelimits,
     };
-    let res = cln_client.lock().await.call(SetChannel(req)).await.unwrap();
+    let res = cln_client.call(SetChannel(req)).await?;
 
-    format_json(res)
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -92,6 +87,7 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
                 .description(opt_info.description)
                 .kind(opt_info.kind)
                 .required(opt_info.required)
+                .set_autocomplete(opt_info.name == "id")
         });
     }
 
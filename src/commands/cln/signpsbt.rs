@@ -1,28 +1,22 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::SignpsbtRequest;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::SignPsbt;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::application_command::CommandDataOption;
 use serenity::model::prelude::command::CommandOptionType;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let psbt: String = get_option_as(&options_map, "psbt").unwrap();
-    let signonly: Option<Vec<u32>> = get_option_as(&options_map, "signonly");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let psbt: String = get_required_option_as(options, "psbt")?;
+    let signonly: Option<Vec<u32>> = get_option_as(options, "signonly")?;
 
     let req = SignpsbtRequest { psbt, signonly };
 
-    match cln_client.lock().await.call(SignPsbt(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(SignPsbt(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
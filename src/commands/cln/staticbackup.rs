@@ -1,23 +1,15 @@
-use std::sync::Arc;
-
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::StaticBackup;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
+use crate::commands::{CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 
-pub async fn run(_options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
+pub async fn run(_options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
     let req = cln_rpc::model::requests::StaticbackupRequest {};
-    let res = cln_client
-        .lock()
-        .await
-        .call(StaticBackup(req))
-        .await
-        .unwrap();
+    let res = cln_client.call(StaticBackup(req)).await?;
 
-    format_json(res)
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,18 +1,21 @@
-use std::sync::Arc;
-
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::Stop;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
-use tokio::sync::Mutex;
 
+use crate::commands::{CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
 
-pub async fn run(_options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
+pub async fn run(_options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
     let req = cln_rpc::model::requests::StopRequest {};
-    let res = cln_client.lock().await.call(Stop(req)).await.unwrap();
+    let res = cln_client.call(Stop(req)).await?;
+
+    Ok(format_json(res).into())
+}
 
-    format_json(res)
+pub fn summarize(_options: &CommandOptions) -> String {
+    "Stop the Core Lightning node. The bot can't reach the node again until it is \
+     restarted."
+        .to_string()
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
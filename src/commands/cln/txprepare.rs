@@ -1,24 +1,19 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::TxprepareRequest;
 use cln_rpc::primitives::{Feerate, Outpoint, OutputDesc};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::TxPrepare;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::application_command::CommandDataOption;
 use serenity::model::prelude::command::CommandOptionType;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let outputs: Vec<OutputDesc> = get_option_as(&options_map, "outputs").unwrap();
-    let feerate: Option<Feerate> = get_option_as(&options_map, "feerate");
-    let minconf: Option<u32> = get_option_as(&options_map, "minconf");
-    let utxos: Option<Vec<Outpoint>> = get_option_as(&options_map, "utxos");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let outputs: Vec<OutputDesc> = get_required_option_as(options, "outputs")?;
+    let feerate: Option<Feerate> = get_option_as(options, "feerate")?;
+    let minconf: Option<u32> = get_option_as(options, "minconf")?;
+    let utxos: Option<Vec<Outpoint>> = get_option_as(options, "utxos")?;
 
     let req = TxprepareRequest {
         outputs,
@@ -27,10 +22,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         utxos,
     };
 
-    match cln_client.lock().await.call(TxPrepare(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(TxPrepare(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,28 +1,32 @@
 // src/commands/cln/txsend.rs
-use std::sync::Arc;
 
 use cln_rpc::model::requests::TxsendRequest;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::TxSend;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::application_command::CommandDataOption;
 use serenity::model::prelude::command::CommandOptionType;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::get_required_option_as;
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let txid: String = get_option_as(&options_map, "txid").unwrap();
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let txid: String = get_required_option_as(options, "txid")?;
 
     let req = TxsendRequest { txid };
 
-    match cln_client.lock().await.call(TxSend(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(TxSend(req)).await?;
+
+    Ok(format_json(res).into())
+}
+
+pub fn summarize(options: &CommandOptions) -> Result<String, CommandError> {
+    let txid: String = get_required_option_as(options, "txid")?;
+
+    Ok(format!(
+        "Sign and broadcast the prepared transaction `{}`",
+        txid
+    ))
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,31 +1,25 @@
-use std::sync::Arc;
-
 use cln_rpc::model::requests::UtxopsbtRequest;
 use cln_rpc::primitives::{Amount, Feerate, Outpoint};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::UtxoPsbt;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::application_command::CommandDataOption;
 use serenity::model::prelude::command::CommandOptionType;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let satoshi: Amount = get_option_as(&options_map, "satoshi").unwrap();
-    let feerate: Feerate = get_option_as(&options_map, "feerate").unwrap();
-    let startweight: u32 = get_option_as(&options_map, "startweight").unwrap();
-    let utxos: Vec<Outpoint> = get_option_as(&options_map, "utxos").unwrap();
-    let reserve: Option<u32> = get_option_as(&options_map, "reserve");
-    let reservedok: Option<bool> = get_option_as(&options_map, "reservedok");
-    let locktime: Option<u32> = get_option_as(&options_map, "locktime");
-    let min_witness_weight: Option<u32> = get_option_as(&options_map, "min_witness_weight");
-    let excess_as_change: Option<bool> = get_option_as(&options_map, "excess_as_change");
-    let opening_anchor_channel: Option<bool> =
-        get_option_as(&options_map, "opening_anchor_channel");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let satoshi: Amount = get_required_option_as(options, "satoshi")?;
+    let feerate: Feerate = get_required_option_as(options, "feerate")?;
+    let startweight: u32 = get_required_option_as(options, "startweight")?;
+    let utxos: Vec<Outpoint> = get_required_option_as(options, "utxos")?;
+    let reserve: Option<u32> = get_option_as(options, "reserve")?;
+    let reservedok: Option<bool> = get_option_as(options, "reservedok")?;
+    let locktime: Option<u32> = get_option_as(options, "locktime")?;
+    let min_witness_weight: Option<u32> = get_option_as(options, "min_witness_weight")?;
+    let excess_as_change: Option<bool> = get_option_as(options, "excess_as_change")?;
+    let opening_anchor_channel: Option<bool> = get_option_as(options, "opening_anchor_channel")?;
 
     let req = UtxopsbtRequest {
         satoshi,
@@ -40,10 +34,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         opening_anchor_channel,
     };
 
-    match cln_client.lock().await.call(UtxoPsbt(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(UtxoPsbt(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,23 +1,18 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::Sha256;
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::WaitSendPay;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::application_command::CommandDataOption;
 use serenity::model::prelude::command::CommandOptionType;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let payment_hash: Sha256 = get_option_as(&options_map, "payment_hash").unwrap();
-    let timeout: Option<u32> = get_option_as(&options_map, "timeout");
-    let partid: Option<u64> = get_option_as(&options_map, "partid");
-    let groupid: Option<u64> = get_option_as(&options_map, "groupid");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let payment_hash: Sha256 = get_required_option_as(options, "payment_hash")?;
+    let timeout: Option<u32> = get_option_as(options, "timeout")?;
+    let partid: Option<u64> = get_option_as(options, "partid")?;
+    let groupid: Option<u64> = get_option_as(options, "groupid")?;
 
     let req = cln_rpc::model::requests::WaitsendpayRequest {
         payment_hash,
@@ -26,10 +21,9 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         groupid,
     };
 
-    match cln_client.lock().await.call(WaitSendPay(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(WaitSendPay(req)).await?;
+
+    Ok(format_json(res).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
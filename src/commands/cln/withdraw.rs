@@ -1,24 +1,19 @@
-use std::sync::Arc;
-
 use cln_rpc::primitives::{AmountOrAll, Feerate, Outpoint};
-use cln_rpc::ClnRpc;
 use cln_rpc::Request::Withdraw;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::application_command::CommandDataOption;
 use serenity::model::prelude::command::CommandOptionType;
-use tokio::sync::Mutex;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::ClnClient;
 use crate::utils::format_json;
-use crate::utils::get_option_as::get_option_as;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 
-pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let destination: String = get_option_as(&options_map, "destination").unwrap();
-    let amount: AmountOrAll = get_option_as(&options_map, "amount").unwrap();
-    let feerate: Option<Feerate> = get_option_as(&options_map, "feerate");
-    let minconf: Option<u16> = get_option_as(&options_map, "minconf");
-    let utxos: Option<Vec<Outpoint>> = get_option_as(&options_map, "utxos");
+pub async fn run(options: &CommandOptions, cln_client: &ClnClient) -> CommandResult {
+    let destination: String = get_required_option_as(options, "destination")?;
+    let amount: AmountOrAll = get_required_option_as(options, "amount")?;
+    let feerate: Option<Feerate> = get_option_as(options, "feerate")?;
+    let minconf: Option<u16> = get_option_as(options, "minconf")?;
+    let utxos: Option<Vec<Outpoint>> = get_option_as(options, "utxos")?;
 
     let req = cln_rpc::model::requests::WithdrawRequest {
         destination,
@@ -28,10 +23,22 @@ pub async fn run(options: &[CommandDataOption], cln_client: &Arc<Mutex<ClnRpc>>)
         utxos,
     };
 
-    match cln_client.lock().await.call(Withdraw(req)).await {
-        Ok(res) => format_json(res),
-        Err(e) => format!("Error: {}", e),
-    }
+    let res = cln_client.call(Withdraw(req)).await?;
+
+    Ok(format_json(res).into())
+}
+
+pub fn summarize(options: &CommandOptions) -> Result<String, CommandError> {
+    let destination: String = get_required_option_as(options, "destination")?;
+    let amount: String = get_required_option_as(options, "amount")?;
+    let feerate: Option<String> = get_option_as(options, "feerate")?;
+
+    Ok(format!(
+        "Withdraw `{}` on-chain to `{}`\nFeerate: {}",
+        amount,
+        destination,
+        feerate.as_deref().unwrap_or("normal")
+    ))
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
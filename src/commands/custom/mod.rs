@@ -1,7 +1,7 @@
+use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::application_command::CommandData;
-use serenity::prelude::Context;
 
-use crate::utils::discord_utils::create_and_log_command;
+use crate::commands::CommandResult;
 
 pub mod ping;
 pub mod user_id;
@@ -22,18 +22,23 @@ impl From<&str> for CustomCommand {
     }
 }
 
-pub async fn ready(ctx: &Context) {
-    let commands = vec![user_id::register, ping::register];
+pub fn commands() -> Vec<CreateApplicationCommand> {
+    let registers = vec![user_id::register, ping::register];
 
-    for command in commands {
-        create_and_log_command(&ctx.http, command).await;
-    }
+    registers
+        .into_iter()
+        .map(|register| {
+            let mut command = CreateApplicationCommand::default();
+            register(&mut command);
+            command
+        })
+        .collect()
 }
 
-pub async fn handle_run(command_name: &str, command_data: &CommandData) -> String {
+pub async fn handle_run(command_name: &str, command_data: &CommandData) -> CommandResult {
     match CustomCommand::from(command_name) {
         CustomCommand::Id => user_id::run(&command_data.options),
         CustomCommand::Ping => ping::run(&command_data.options),
-        CustomCommand::Unknown => format!("Unknown command: {}", command_name),
+        CustomCommand::Unknown => Ok(format!("Unknown command: {}", command_name).into()),
     }
 }
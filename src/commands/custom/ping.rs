@@ -1,8 +1,10 @@
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::prelude::application_command::CommandDataOption;
 
-pub fn run(_options: &[CommandDataOption]) -> String {
-    "Hey, I'm alive!".to_string()
+use crate::commands::CommandResult;
+
+pub fn run(_options: &[CommandDataOption]) -> CommandResult {
+    Ok("Hey, I'm alive!".to_string().into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -4,18 +4,19 @@ use serenity::model::prelude::interaction::application_command::{
     CommandDataOption, CommandDataOptionValue,
 };
 
-pub fn run(options: &[CommandDataOption]) -> String {
+use crate::commands::{CommandError, CommandResult};
+
+pub fn run(options: &[CommandDataOption]) -> CommandResult {
     let option = options
-        .first()
-        .expect("Expected user option")
-        .resolved
-        .as_ref()
-        .expect("Expected user object");
+        .iter()
+        .find(|opt| opt.name == "id")
+        .and_then(|opt| opt.resolved.as_ref());
 
-    if let CommandDataOptionValue::User(user, _member) = option {
-        format!("{}'s id is {}", user.tag(), user.id)
-    } else {
-        "Please provide a valid user".to_string()
+    match option {
+        Some(CommandDataOptionValue::User(user, _member)) => {
+            Ok(format!("{}'s id is {}", user.tag(), user.id).into())
+        }
+        _ => Err(CommandError::MissingOption("id".to_string())),
     }
 }
 
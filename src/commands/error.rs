@@ -0,0 +1,158 @@
+use std::fmt;
+
+use serenity::builder::CreateEmbed;
+use serenity::utils::Colour;
+
+use super::CommandResponse;
+use crate::ledger::Rejected;
+
+pub type CommandResult = Result<CommandResponse, CommandError>;
+
+/// Why a command failed, shown to the user in place of its reply
+#[derive(Debug, Clone)]
+pub enum CommandError {
+    /// A required option wasn't provided
+    MissingOption(String),
+    /// None of several options, one of which is required, was provided
+    MissingOneOf(Vec<String>),
+    /// Options that exclude each other were provided together
+    ConflictingOptions(Vec<String>),
+    /// An option couldn't be parsed as the type the command expects
+    InvalidOption {
+        name: String,
+        expected: String,
+        reason: String,
+    },
+    /// The Lightning node rejected the request
+    Rpc(String),
+    /// The federation client, or the ledger backed by it, failed
+    Federation(String),
+    /// The ledger refused the request, e.g. for spending more than the balance
+    Ledger(String),
+}
+
+impl CommandError {
+    pub fn invalid_option(
+        name: impl Into<String>,
+        expected: impl Into<String>,
+        reason: impl ToString,
+    ) -> Self {
+        CommandError::InvalidOption {
+            name: name.into(),
+            expected: expected.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn rpc(e: impl ToString) -> Self {
+        CommandError::Rpc(e.to_string())
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            CommandError::MissingOption(_) | CommandError::MissingOneOf(_) => "Missing option",
+            CommandError::ConflictingOptions(_) => "Conflicting options",
+            CommandError::InvalidOption { .. } => "Invalid option",
+            CommandError::Rpc(_) => "Lightning node error",
+            CommandError::Federation(_) => "Federation error",
+            CommandError::Ledger(_) => "Balance error",
+        }
+    }
+
+    pub fn embed(&self, command_name: &str) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        embed
+            .title(format!("`{}` failed: {}", command_name, self.title()))
+            .description(self.to_string())
+            .colour(Colour::RED);
+        embed
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::MissingOption(name) => write!(f, "Missing required option `{}`", name),
+            CommandError::MissingOneOf(names) => {
+                write!(f, "Provide one of the options {}", list(names, "or"))
+            }
+            CommandError::ConflictingOptions(names) => {
+                write!(f, "Options {} can't be used together", list(names, "and"))
+            }
+            CommandError::InvalidOption {
+                name,
+                expected,
+                reason,
+            } => write!(f, "Option `{}` must be a {}: {}", name, expected, reason),
+            CommandError::Rpc(e) => write!(f, "{}", e),
+            CommandError::Federation(e) => write!(f, "{}", e),
+            CommandError::Ledger(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+// Lists option names as "`a`, `b` or `c`"
+fn list(names: &[String], conjunction: &str) -> String {
+    let names = names
+        .iter()
+        .map(|name| format!("`{}`", name))
+        .collect::<Vec<_>>();
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => {
+            format!("{} {} {}", rest.join(", "), conjunction, last)
+        }
+        _ => names.concat(),
+    }
+}
+
+impl From<cln_rpc::RpcError> for CommandError {
+    fn from(e: cln_rpc::RpcError) -> Self {
+        CommandError::Rpc(e.to_string())
+    }
+}
+
+impl From<anyhow::Error> for CommandError {
+    fn from(e: anyhow::Error) -> Self {
+        if e.is::<Rejected>() {
+            CommandError::Ledger(e.to_string())
+        } else {
+            CommandError::Federation(e.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_the_options_to_pick_from() {
+        let names = vec!["notes".to_string(), "amount_msat".to_string()];
+
+        assert_eq!(
+            CommandError::MissingOneOf(names.clone()).to_string(),
+            "Provide one of the options `notes` or `amount_msat`"
+        );
+        assert_eq!(
+            CommandError::ConflictingOptions(names).to_string(),
+            "Options `notes` and `amount_msat` can't be used together"
+        );
+    }
+
+    #[test]
+    fn tells_ledger_rejections_from_failures() {
+        let rejected = anyhow::Error::from(Rejected("Insufficient balance".to_string()));
+        assert!(matches!(
+            CommandError::from(rejected),
+            CommandError::Ledger(_)
+        ));
+
+        let failed = anyhow::anyhow!("Cannot reach the federation");
+        assert!(matches!(
+            CommandError::from(failed),
+            CommandError::Federation(_)
+        ));
+    }
+}
@@ -2,21 +2,17 @@ use fedimint_client::backup::Metadata;
 use fedimint_client::ClientArc;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
-use crate::utils::to_codeblock;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::utils::get_option_as::get_required_option_as;
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let metadata: String = get_option_as(&options_map, "metadata").unwrap();
-    let metadata: Metadata = serde_json::from_str(&metadata).unwrap();
-    let res = fm_client
-        .backup_to_federation(metadata)
-        .await
-        .map_err(|e| e.to_string());
-    to_codeblock(serde_json::to_string_pretty(&res).unwrap())
+pub async fn run(options: &CommandOptions, fm_client: &ClientArc) -> CommandResult {
+    let metadata: String = get_required_option_as(options, "metadata")?;
+    let metadata: Metadata = serde_json::from_str(&metadata)
+        .map_err(|e| CommandError::invalid_option("metadata", "JSON object", e))?;
+    fm_client.backup_to_federation(metadata).await?;
+
+    Ok("Backup uploaded to the federation".to_string().into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
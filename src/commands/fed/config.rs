@@ -1,12 +1,13 @@
 use fedimint_client::ClientArc;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
+use crate::commands::{CommandOptions, CommandResult};
 use crate::utils::to_codeblock;
 
-pub async fn run(_options: &[CommandDataOption], fm_client: &ClientArc) -> String {
+pub async fn run(_options: &CommandOptions, fm_client: &ClientArc) -> CommandResult {
     let res = fm_client.get_config_json();
-    to_codeblock(serde_json::to_string_pretty(&res).unwrap())
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,15 +1,13 @@
 use fedimint_client::ClientArc;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
+use crate::commands::{CommandOptions, CommandResult};
 use crate::utils::to_codeblock;
 
-pub async fn run(_options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let res = fm_client
-        .discover_common_api_version()
-        .await
-        .map_err(|e| e.to_string());
-    to_codeblock(serde_json::to_string_pretty(&res).unwrap())
+pub async fn run(_options: &CommandOptions, fm_client: &ClientArc) -> CommandResult {
+    let res = fm_client.discover_common_api_version().await?;
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
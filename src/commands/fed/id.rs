@@ -1,11 +1,12 @@
 use fedimint_client::ClientArc;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::prelude::interaction::application_command::CommandDataOption;
 
-pub async fn run(_options: &[CommandDataOption], fm_client: &ClientArc) -> String {
+use crate::commands::{CommandOptions, CommandResult};
+
+pub async fn run(_options: &CommandOptions, fm_client: &ClientArc) -> CommandResult {
     let federation_id = fm_client.federation_id();
 
-    format!("Your federation id is: {}", federation_id)
+    Ok(format!("Your federation id is: {}", federation_id).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
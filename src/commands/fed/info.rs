@@ -7,8 +7,8 @@ use fedimint_mint_client::MintClientModule;
 use fedimint_wallet_client::WalletClientModule;
 use serde::Serialize;
 use serenity::builder::CreateApplicationCommand;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
+use crate::commands::{CommandOptions, CommandResult};
 use crate::utils::to_codeblock;
 
 #[derive(Debug, Serialize)]
@@ -22,18 +22,23 @@ pub struct InfoResponse {
     pub denominations_msat: TieredSummary,
 }
 
-pub async fn run(_options: &[CommandDataOption], fm_client: &ClientArc) -> String {
+/// The notes the client holds, by denomination
+pub async fn note_summary(fm_client: &ClientArc) -> TieredSummary {
     let mint_client = fm_client.get_first_module::<MintClientModule>();
-    let wallet_client = fm_client.get_first_module::<WalletClientModule>();
-    let summary = mint_client
+    mint_client
         .get_wallet_summary(
             &mut fm_client
                 .db()
                 .begin_transaction_nc()
                 .await
-                .to_ref_with_prefix_module_id(1),
+                .to_ref_with_prefix_module_id(mint_client.id),
         )
-        .await;
+        .await
+}
+
+pub async fn run(_options: &CommandOptions, fm_client: &ClientArc) -> CommandResult {
+    let wallet_client = fm_client.get_first_module::<WalletClientModule>();
+    let summary = note_summary(fm_client).await;
     let response = InfoResponse {
         federation_id: fm_client.federation_id(),
         network: wallet_client.get_network().to_string(),
@@ -43,7 +48,7 @@ pub async fn run(_options: &[CommandDataOption], fm_client: &ClientArc) -> Strin
         denominations_msat: summary,
     };
 
-    to_codeblock(serde_json::to_string_pretty(&response).unwrap())
+    Ok(to_codeblock(serde_json::to_string_pretty(&response).unwrap()).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
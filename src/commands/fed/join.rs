@@ -0,0 +1,42 @@
+use std::str::FromStr;
+
+use fedimint_core::api::InviteCode;
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::federations::Federations;
+use crate::utils::get_option_as::get_required_option_as;
+
+pub async fn run(options: &CommandOptions, federations: &Federations) -> CommandResult {
+    let invite_code = get_required_option_as::<String>(options, "invite_code")?;
+    let invite_code = InviteCode::from_str(invite_code.trim())
+        .map_err(|e| CommandError::invalid_option("invite_code", "invite code", e))?;
+
+    let federation = federations.join(invite_code).await?;
+
+    Ok(format!("Joined federation {}", federation.label()).into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![CommandOptionInfo {
+        name: "invite_code",
+        description: "Invite code of the federation to join",
+        kind: CommandOptionType::String,
+        required: true,
+    }];
+    command
+        .name("fm_join")
+        .description("Join another federation");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
@@ -0,0 +1,24 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+
+use crate::commands::CommandResult;
+use crate::federations::{Federation, Federations};
+
+pub async fn run(federation: &Federation, federations: &Federations) -> CommandResult {
+    federations.leave(federation.id()).await?;
+
+    Ok(format!("Left federation {}", federation.label()).into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("fm_leave")
+        .description("Leave a federation joined with /fm_join")
+        .create_option(|opt| {
+            opt.name("federation")
+                .description("The federation to leave")
+                .kind(CommandOptionType::String)
+                .required(true)
+                .set_autocomplete(true)
+        })
+}
@@ -5,13 +5,12 @@ use fedimint_core::core::OperationId;
 use serde::{Deserialize, Serialize};
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 use time::format_description::well_known::iso8601;
 use time::OffsetDateTime;
 
-use crate::commands::CommandOptionInfo;
-use crate::utils::get_option_as::get_option_as;
-use crate::utils::to_codeblock;
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::components::pagination::PagedList;
+use crate::utils::get_option_as::get_required_option_as;
 
 #[derive(Debug, Deserialize)]
 pub struct ListOperationsRequest {
@@ -29,9 +28,8 @@ pub struct OperationOutput {
     pub outcome: Option<serde_json::Value>,
 }
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = crate::commands::discord_command_options_to_map(options);
-    let limit: usize = get_option_as(&options_map, "limit").unwrap();
+pub async fn run(options: &CommandOptions, fm_client: &ClientArc) -> CommandResult {
+    let limit: usize = get_required_option_as(options, "limit")?;
     const ISO8601_CONFIG: iso8601::EncodedConfig = iso8601::Config::DEFAULT
         .set_formatted_components(iso8601::FormattedComponents::DateTime)
         .encode();
@@ -60,7 +58,9 @@ pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String
             }
         })
         .collect::<Vec<_>>();
-    to_codeblock(serde_json::to_string_pretty(&operations).unwrap())
+    Ok(PagedList::new("Operations")
+        .with_items("Operation", "id", operations)
+        .into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -2,45 +2,62 @@ use std::str::FromStr;
 
 use fedimint_client::ClientArc;
 use fedimint_core::core::OperationId;
+use fedimint_core::Amount;
 use fedimint_ln_client::{LightningClientModule, LnReceiveState};
 use futures::StreamExt;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
 use crate::commands::fed::ln::get_note_summary;
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::pending::{OperationKind, StartedOperation};
+use crate::utils::get_option_as::get_required_option_as;
+use crate::utils::progress::Progress;
 use crate::utils::to_codeblock;
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let operation_id = get_option_as::<String>(&options_map, "operation_id").unwrap();
-    let operation_id = OperationId::from_str(&operation_id).unwrap();
+pub async fn run(
+    options: &CommandOptions,
+    fm_client: &ClientArc,
+    progress: &Progress,
+) -> CommandResult {
+    let operation_id = get_required_option_as::<String>(options, "operation_id")?;
+    let operation_id = OperationId::from_str(&operation_id)
+        .map_err(|e| CommandError::invalid_option("operation_id", "OperationId", e))?;
+
+    progress.operation_started(StartedOperation {
+        federation_id: fm_client.federation_id(),
+        operation_id,
+        kind: OperationKind::LnReceive,
+        refund: Amount::ZERO,
+        background: false,
+    });
 
     let lightning_module = &fm_client.get_first_module::<LightningClientModule>();
-    let updates = lightning_module.subscribe_ln_receive(operation_id).await;
-    let mut updates = match updates {
-        Ok(updates) => updates.into_stream(),
-        Err(e) => return format!("Error: {}", e),
-    };
+    let mut updates = lightning_module
+        .subscribe_ln_receive(operation_id)
+        .await?
+        .into_stream();
     while let Some(update) = updates.next().await {
+        progress.update(format!("Ln receive {}: {:?}", operation_id, update));
         match update {
             LnReceiveState::Claimed => {
-                let res = get_note_summary(&fm_client).await;
-                match res {
-                    Ok(res) => return to_codeblock(serde_json::to_string_pretty(&res).unwrap()),
-                    Err(e) => return format!("Error: {}", e),
-                }
+                let res = get_note_summary(&fm_client).await?;
+                return Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into());
             }
             LnReceiveState::Canceled { reason } => {
-                return format!("Ln receive payment cancelled: {}", reason);
+                return Err(CommandError::Federation(format!(
+                    "Ln receive payment cancelled: {}",
+                    reason
+                )));
             }
             _ => {}
         }
     }
 
-    format!("Error: end of stream for operation_id {}", operation_id)
+    Err(CommandError::Federation(format!(
+        "End of stream for operation_id {}",
+        operation_id
+    )))
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -60,6 +77,7 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
                 .description(opt_info.description)
                 .kind(opt_info.kind)
                 .required(opt_info.required)
+                .set_autocomplete(opt_info.name == "operation_id")
         });
     }
 
@@ -5,44 +5,51 @@ use fedimint_core::core::OperationId;
 use fedimint_ln_client::{LightningClientModule, PayType};
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
-use super::wait_for_ln_payment;
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use super::{wait_for_ln_payment, LnPayResponse};
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::utils::get_option_as::get_required_option_as;
+use crate::utils::progress::Progress;
 use crate::utils::to_codeblock;
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let operation_id = get_option_as::<String>(&options_map, "operation_id").unwrap();
-    let operation_id = OperationId::from_str(&operation_id).unwrap();
+pub async fn run(
+    options: &CommandOptions,
+    fm_client: &ClientArc,
+    progress: &Progress,
+) -> CommandResult {
+    let operation_id = get_required_option_as::<String>(options, "operation_id")?;
+    let operation_id = OperationId::from_str(&operation_id)
+        .map_err(|e| CommandError::invalid_option("operation_id", "OperationId", e))?;
+    let res = wait(fm_client, operation_id, progress).await?;
 
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+/// Waits for the payment to succeed or fail
+pub async fn wait(
+    fm_client: &ClientArc,
+    operation_id: OperationId,
+    progress: &Progress,
+) -> anyhow::Result<LnPayResponse> {
     let lightning_module = fm_client.get_first_module::<LightningClientModule>();
-    let ln_pay_details = lightning_module.get_ln_pay_details_for(operation_id).await;
-    let ln_pay_details = match ln_pay_details {
-        Ok(ln_pay_details) => ln_pay_details,
-        Err(e) => return format!("Error: {}", e),
-    };
+    let ln_pay_details = lightning_module
+        .get_ln_pay_details_for(operation_id)
+        .await?;
     let payment_type = if ln_pay_details.is_internal_payment {
         PayType::Internal(operation_id)
     } else {
         PayType::Lightning(operation_id)
     };
-    let res = wait_for_ln_payment(
-        &fm_client,
+
+    wait_for_ln_payment(
+        fm_client,
         payment_type,
         ln_pay_details.contract_id.to_string(),
         false,
+        progress,
     )
-    .await;
-    match res {
-        Ok(Some(res)) => {
-            let res = to_codeblock(serde_json::to_string_pretty(&res).unwrap());
-            res
-        }
-        Ok(None) => format!("Error: end of stream for operation_id {}", operation_id),
-        Err(e) => format!("Error: {}", e),
-    }
+    .await?
+    .ok_or_else(|| anyhow::anyhow!("End of stream for operation_id {}", operation_id))
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -62,6 +69,7 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
                 .description(opt_info.description)
                 .kind(opt_info.kind)
                 .required(opt_info.required)
+                .set_autocomplete(opt_info.name == "operation_id")
         });
     }
 
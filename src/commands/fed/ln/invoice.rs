@@ -1,14 +1,18 @@
 use fedimint_client::ClientArc;
 use fedimint_core::core::OperationId;
 use fedimint_core::Amount;
-use fedimint_ln_client::LightningClientModule;
+use fedimint_ln_client::{LightningClientModule, LnReceiveState};
+use futures::StreamExt;
 use serde::Serialize;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
+use serenity::model::id::UserId;
+use tracing::{error, info};
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::federations::Federations;
+use crate::ledger::Ledger;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 use crate::utils::to_codeblock;
 
 #[derive(Debug, Serialize)]
@@ -17,31 +21,111 @@ pub struct LnInvoiceResponse {
     pub invoice: String,
 }
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let amount_msat = get_option_as::<u64>(&options_map, "amount_msat").unwrap();
+pub async fn run(
+    options: &CommandOptions,
+    fm_client: &ClientArc,
+    ledger: &Ledger,
+    user_id: UserId,
+) -> CommandResult {
+    let amount_msat = get_required_option_as::<u64>(options, "amount_msat")?;
     let amount_msat = Amount::from_msats(amount_msat);
-    let description = get_option_as::<String>(&options_map, "description").unwrap();
-    let expiry_time = get_option_as::<u64>(&options_map, "expiry_time").unwrap_or(3600);
+    let description = get_required_option_as::<String>(options, "description")?;
+    let expiry_time = get_option_as::<u64>(options, "expiry_time")?.unwrap_or(3600);
+
+    let res = create_invoice(fm_client, amount_msat, description, expiry_time).await?;
+    credit_on_claim(
+        fm_client.clone(),
+        ledger.clone(),
+        user_id,
+        res.operation_id,
+        amount_msat,
+    )
+    .await?;
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+pub async fn create_invoice(
+    fm_client: &ClientArc,
+    amount_msat: Amount,
+    description: String,
+    expiry_time: u64,
+) -> anyhow::Result<LnInvoiceResponse> {
     let lightning_module = fm_client.get_first_module::<LightningClientModule>();
-    let res = lightning_module.select_active_gateway().await;
-    if let Err(e) = res {
-        return format!("Error: {}", e);
-    }
+    lightning_module.select_active_gateway().await?;
 
-    let res = lightning_module
+    let (operation_id, invoice) = lightning_module
         .create_bolt11_invoice(amount_msat, description, Some(expiry_time), ())
-        .await;
+        .await?;
 
-    let res = match res {
-        Ok(res) => LnInvoiceResponse {
-            operation_id: res.0,
-            invoice: res.1.to_string(),
-        },
-        Err(e) => return format!("Error: {}", e),
+    Ok(LnInvoiceResponse {
+        operation_id,
+        invoice: invoice.to_string(),
+    })
+}
+
+/// Waits in the background for the invoice to be paid and credits its amount
+/// to the user's balance once the federation has claimed the funds. The
+/// deposit is kept in the ledger until then, so `resume_claims` picks it up
+/// again after a restart.
+pub async fn credit_on_claim(
+    fm_client: ClientArc,
+    ledger: Ledger,
+    user_id: UserId,
+    operation_id: OperationId,
+    amount_msat: Amount,
+) -> anyhow::Result<()> {
+    ledger
+        .expect_claim(operation_id, user_id, amount_msat)
+        .await?;
+    tokio::spawn(watch_claim(fm_client, ledger, operation_id));
+    Ok(())
+}
+
+/// Watches again the deposits left unclaimed by the previous run
+pub fn resume_claims(federations: &Federations) {
+    for federation in federations.list() {
+        tokio::spawn(async move {
+            for operation_id in federation.ledger.pending_claims().await {
+                info!("Resuming deposit {}", operation_id);
+                tokio::spawn(watch_claim(
+                    federation.client.clone(),
+                    federation.ledger.clone(),
+                    operation_id,
+                ));
+            }
+        });
+    }
+}
+
+async fn watch_claim(fm_client: ClientArc, ledger: Ledger, operation_id: OperationId) {
+    let lightning_module = fm_client.get_first_module::<LightningClientModule>();
+    let mut updates = match lightning_module.subscribe_ln_receive(operation_id).await {
+        Ok(updates) => updates.into_stream(),
+        Err(e) => {
+            error!("Cannot subscribe to ln receive {}: {}", operation_id, e);
+            return;
+        }
     };
 
-    to_codeblock(serde_json::to_string_pretty(&res).unwrap())
+    while let Some(update) = updates.next().await {
+        match update {
+            LnReceiveState::Claimed => {
+                if let Err(e) = ledger.settle_claim(operation_id).await {
+                    error!("Cannot credit deposit {}: {}", operation_id, e);
+                }
+                return;
+            }
+            LnReceiveState::Canceled { reason } => {
+                info!("Ln receive {} canceled: {}", operation_id, reason);
+                if let Err(e) = ledger.cancel_claim(operation_id).await {
+                    error!("Cannot forget deposit {}: {}", operation_id, e);
+                }
+                return;
+            }
+            _ => {}
+        }
+    }
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::str::FromStr;
 
 use anyhow::{bail, Context};
@@ -13,6 +14,7 @@ use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
 use super::info::InfoResponse;
+use crate::utils::progress::Progress;
 
 pub mod await_invoice;
 pub mod await_pay;
@@ -106,11 +108,32 @@ pub struct LnPayResponse {
     pub fee: Amount,
 }
 
+/// A payment that failed for good, leaving its funds with the client: the
+/// gateway refunded it, it was canceled or it was never funded. Other errors
+/// waiting on a payment don't say whether it may still succeed.
+#[derive(Debug)]
+pub struct PaymentFailed(pub String);
+
+impl fmt::Display for PaymentFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for PaymentFailed {}
+
+/// Whether `e` is a payment's final failure, after which its funds can be
+/// returned to the user
+pub fn is_payment_failed(e: &anyhow::Error) -> bool {
+    e.is::<PaymentFailed>()
+}
+
 pub async fn wait_for_ln_payment(
     client: &ClientArc,
     payment_type: PayType,
     contract_id: String,
     return_on_funding: bool,
+    progress: &Progress,
 ) -> anyhow::Result<Option<LnPayResponse>> {
     let lightning_module = client.get_first_module::<LightningClientModule>();
     lightning_module.select_active_gateway().await?;
@@ -123,6 +146,7 @@ pub async fn wait_for_ln_payment(
                 .into_stream();
 
             while let Some(update) = updates.next().await {
+                progress.update(format!("Internal payment {operation_id}: {update:?}"));
                 match update {
                     InternalPayState::Preimage(_preimage) => {
                         return Ok(Some(LnPayResponse {
@@ -137,7 +161,7 @@ pub async fn wait_for_ln_payment(
                             "Internal payment failed. A refund was issued to {:?} Error: {error}",
                             out_points
                         );
-                        bail!("{e}");
+                        return Err(PaymentFailed(e).into());
                     }
                     InternalPayState::UnexpectedError(e) => {
                         bail!("{e}");
@@ -149,7 +173,7 @@ pub async fn wait_for_ln_payment(
                         error,
                     } => bail!("RefundError: {error_message} {error}"),
                     InternalPayState::FundingFailed { error } => {
-                        bail!("FundingFailed: {error}")
+                        return Err(PaymentFailed(format!("FundingFailed: {error}")).into());
                     }
                 }
                 info!("Update: {update:?}");
@@ -162,6 +186,7 @@ pub async fn wait_for_ln_payment(
                 .into_stream();
 
             while let Some(update) = updates.next().await {
+                progress.update(format!("Lightning payment {operation_id}: {update:?}"));
                 let update_clone = update.clone();
                 match update_clone {
                     LnPayState::Success { preimage: _ } => {
@@ -174,10 +199,10 @@ pub async fn wait_for_ln_payment(
                     }
                     LnPayState::Refunded { gateway_error } => {
                         info!("{gateway_error}");
-                        Err(anyhow::anyhow!("Payment was refunded"))?;
+                        return Err(PaymentFailed("Payment was refunded".to_string()).into());
                     }
                     LnPayState::Canceled => {
-                        Err(anyhow::anyhow!("Payment was canceled"))?;
+                        return Err(PaymentFailed("Payment was canceled".to_string()).into());
                     }
                     LnPayState::Created
                     | LnPayState::AwaitingChange
@@ -1,79 +1,267 @@
 use fedimint_client::ClientArc;
+use fedimint_core::core::OperationId;
 use fedimint_core::Amount;
-use fedimint_ln_client::{LightningClientModule, OutgoingLightningPayment};
+use fedimint_ln_client::{LightningClientModule, OutgoingLightningPayment, PayType};
+use lightning_invoice::Bolt11InvoiceDescription;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
-use tracing::info;
+use serenity::model::id::UserId;
+use tracing::{error, info};
 
-use super::{get_invoice, wait_for_ln_payment};
+use super::{get_invoice, is_payment_failed, wait_for_ln_payment};
 use crate::commands::fed::ln::LnPayRequest;
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::ledger::Ledger;
+use crate::pending::{OperationKind, StartedOperation};
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
+use crate::utils::progress::Progress;
 use crate::utils::to_codeblock;
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let payment_info = get_option_as::<String>(&options_map, "payment_info").unwrap();
-    let amount_msat = get_option_as::<u64>(&options_map, "amount_msat").unwrap();
-    let amount_msat = Amount::from_msats(amount_msat);
+pub async fn run(
+    options: &CommandOptions,
+    fm_client: &ClientArc,
+    ledger: &Ledger,
+    user_id: UserId,
+    progress: &Progress,
+) -> CommandResult {
+    let req = pay_request(options)?;
+
+    Ok(pay_from_balance(fm_client, ledger, user_id, req, progress)
+        .await?
+        .into())
+}
+
+fn pay_request(options: &CommandOptions) -> Result<LnPayRequest, CommandError> {
+    let payment_info = get_required_option_as::<String>(options, "payment_info")?;
+    let amount_msat = get_option_as::<u64>(options, "amount_msat")?.map(Amount::from_msats);
     let finish_in_background =
-        get_option_as::<bool>(&options_map, "finish_in_background").unwrap_or(false);
+        get_option_as::<bool>(options, "finish_in_background")?.unwrap_or(false);
     let lnurl_comment =
-        get_option_as::<String>(&options_map, "lnurl_comment").unwrap_or("".to_string());
-    let req = LnPayRequest {
+        get_option_as::<String>(options, "lnurl_comment")?.unwrap_or("".to_string());
+    Ok(LnPayRequest {
         payment_info,
-        amount_msat: Some(amount_msat),
+        amount_msat,
         finish_in_background,
         lnurl_comment: Some(lnurl_comment),
-    };
+    })
+}
 
-    let bolt11 = get_invoice(&req).await;
-    let bolt11 = match bolt11 {
-        Ok(bolt11) => bolt11,
-        Err(e) => return format!("Error: {}", e),
+/// Describes the payment `run` would make: the decoded invoice and the most
+/// the active gateway can charge for it
+pub async fn summarize(
+    options: &CommandOptions,
+    fm_client: &ClientArc,
+) -> Result<String, CommandError> {
+    let req = pay_request(options)?;
+    let bolt11 = get_invoice(&req).await?;
+    let invoice_amount = Amount::from_msats(bolt11.amount_milli_satoshis().unwrap_or_default());
+    let max_fee = max_gateway_fee(fm_client, invoice_amount).await?;
+    let description = match bolt11.description() {
+        Bolt11InvoiceDescription::Direct(description) => description.to_string(),
+        Bolt11InvoiceDescription::Hash(_) => "(description hash)".to_string(),
     };
+
+    Ok(format!(
+        "Pay {} to `{}`\nDescription: {}\nMaximum gateway fee: {}\nThe amount and fee are \
+         debited from your balance, unused fees are refunded.",
+        invoice_amount,
+        bolt11.recover_payee_pub_key(),
+        description,
+        max_fee
+    ))
+}
+
+// The most the active gateway charges to route a payment of `amount`
+async fn max_gateway_fee(fm_client: &ClientArc, amount: Amount) -> anyhow::Result<Amount> {
+    let gateway = fm_client
+        .get_first_module::<LightningClientModule>()
+        .select_active_gateway()
+        .await?;
+
+    Ok(Amount::from_msats(
+        gateway.fees.base_msat as u64
+            + amount.msats * gateway.fees.proportional_millionths as u64 / 1_000_000,
+    ))
+}
+
+/// Pays an invoice out of the user's balance. The invoice amount plus the
+/// maximum fee of the active gateway is debited before paying; whatever isn't
+/// spent is refunded once the actual fee is known or the payment fails.
+pub async fn pay_from_balance(
+    fm_client: &ClientArc,
+    ledger: &Ledger,
+    user_id: UserId,
+    req: LnPayRequest,
+    progress: &Progress,
+) -> Result<String, CommandError> {
+    let bolt11 = get_invoice(&req).await?;
+    let invoice_amount = Amount::from_msats(bolt11.amount_milli_satoshis().unwrap_or_default());
     let lightning_module = fm_client.get_first_module::<LightningClientModule>();
-    let res = lightning_module.select_active_gateway().await;
-    if let Err(e) = res {
-        return format!("Error: {}", e);
-    }
+    let max_fee = max_gateway_fee(fm_client, invoice_amount).await?;
+    let reserved = invoice_amount + max_fee;
+    ledger.debit(user_id, reserved).await?;
 
     let res = lightning_module.pay_bolt11_invoice(bolt11, ()).await;
     let res = match res {
         Ok(res) => res,
-        Err(e) => return format!("Error: {}", e),
+        Err(e) => {
+            refund(ledger, user_id, reserved).await;
+            return Err(e.into());
+        }
     };
     let OutgoingLightningPayment {
         payment_type,
         contract_id,
         fee,
     } = res;
+    let spent = invoice_amount + fee;
+    if spent < reserved {
+        refund(ledger, user_id, reserved - spent).await;
+    } else if spent > reserved {
+        // The gateway raised its fees since they were looked up
+        let extra = spent - reserved;
+        if let Err(e) = ledger.debit(user_id, extra).await {
+            error!(
+                "Cannot debit the extra fee {} from user {}: {}",
+                extra, user_id, e
+            );
+        }
+    }
+
     let operation_id = payment_type.operation_id();
     info!("Gateway fee: {fee}, payment operation id: {operation_id}");
-    if req.finish_in_background {
-        let res =
-            wait_for_ln_payment(&fm_client, payment_type, contract_id.to_string(), true).await;
-        match res {
-        Ok(Some(res)) => {
+    // Payments finishing in the background are forgotten by `refund_on_failure`
+    let started = StartedOperation {
+        federation_id: fm_client.federation_id(),
+        operation_id,
+        kind: OperationKind::LnPay,
+        refund: spent,
+        background: req.finish_in_background,
+    };
+    progress.operation_started(started.clone());
+    let res = wait_for_ln_payment(
+        &fm_client,
+        payment_type,
+        contract_id.to_string(),
+        req.finish_in_background,
+        progress,
+    )
+    .await;
+    match res {
+        Ok(Some(res)) if req.finish_in_background => {
             let pretty_res = serde_json::to_string_pretty(&res).unwrap();
-            return format!("Payment will finish in background, use await-ln-pay to get the result\n{}", to_codeblock(pretty_res));
+            Ok(format!(
+                "Payment will finish in background, use await-ln-pay to get the result\n{}",
+                to_codeblock(pretty_res)
+            ))
+        }
+        Ok(Some(res)) => Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap())),
+        // The payment is funded and still in flight
+        Ok(None) if req.finish_in_background => {
+            refund_on_failure(
+                fm_client.clone(),
+                ledger.clone(),
+                user_id,
+                operation_id,
+                payment_type,
+                contract_id.to_string(),
+                spent,
+            );
+            Ok(format!(
+                "Payment will finish in background, use await-ln-pay to get the result\nOperation id: {}",
+                operation_id
+            ))
         }
-        Ok(None) => return format!("Payment will finish in background, use await-ln-pay to get the result\nError: end of stream for operation_id {}", operation_id),
-        Err(e) => return format!("Payment will finish in background, use await-ln-pay to get the result\nError: {}", e),
+        Ok(None) => Err(CommandError::Federation(format!(
+            "End of stream for operation_id {}",
+            operation_id
+        ))),
+        Err(e) if is_payment_failed(&e) => {
+            refund_operation(ledger, operation_id, user_id, spent).await;
+            Err(e.into())
+        }
+        // The payment may still go through, so it's only refunded once it
+        // fails for good
+        Err(e) => {
+            progress.operation_started(StartedOperation {
+                background: true,
+                ..started
+            });
+            refund_on_failure(
+                fm_client.clone(),
+                ledger.clone(),
+                user_id,
+                operation_id,
+                payment_type,
+                contract_id.to_string(),
+                spent,
+            );
+            Err(CommandError::Federation(format!(
+                "Cannot tell whether the payment went through, you are refunded if it fails: {}\nOperation id: {}",
+                e, operation_id
+            )))
+        }
+    }
+}
+
+/// Credits `amount` back to the user, logging rather than returning a failure
+/// so that the error which led to the refund is the one reported
+pub(crate) async fn refund(ledger: &Ledger, user_id: UserId, amount: Amount) {
+    if let Err(e) = ledger.credit(user_id, amount).await {
+        error!("Cannot refund {} to user {}: {}", amount, user_id, e);
+    }
+}
+
+// Refunds a payment that may also be recorded as pending, which the refund
+// removes so the payment isn't refunded again when resumed
+async fn refund_operation(
+    ledger: &Ledger,
+    operation_id: OperationId,
+    user_id: UserId,
+    amount: Amount,
+) {
+    if let Err(e) = ledger.refund_operation(operation_id, user_id, amount).await {
+        error!("Cannot refund {} to user {}: {}", amount, user_id, e);
     }
-    } else {
-        let res =
-            wait_for_ln_payment(&fm_client, payment_type, contract_id.to_string(), false).await;
+}
+
+// Keeps watching a payment that finishes in the background so the user is
+// refunded if it eventually fails, then forgets it as pending. A restart before
+// then, or losing track of the payment, leaves it to `pending::resume`.
+fn refund_on_failure(
+    fm_client: ClientArc,
+    ledger: Ledger,
+    user_id: UserId,
+    operation_id: OperationId,
+    payment_type: PayType,
+    contract_id: String,
+    amount: Amount,
+) {
+    tokio::spawn(async move {
+        let res = wait_for_ln_payment(
+            &fm_client,
+            payment_type,
+            contract_id,
+            false,
+            &Progress::default(),
+        )
+        .await;
         match res {
-            Ok(Some(res)) => {
-                let res = to_codeblock(serde_json::to_string_pretty(&res).unwrap());
-                res
+            Err(e) if is_payment_failed(&e) => {
+                info!("Background payment failed: {}", e);
+                refund_operation(&ledger, operation_id, user_id, amount).await;
+            }
+            Err(e) => error!(
+                "Cannot learn the outcome of payment {}: {}",
+                operation_id, e
+            ),
+            Ok(_) => {
+                if let Err(e) = ledger.pending_operations().remove(operation_id).await {
+                    error!("Cannot remove pending operation {}: {:?}", operation_id, e);
+                }
             }
-            Ok(None) => format!("Error: end of stream for operation_id {}", operation_id),
-            Err(e) => format!("Error: {}", e),
         }
-    }
+    });
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -0,0 +1,180 @@
+use std::str::FromStr;
+
+use fedimint_client::ClientArc;
+use fedimint_mint_client::OOBNotes;
+use reqwest::redirect::Policy;
+use reqwest::Url;
+use serde::Serialize;
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::utils::get_option_as::get_option_as;
+use crate::utils::to_codeblock;
+
+// Files are only downloaded from where Discord keeps attachments, and only up
+// to this many bytes
+const ATTACHMENT_HOSTS: [&str; 2] = ["cdn.discordapp.com", "media.discordapp.net"];
+const MAX_FILE_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct CombineResponse {
+    pub notes: OOBNotes,
+}
+
+pub async fn run(options: &CommandOptions, _fm_client: &ClientArc) -> CommandResult {
+    let mut notes = String::new();
+    if let Some(inline) = get_option_as::<String>(options, "notes")? {
+        notes.push_str(&inline);
+    }
+    // Discord attachments arrive as the URL of the uploaded file
+    if let Some(url) = get_option_as::<String>(options, "file")? {
+        notes.push('\n');
+        notes.push_str(&download(&url).await?);
+    }
+
+    let notes = parse_notes(&notes)?;
+    let res = CombineResponse {
+        notes: combine(notes)?,
+    };
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+async fn download(url: &str) -> Result<String, CommandError> {
+    let invalid = |e| CommandError::invalid_option("file", "text file of notes", e);
+    let url = attachment_url(url)?;
+    let client = reqwest::Client::builder()
+        .redirect(Policy::none())
+        .build()
+        .map_err(|e| invalid(e.to_string()))?;
+    let mut res = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|res| res.error_for_status())
+        .map_err(|e| invalid(e.to_string()))?;
+
+    let too_large = || invalid(format!("files are limited to {} bytes", MAX_FILE_SIZE));
+    if res.content_length().unwrap_or_default() > MAX_FILE_SIZE as u64 {
+        return Err(too_large());
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = res.chunk().await.map_err(|e| invalid(e.to_string()))? {
+        if body.len() + chunk.len() > MAX_FILE_SIZE {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    String::from_utf8(body).map_err(|e| invalid(e.to_string()))
+}
+
+// The file option holds the URL of the uploaded attachment, but through the
+// API and the CLI it could be any URL
+fn attachment_url(url: &str) -> Result<Url, CommandError> {
+    let url = Url::parse(url)
+        .map_err(|e| CommandError::invalid_option("file", "Discord attachment", e))?;
+    let is_attachment = url.scheme() == "https"
+        && url
+            .host_str()
+            .map_or(false, |host| ATTACHMENT_HOSTS.contains(&host));
+    if !is_attachment {
+        return Err(CommandError::invalid_option(
+            "file",
+            "Discord attachment",
+            format!("{} isn't hosted by Discord", url),
+        ));
+    }
+
+    Ok(url)
+}
+
+// Note strings can be separated by whitespace or commas
+fn parse_notes(notes: &str) -> Result<Vec<OOBNotes>, CommandError> {
+    let notes = notes
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|notes| !notes.is_empty())
+        .map(|notes| {
+            OOBNotes::from_str(notes)
+                .map_err(|e| CommandError::invalid_option("notes", "OOBNotes", e))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if notes.is_empty() {
+        return Err(CommandError::MissingOption("notes".to_string()));
+    }
+
+    Ok(notes)
+}
+
+fn combine(notes: Vec<OOBNotes>) -> Result<OOBNotes, CommandError> {
+    let federation_id_prefix = notes[0].federation_id_prefix();
+    if let Some(other) = notes
+        .iter()
+        .map(|notes| notes.federation_id_prefix())
+        .find(|prefix| *prefix != federation_id_prefix)
+    {
+        return Err(CommandError::invalid_option(
+            "notes",
+            "set of notes from one federation",
+            format!(
+                "got notes from federations {} and {}",
+                federation_id_prefix, other
+            ),
+        ));
+    }
+
+    let combined_notes = notes
+        .iter()
+        .flat_map(|notes| notes.notes().iter_items().map(|(amt, note)| (amt, *note)))
+        .collect();
+
+    Ok(OOBNotes::new(federation_id_prefix, combined_notes))
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![
+        CommandOptionInfo {
+            name: "notes",
+            description: "The notes to combine, separated by spaces or commas",
+            kind: CommandOptionType::String,
+            required: false,
+        },
+        CommandOptionInfo {
+            name: "file",
+            description: "A text file of notes to combine",
+            kind: CommandOptionType::Attachment,
+            required: false,
+        },
+    ];
+
+    command
+        .name("fm_mint_combine")
+        .description("Combine ecash notes into a single notes string");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_downloads_discord_attachments() {
+        assert!(attachment_url("https://cdn.discordapp.com/attachments/1/2/notes.txt").is_ok());
+        assert!(attachment_url("https://media.discordapp.net/attachments/1/2/notes.txt").is_ok());
+        assert!(attachment_url("http://cdn.discordapp.com/attachments/1/2/notes.txt").is_err());
+        assert!(attachment_url("http://169.254.169.254/latest/meta-data").is_err());
+        assert!(attachment_url("https://cdn.discordapp.com.evil.example/notes.txt").is_err());
+        assert!(attachment_url("not a url").is_err());
+    }
+}
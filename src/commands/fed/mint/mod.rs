@@ -1,3 +1,4 @@
+pub mod combine;
 pub mod reissue;
 pub mod spend;
 pub mod split;
@@ -7,10 +7,11 @@ use futures::StreamExt;
 use serde::Serialize;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
+use serenity::model::id::UserId;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::ledger::Ledger;
+use crate::utils::get_option_as::get_required_option_as;
 use crate::utils::to_codeblock;
 
 #[derive(Debug, Serialize)]
@@ -18,34 +19,43 @@ pub struct ReissueResponse {
     pub amount_msat: Amount,
 }
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let notes = get_option_as::<String>(&options_map, "notes").unwrap();
-    let notes: OOBNotes = OOBNotes::from_str(&notes).unwrap();
+pub async fn run(
+    options: &CommandOptions,
+    fm_client: &ClientArc,
+    ledger: &Ledger,
+    user_id: UserId,
+) -> CommandResult {
+    let notes = get_required_option_as::<String>(options, "notes")?;
+    let notes: OOBNotes = OOBNotes::from_str(&notes)
+        .map_err(|e| CommandError::invalid_option("notes", "OOBNotes", e))?;
+
+    let amount_msat = reissue_notes(fm_client, notes).await?;
+    ledger.credit(user_id, amount_msat).await?;
+
+    let res = ReissueResponse { amount_msat };
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+/// Reissues the notes into the bot's wallet, returning their total amount
+/// once the federation has accepted them
+pub async fn reissue_notes(fm_client: &ClientArc, notes: OOBNotes) -> anyhow::Result<Amount> {
     let amount_msat = notes.total_amount();
     let mint = fm_client.get_first_module::<MintClientModule>();
 
-    let operation_id = mint.reissue_external_notes(notes, ()).await;
-    let operation_id = match operation_id {
-        Ok(operation_id) => operation_id,
-        Err(e) => return format!("Error: {}", e),
-    };
-
+    let operation_id = mint.reissue_external_notes(notes, ()).await?;
     let mut updates = mint
         .subscribe_reissue_external_notes(operation_id)
-        .await
-        .unwrap()
+        .await?
         .into_stream();
 
     while let Some(update) = updates.next().await {
         if let fedimint_mint_client::ReissueExternalNotesState::Failed(e) = update {
-            return format!("Error: {}", e);
+            anyhow::bail!(e);
         }
     }
 
-    let res = ReissueResponse { amount_msat };
-
-    to_codeblock(serde_json::to_string_pretty(&res).unwrap())
+    Ok(amount_msat)
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -7,10 +7,12 @@ use fedimint_mint_client::{MintClientModule, OOBNotes, SelectNotesWithAtleastAmo
 use serde::Serialize;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
+use serenity::model::id::UserId;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::fed::ln::pay::refund;
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::ledger::Ledger;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
 use crate::utils::to_codeblock;
 
 #[derive(Debug, Serialize)]
@@ -19,12 +21,42 @@ pub struct SpendResponse {
     pub notes: OOBNotes,
 }
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let amount_msat = get_option_as::<u64>(&options_map, "amount_msat").unwrap();
+pub async fn run(
+    options: &CommandOptions,
+    fm_client: &ClientArc,
+    ledger: &Ledger,
+    user_id: UserId,
+) -> CommandResult {
+    let amount_msat = get_required_option_as::<u64>(options, "amount_msat")?;
     let amount_msat = Amount::from_msats(amount_msat);
-    let allow_overpay = get_option_as::<bool>(&options_map, "allow_overpay").unwrap_or(false);
-    let timeout = get_option_as::<u64>(&options_map, "timeout").unwrap_or(60);
+    let allow_overpay = get_option_as::<bool>(options, "allow_overpay")?.unwrap_or(false);
+    let timeout = get_option_as::<u64>(options, "timeout")?.unwrap_or(60);
+
+    let res = spend_notes(
+        fm_client,
+        ledger,
+        user_id,
+        amount_msat,
+        allow_overpay,
+        timeout,
+    )
+    .await?;
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+/// Spends notes out of the user's balance. The requested amount is debited
+/// before the notes are selected and refunded if the spend doesn't go through.
+/// Unclaimed notes are reissued into the bot's wallet after `timeout` seconds.
+pub async fn spend_notes(
+    fm_client: &ClientArc,
+    ledger: &Ledger,
+    user_id: UserId,
+    amount_msat: Amount,
+    allow_overpay: bool,
+    timeout: u64,
+) -> anyhow::Result<SpendResponse> {
+    ledger.debit(user_id, amount_msat).await?;
 
     let mint_module = fm_client.get_first_module::<MintClientModule>();
     let timeout = Duration::from_secs(timeout);
@@ -33,26 +65,32 @@ pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String
         .await;
     let (operation_id, notes) = match res {
         Ok(res) => res,
-        Err(e) => return format!("Error: {}", e),
+        Err(e) => {
+            refund(ledger, user_id, amount_msat).await;
+            return Err(e);
+        }
     };
 
     let overspend_amount = notes.total_amount() - amount_msat;
     if overspend_amount != Amount::ZERO {
         if !allow_overpay {
-            return format!(
-                "Error: The amount you want to spend is {} msat less than the amount of the notes you selected. \
+            refund(ledger, user_id, amount_msat).await;
+            anyhow::bail!(
+                "The amount you want to spend is {} msat less than the amount of the notes you selected. \
                 If you want to spend the notes anyway, use the `allow_overpay` option.",
                 overspend_amount
             );
         }
+        if let Err(e) = ledger.debit(user_id, overspend_amount).await {
+            refund(ledger, user_id, amount_msat).await;
+            return Err(e);
+        }
     }
 
-    let res = SpendResponse {
+    Ok(SpendResponse {
         operation: operation_id,
         notes,
-    };
-
-    to_codeblock(serde_json::to_string_pretty(&res).unwrap())
+    })
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
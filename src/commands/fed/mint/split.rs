@@ -7,10 +7,9 @@ use fedimint_mint_client::OOBNotes;
 use serde::Serialize;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::utils::get_option_as::get_required_option_as;
 use crate::utils::to_codeblock;
 
 #[derive(Debug, Serialize)]
@@ -18,10 +17,10 @@ pub struct SplitResponse {
     pub notes: BTreeMap<Amount, OOBNotes>,
 }
 
-pub async fn run(options: &[CommandDataOption], _fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let notes = get_option_as::<String>(&options_map, "notes").unwrap();
-    let notes: OOBNotes = OOBNotes::from_str(&notes).unwrap();
+pub async fn run(options: &CommandOptions, _fm_client: &ClientArc) -> CommandResult {
+    let notes = get_required_option_as::<String>(options, "notes")?;
+    let notes: OOBNotes = OOBNotes::from_str(&notes)
+        .map_err(|e| CommandError::invalid_option("notes", "OOBNotes", e))?;
     let federation = notes.federation_id_prefix();
     let notes = notes
         .notes()
@@ -43,7 +42,7 @@ pub async fn run(options: &[CommandDataOption], _fm_client: &ClientArc) -> Strin
 
     let res = SplitResponse { notes };
 
-    to_codeblock(serde_json::to_string_pretty(&res).unwrap())
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -6,10 +6,9 @@ use fedimint_mint_client::{MintClientModule, OOBNotes};
 use serde::Serialize;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::utils::get_option_as::get_required_option_as;
 use crate::utils::to_codeblock;
 
 #[derive(Debug, Serialize)]
@@ -17,22 +16,18 @@ pub struct ValidateResponse {
     pub amount_msat: Amount,
 }
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let notes = get_option_as::<String>(&options_map, "notes").unwrap();
-    let notes = OOBNotes::from_str(&notes).unwrap();
+pub async fn run(options: &CommandOptions, fm_client: &ClientArc) -> CommandResult {
+    let notes = get_required_option_as::<String>(options, "notes")?;
+    let notes = OOBNotes::from_str(&notes)
+        .map_err(|e| CommandError::invalid_option("notes", "OOBNotes", e))?;
     let amount_msat = fm_client
         .get_first_module::<MintClientModule>()
         .validate_notes(notes)
-        .await;
-
-    match amount_msat {
-        Ok(amount_msat) => {
-            let res = ValidateResponse { amount_msat };
-            to_codeblock(serde_json::to_string_pretty(&res).unwrap())
-        }
-        Err(e) => format!("Error: {}", e),
-    }
+        .await?;
+
+    let res = ValidateResponse { amount_msat };
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
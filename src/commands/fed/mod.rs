@@ -1,14 +1,25 @@
-use fedimint_client::ClientArc;
-use serenity::model::prelude::application_command::CommandData;
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+use serenity::model::application::interaction::autocomplete::AutocompleteInteraction;
+use serenity::model::id::UserId;
 use serenity::prelude::Context;
 
-use crate::utils::discord_utils::create_and_log_command;
+use crate::commands::autocomplete::{self, Choice};
+use crate::commands::{
+    discord_command_options_to_map, subcommands, CommandError, CommandOptions, CommandResult,
+};
+use crate::federations::{Federation, Federations};
+use crate::permissions::Permission;
+use crate::utils::get_option_as::get_option_as;
+use crate::utils::progress::Progress;
 
 pub mod backup;
 pub mod config;
 pub mod discover_version;
 pub mod id;
 pub mod info;
+pub mod join;
+pub mod leave;
 pub mod list_operations;
 pub mod ln;
 pub mod mint;
@@ -20,6 +31,8 @@ pub enum FmCommand {
     DiscoverVersion,
     Id,
     Info,
+    Join,
+    Leave,
     ListOperations,
     LnAwaitInvoice,
     LnAwaitPay,
@@ -27,6 +40,7 @@ pub enum FmCommand {
     LnPay,
     LnListGateways,
     LnSwitchGateway,
+    MintCombine,
     MintReissue,
     MintSpend,
     MintSplit,
@@ -45,6 +59,8 @@ impl From<&str> for FmCommand {
             "fm_discover_version" => Self::DiscoverVersion,
             "fm_id" => Self::Id,
             "fm_info" => Self::Info,
+            "fm_join" => Self::Join,
+            "fm_leave" => Self::Leave,
             "fm_list_operations" => Self::ListOperations,
             "fm_ln_await_invoice" => Self::LnAwaitInvoice,
             "fm_ln_await_pay" => Self::LnAwaitPay,
@@ -52,6 +68,7 @@ impl From<&str> for FmCommand {
             "fm_ln_pay" => Self::LnPay,
             "fm_ln_list_gateways" => Self::LnListGateways,
             "fm_ln_switch_gateway" => Self::LnSwitchGateway,
+            "fm_mint_combine" => Self::MintCombine,
             "fm_mint_reissue" => Self::MintReissue,
             "fm_mint_spend" => Self::MintSpend,
             "fm_mint_split" => Self::MintSplit,
@@ -64,8 +81,43 @@ impl From<&str> for FmCommand {
     }
 }
 
-pub async fn ready(ctx: &Context) {
-    let commands = vec![
+impl FmCommand {
+    pub fn permission(&self) -> Permission {
+        match self {
+            Self::Config
+            | Self::DiscoverVersion
+            | Self::Id
+            | Self::Info
+            | Self::ListOperations
+            | Self::LnAwaitInvoice
+            | Self::LnAwaitPay
+            | Self::LnListGateways
+            | Self::MintCombine
+            | Self::MintSplit
+            | Self::MintValidate
+            | Self::WalletAwaitDeposit => Permission::ReadOnly,
+            Self::LnInvoice
+            | Self::LnPay
+            | Self::MintReissue
+            | Self::MintSpend
+            | Self::WalletDepositAddress
+            | Self::WalletWithdraw => Permission::Spending,
+            Self::Backup | Self::Join | Self::Leave | Self::LnSwitchGateway | Self::Unknown => {
+                Permission::Admin
+            }
+        }
+    }
+
+    /// Commands that move funds out of the federation or leave it, which the
+    /// invoking user has to confirm after seeing a summary of what they will do
+    pub fn needs_confirmation(&self) -> bool {
+        matches!(self, Self::Leave | Self::LnPay | Self::WalletWithdraw)
+    }
+}
+
+/// The `fm_*` commands under their flat names, as the API and the CLI run them
+pub fn commands() -> Vec<CreateApplicationCommand> {
+    let registers = vec![
         backup::register,
         config::register,
         discover_version::register,
@@ -78,6 +130,7 @@ pub async fn ready(ctx: &Context) {
         ln::pay::register,
         ln::list_gateways::register,
         ln::switch_gateway::register,
+        mint::combine::register,
         mint::reissue::register,
         mint::spend::register,
         mint::split::register,
@@ -87,42 +140,286 @@ pub async fn ready(ctx: &Context) {
         wallet::withdraw::register,
     ];
 
-    for command in commands {
-        create_and_log_command(&ctx.http, command).await;
+    let mut commands = registers
+        .into_iter()
+        .map(|register| {
+            let mut command = CreateApplicationCommand::default();
+            add_federation_option(register(&mut command));
+            command
+        })
+        .collect::<Vec<_>>();
+
+    for register in [join::register, leave::register] {
+        let mut command = CreateApplicationCommand::default();
+        register(&mut command);
+        commands.push(command);
+    }
+
+    commands
+}
+
+// The fedimint client modules, whose commands are grouped under `/fm`
+const MODULE_GROUPS: [(&str, &str); 3] = [
+    ("ln", "The Lightning module"),
+    ("mint", "The ecash module"),
+    ("wallet", "The on-chain module"),
+];
+
+/// The `/fm` command Discord lists, with the `fm_*` commands as subcommands
+/// and those of each client module in a group, e.g. `/fm ln pay`
+pub fn tree() -> CreateApplicationCommand {
+    subcommands::tree(
+        "fm",
+        "Run a Fedimint client command",
+        &MODULE_GROUPS,
+        commands(),
+        |name| {
+            let name = name.trim_start_matches("fm_");
+            for (group, _) in MODULE_GROUPS {
+                if let Some(subcommand) = name
+                    .strip_prefix(group)
+                    .and_then(|rest| rest.strip_prefix('_'))
+                {
+                    return (Some(group), subcommand.to_string());
+                }
+            }
+            (None, name.to_string())
+        },
+    )
+}
+
+/// The flat name of a `/fm` subcommand, e.g. `fm_ln_pay` for `/fm ln pay`
+pub fn command_name(group: Option<&str>, subcommand: &str) -> String {
+    match group {
+        Some(group) => format!("fm_{}_{}", group, subcommand),
+        None => format!("fm_{}", subcommand),
+    }
+}
+
+// Lets a command run in any federation the bot is a member of
+fn add_federation_option(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command.create_option(|opt| {
+        opt.name("federation")
+            .description("The federation to use, defaults to the bot's main federation")
+            .kind(CommandOptionType::String)
+            .required(false)
+            .set_autocomplete(true)
+    })
+}
+
+// Finds the federation picked with the `federation` option
+fn select_federation(
+    options: &CommandOptions,
+    federations: &Federations,
+) -> Result<Federation, CommandError> {
+    let selector = get_option_as::<String>(options, "federation")?;
+    federations
+        .select(selector.as_deref())
+        .map_err(|e| CommandError::invalid_option("federation", "federation id or name", e))
+}
+
+/// Suggests values for the option the user is typing in: the federations for
+/// `federation`, and operations or gateways of the selected federation for
+/// the options that take them
+pub async fn autocomplete(
+    ctx: &Context,
+    autocomplete: &AutocompleteInteraction,
+    federations: &Federations,
+) {
+    let (option, typed) = match autocomplete::focused(&autocomplete.data.options) {
+        Some(focused) => focused,
+        None => return,
+    };
+    if option == "federation" {
+        let choices = federations
+            .list()
+            .into_iter()
+            .map(|federation| Choice::new(federation.label(), federation.id().to_string()))
+            .collect();
+        return autocomplete::respond(ctx, autocomplete, typed, choices).await;
+    }
+
+    // The other suggestions come from the federation the command runs in
+    let options = discord_command_options_to_map(&autocomplete.data.options);
+    let fm_client = match select_federation(&options, federations) {
+        Ok(federation) => federation.client,
+        Err(_) => return autocomplete::respond(ctx, autocomplete, typed, Vec::new()).await,
+    };
+    let choices = match (FmCommand::from(autocomplete.data.name.as_str()), option) {
+        (FmCommand::LnAwaitInvoice | FmCommand::LnAwaitPay, "operation_id") => {
+            autocomplete::fm_operations(&fm_client, "ln").await
+        }
+        (FmCommand::WalletAwaitDeposit, "operation_id") => {
+            autocomplete::fm_operations(&fm_client, "wallet").await
+        }
+        (FmCommand::LnSwitchGateway, "gateway_id") => autocomplete::fm_gateways(&fm_client).await,
+        _ => Vec::new(),
+    };
+
+    autocomplete::respond(ctx, autocomplete, typed, choices).await
+}
+
+pub async fn handle_summary(
+    command_name: &str,
+    options: &CommandOptions,
+    federations: &Federations,
+) -> Result<String, CommandError> {
+    let federation = select_federation(options, federations)?;
+    let fm_client = &federation.client;
+    match FmCommand::from(command_name) {
+        FmCommand::Leave => Ok(format!(
+            "Leave federation {}? Users lose access to their balances in it.",
+            federation.label()
+        )),
+        FmCommand::LnPay => ln::pay::summarize(options, fm_client).await,
+        FmCommand::WalletWithdraw => wallet::withdraw::summarize(options, fm_client).await,
+        _ => Ok(format!("Run `{}`", command_name)),
     }
 }
 
 pub async fn handle_run(
     command_name: &str,
-    command_data: &CommandData,
-    fm_client: &ClientArc,
-) -> String {
+    options: &CommandOptions,
+    federations: &Federations,
+    user_id: UserId,
+    progress: &Progress,
+) -> CommandResult {
+    let federation = select_federation(options, federations)?;
+    let fm_client = &federation.client;
+    let ledger = &federation.ledger;
     match FmCommand::from(command_name) {
-        FmCommand::Backup => backup::run(&command_data.options, fm_client).await,
-        FmCommand::Config => config::run(&command_data.options, fm_client).await,
-        FmCommand::DiscoverVersion => discover_version::run(&command_data.options, fm_client).await,
-        FmCommand::Id => id::run(&command_data.options, fm_client).await,
-        FmCommand::Info => info::run(&command_data.options, fm_client).await,
-        FmCommand::ListOperations => list_operations::run(&command_data.options, fm_client).await,
-        FmCommand::LnAwaitInvoice => ln::await_invoice::run(&command_data.options, fm_client).await,
-        FmCommand::LnAwaitPay => ln::await_pay::run(&command_data.options, fm_client).await,
-        FmCommand::LnInvoice => ln::invoice::run(&command_data.options, fm_client).await,
-        FmCommand::LnPay => ln::pay::run(&command_data.options, fm_client).await,
-        FmCommand::LnListGateways => ln::list_gateways::run(&command_data.options, fm_client).await,
-        FmCommand::LnSwitchGateway => {
-            ln::switch_gateway::run(&command_data.options, fm_client).await
-        }
-        FmCommand::MintReissue => mint::reissue::run(&command_data.options, fm_client).await,
-        FmCommand::MintSpend => mint::spend::run(&command_data.options, fm_client).await,
-        FmCommand::MintSplit => mint::split::run(&command_data.options, fm_client).await,
-        FmCommand::MintValidate => mint::validate::run(&command_data.options, fm_client).await,
+        FmCommand::Backup => backup::run(options, fm_client).await,
+        FmCommand::Config => config::run(options, fm_client).await,
+        FmCommand::DiscoverVersion => discover_version::run(options, fm_client).await,
+        FmCommand::Id => id::run(options, fm_client).await,
+        FmCommand::Info => info::run(options, fm_client).await,
+        FmCommand::Join => join::run(options, federations).await,
+        FmCommand::Leave => leave::run(&federation, federations).await,
+        FmCommand::ListOperations => list_operations::run(options, fm_client).await,
+        FmCommand::LnAwaitInvoice => ln::await_invoice::run(options, fm_client, progress).await,
+        FmCommand::LnAwaitPay => ln::await_pay::run(options, fm_client, progress).await,
+        FmCommand::LnInvoice => ln::invoice::run(options, fm_client, ledger, user_id).await,
+        FmCommand::LnPay => ln::pay::run(options, fm_client, ledger, user_id, progress).await,
+        FmCommand::LnListGateways => ln::list_gateways::run(options, fm_client).await,
+        FmCommand::LnSwitchGateway => ln::switch_gateway::run(options, fm_client).await,
+        FmCommand::MintCombine => mint::combine::run(options, fm_client).await,
+        FmCommand::MintReissue => mint::reissue::run(options, fm_client, ledger, user_id).await,
+        FmCommand::MintSpend => mint::spend::run(options, fm_client, ledger, user_id).await,
+        FmCommand::MintSplit => mint::split::run(options, fm_client).await,
+        FmCommand::MintValidate => mint::validate::run(options, fm_client).await,
         FmCommand::WalletAwaitDeposit => {
-            wallet::await_deposit::run(&command_data.options, fm_client).await
+            wallet::await_deposit::run(options, fm_client, progress).await
         }
-        FmCommand::WalletDepositAddress => {
-            wallet::deposit_address::run(&command_data.options, fm_client).await
+        FmCommand::WalletDepositAddress => wallet::deposit_address::run(options, fm_client).await,
+        FmCommand::WalletWithdraw => wallet::withdraw::run(options, fm_client, progress).await,
+        FmCommand::Unknown => Ok(format!("Unknown command: {}", command_name).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::CommandResponse;
+    use crate::testing;
+    use crate::testing::fed::TestFederation;
+
+    async fn run(
+        fed: &TestFederation,
+        name: &str,
+        options: &[(&str, serde_json::Value)],
+    ) -> CommandResult {
+        handle_run(
+            name,
+            &testing::options(options),
+            &fed.federations(),
+            UserId(1),
+            &Progress::default(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn info_reports_the_federation() {
+        let fed = TestFederation::new().await;
+
+        match run(&fed, "fm_info", &[]).await {
+            Ok(CommandResponse::Text(content)) => {
+                assert!(content.contains(&fed.client.federation_id().to_string()))
+            }
+            res => panic!("Unexpected response: {:?}", res),
         }
-        FmCommand::WalletWithdraw => wallet::withdraw::run(&command_data.options, fm_client).await,
-        FmCommand::Unknown => format!("Unknown command: {}", command_name),
+    }
+
+    #[tokio::test]
+    async fn invalid_notes_are_rejected() {
+        let fed = TestFederation::new().await;
+
+        for name in [
+            "fm_mint_reissue",
+            "fm_mint_validate",
+            "fm_mint_split",
+            "fm_mint_combine",
+        ] {
+            let res = run(&fed, name, &[("notes", json!("not notes"))]).await;
+            assert!(
+                matches!(&res, Err(CommandError::InvalidOption { name, .. }) if name == "notes"),
+                "{}: {:?}",
+                name,
+                res
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn spending_more_than_the_balance_fails() {
+        let fed = TestFederation::new().await;
+
+        let res = run(&fed, "fm_mint_spend", &[("amount_msat", json!(1000))]).await;
+
+        assert!(matches!(res, Err(CommandError::Federation(_))), "{:?}", res);
+        assert_eq!(
+            fed.ledger.balance(UserId(1)).await,
+            fedimint_core::Amount::ZERO
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_federations_are_rejected() {
+        let fed = TestFederation::new().await;
+
+        let res = run(
+            &fed,
+            "fm_info",
+            &[("federation", json!("not a federation"))],
+        )
+        .await;
+
+        assert!(
+            matches!(&res, Err(CommandError::InvalidOption { name, .. }) if name == "federation"),
+            "{:?}",
+            res
+        );
+    }
+
+    #[tokio::test]
+    async fn the_default_federation_cannot_be_left() {
+        let fed = TestFederation::new().await;
+        let federation_id = fed.client.federation_id().to_string();
+
+        let res = run(&fed, "fm_leave", &[("federation", json!(federation_id))]).await;
+
+        assert!(matches!(res, Err(CommandError::Federation(_))), "{:?}", res);
+        assert_eq!(fed.federations().list().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn missing_options_are_reported() {
+        let fed = TestFederation::new().await;
+
+        let res = run(&fed, "fm_wallet_deposit_address", &[]).await;
+
+        assert!(matches!(res, Err(CommandError::MissingOption(name)) if name == "timeout"));
     }
 }
@@ -2,48 +2,61 @@ use std::str::FromStr;
 
 use fedimint_client::ClientArc;
 use fedimint_core::core::OperationId;
+use fedimint_core::Amount;
 use fedimint_wallet_client::{DepositState, WalletClientModule};
 use futures::StreamExt;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::pending::{OperationKind, StartedOperation};
+use crate::utils::get_option_as::get_required_option_as;
+use crate::utils::progress::Progress;
 use crate::utils::to_codeblock;
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let operation_id: String = get_option_as(&options_map, "operation_id").unwrap();
-    let operation_id: OperationId = OperationId::from_str(&operation_id).unwrap();
-    let updates_result = fm_client
+pub async fn run(
+    options: &CommandOptions,
+    fm_client: &ClientArc,
+    progress: &Progress,
+) -> CommandResult {
+    let operation_id: String = get_required_option_as(options, "operation_id")?;
+    let operation_id: OperationId = OperationId::from_str(&operation_id)
+        .map_err(|e| CommandError::invalid_option("operation_id", "OperationId", e))?;
+    progress.operation_started(StartedOperation {
+        federation_id: fm_client.federation_id(),
+        operation_id,
+        kind: OperationKind::Deposit,
+        refund: Amount::ZERO,
+        background: false,
+    });
+    let mut updates = fm_client
         .get_first_module::<WalletClientModule>()
         .subscribe_deposit_updates(operation_id)
-        .await;
-
-    let mut updates = match updates_result {
-        Ok(stream) => stream.into_stream(),
-        Err(e) => {
-            return to_codeblock(serde_json::to_string_pretty(&format!("Error: {}", e)).unwrap())
-        }
-    };
+        .await?
+        .into_stream();
 
     while let Some(update) = updates.next().await {
+        progress.update(format!("Deposit {}: {:?}", operation_id, update));
         match update {
             DepositState::Confirmed(tx) => {
-                return to_codeblock(serde_json::to_string_pretty(&tx).unwrap())
+                return Ok(to_codeblock(serde_json::to_string_pretty(&tx).unwrap()).into())
             }
             DepositState::Claimed(tx) => {
-                return to_codeblock(serde_json::to_string_pretty(&tx).unwrap())
+                return Ok(to_codeblock(serde_json::to_string_pretty(&tx).unwrap()).into())
             }
             DepositState::Failed(reason) => {
-                return to_codeblock(serde_json::to_string_pretty(&reason).unwrap())
+                return Err(CommandError::Federation(format!(
+                    "Deposit failed: {}",
+                    reason
+                )))
             }
             _ => {}
         }
     }
 
-    to_codeblock(serde_json::to_string_pretty("Error: Unexpected end of stream").unwrap())
+    Err(CommandError::Federation(
+        "Unexpected end of stream".to_string(),
+    ))
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
@@ -63,6 +76,7 @@ pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicatio
                 .description(opt_info.description)
                 .kind(opt_info.kind)
                 .required(opt_info.required)
+                .set_autocomplete(opt_info.name == "operation_id")
         });
     }
 
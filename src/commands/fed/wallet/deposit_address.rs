@@ -8,10 +8,9 @@ use fedimint_wallet_client::WalletClientModule;
 use serde::Serialize;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandOptionInfo, CommandOptions, CommandResult};
+use crate::utils::get_option_as::get_required_option_as;
 use crate::utils::to_codeblock;
 
 #[derive(Debug, Serialize)]
@@ -20,24 +19,19 @@ pub struct DepositAddressResponse {
     pub operation_id: OperationId,
 }
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let timeout: u64 = get_option_as(&options_map, "timeout").unwrap();
-    let res = fm_client
+pub async fn run(options: &CommandOptions, fm_client: &ClientArc) -> CommandResult {
+    let timeout: u64 = get_required_option_as(options, "timeout")?;
+    let (operation_id, address) = fm_client
         .get_first_module::<WalletClientModule>()
         .get_deposit_address(now() + Duration::from_secs(timeout), ())
-        .await;
+        .await?;
 
-    match res {
-        Ok((operation_id, address)) => {
-            let res = DepositAddressResponse {
-                address,
-                operation_id,
-            };
-            to_codeblock(serde_json::to_string_pretty(&res).unwrap())
-        }
-        Err(e) => to_codeblock(serde_json::to_string_pretty(&format!("Error: {}", e)).unwrap()),
-    }
+    let res = DepositAddressResponse {
+        address,
+        operation_id,
+    };
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
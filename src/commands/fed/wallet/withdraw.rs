@@ -2,15 +2,19 @@ use std::str::FromStr;
 
 use bitcoin::Amount;
 use fedimint_client::ClientArc;
-use fedimint_wallet_client::{WalletClientModule, WithdrawState};
+use fedimint_core::core::OperationId;
+use fedimint_wallet_client::{
+    WalletClientModule, WalletOperationMeta, WalletOperationMetaVariant, WithdrawState,
+};
 use futures::StreamExt;
 use serde::Serialize;
 use serenity::builder::CreateApplicationCommand;
 use serenity::model::application::command::CommandOptionType;
-use serenity::model::application::interaction::application_command::CommandDataOption;
 
-use crate::commands::{discord_command_options_to_map, CommandOptionInfo};
-use crate::utils::get_option_as::get_option_as;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::pending::{OperationKind, StartedOperation};
+use crate::utils::get_option_as::get_required_option_as;
+use crate::utils::progress::Progress;
 use crate::utils::to_codeblock;
 
 #[derive(Debug, Serialize)]
@@ -19,45 +23,51 @@ pub struct WithdrawResponse {
     pub fees_sat: u64,
 }
 
-pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String {
-    let options_map = discord_command_options_to_map(options);
-    let address: String = get_option_as(&options_map, "address").unwrap();
-    let address = bitcoin::Address::from_str(&address).unwrap();
-    let amount_msat: u64 = get_option_as(&options_map, "amount_msat").unwrap();
-    let amount = Amount::from_sat(amount_msat);
+pub async fn run(
+    options: &CommandOptions,
+    fm_client: &ClientArc,
+    progress: &Progress,
+) -> CommandResult {
+    let address = parse_address(options)?;
+    let amount = parse_amount(options)?;
     let wallet_module = fm_client.get_first_module::<WalletClientModule>();
     let fees = wallet_module
         .get_withdraw_fees(address.clone(), amount)
-        .await
-        .map_err(|e| e.to_string());
-    let fees = match fees {
-        Ok(fees) => fees,
-        Err(e) => {
-            return to_codeblock(serde_json::to_string_pretty(&format!("Error: {}", e)).unwrap())
-        }
-    };
+        .await?;
     let absolute_fees = fees.amount();
 
-    let operation_id = wallet_module
-        .withdraw(address, amount, fees, ())
-        .await
-        .map_err(|e| e.to_string());
-    let operation_id = match operation_id {
-        Ok(operation_id) => operation_id,
-        Err(e) => {
-            return to_codeblock(serde_json::to_string_pretty(&format!("Error: {}", e)).unwrap())
-        }
-    };
+    let operation_id = wallet_module.withdraw(address, amount, fees, ()).await?;
+    progress.operation_started(StartedOperation {
+        federation_id: fm_client.federation_id(),
+        operation_id,
+        kind: OperationKind::Withdraw,
+        refund: fedimint_core::Amount::ZERO,
+        background: false,
+    });
+
+    await_withdrawal(fm_client, operation_id, Some(absolute_fees), progress).await
+}
 
-    let updates = wallet_module.subscribe_withdraw_updates(operation_id).await;
-    let mut updates = match updates {
-        Ok(stream) => stream.into_stream(),
-        Err(e) => {
-            return to_codeblock(serde_json::to_string_pretty(&format!("Error: {}", e)).unwrap())
-        }
+/// Waits for the withdrawal to be broadcast. The fees are looked up in the
+/// operation log unless given.
+pub async fn await_withdrawal(
+    fm_client: &ClientArc,
+    operation_id: OperationId,
+    fees: Option<Amount>,
+    progress: &Progress,
+) -> CommandResult {
+    let absolute_fees = match fees {
+        Some(fees) => fees,
+        None => withdrawal_fees(fm_client, operation_id).await?,
     };
+    let mut updates = fm_client
+        .get_first_module::<WalletClientModule>()
+        .subscribe_withdraw_updates(operation_id)
+        .await?
+        .into_stream();
 
     while let Some(update) = updates.next().await {
+        progress.update(format!("Withdrawal {}: {:?}", operation_id, update));
         match update {
             WithdrawState::Succeeded(txid) => {
                 let res = WithdrawResponse {
@@ -65,18 +75,79 @@ pub async fn run(options: &[CommandDataOption], fm_client: &ClientArc) -> String
                     fees_sat: absolute_fees.to_sat(),
                 };
 
-                return to_codeblock(serde_json::to_string_pretty(&res).unwrap());
+                return Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into());
             }
             WithdrawState::Failed(e) => {
-                return to_codeblock(
-                    serde_json::to_string_pretty(&format!("Error: {}", e)).unwrap(),
-                )
+                return Err(CommandError::Federation(format!(
+                    "Withdrawal failed: {}",
+                    e
+                )))
             }
             _ => continue,
         };
     }
 
-    to_codeblock(serde_json::to_string_pretty("Error: Unexpected end of stream").unwrap())
+    Err(CommandError::Federation(
+        "Unexpected end of stream".to_string(),
+    ))
+}
+
+async fn withdrawal_fees(
+    fm_client: &ClientArc,
+    operation_id: OperationId,
+) -> Result<Amount, CommandError> {
+    let operation = fm_client
+        .operation_log()
+        .get_operation(operation_id)
+        .await
+        .ok_or_else(|| CommandError::Federation(format!("Unknown operation {}", operation_id)))?;
+    match operation.meta::<WalletOperationMeta>().variant {
+        WalletOperationMetaVariant::Withdraw { fee, .. } => Ok(fee.amount()),
+        _ => Err(CommandError::Federation(format!(
+            "Operation {} is not a withdrawal",
+            operation_id
+        ))),
+    }
+}
+
+/// Describes the withdrawal `run` would make, including the fees the
+/// federation currently charges for it
+pub async fn summarize(
+    options: &CommandOptions,
+    fm_client: &ClientArc,
+) -> Result<String, CommandError> {
+    let address = parse_address(options)?;
+    let amount = parse_amount(options)?;
+    let fees = fm_client
+        .get_first_module::<WalletClientModule>()
+        .get_withdraw_fees(address.clone(), amount)
+        .await?;
+
+    Ok(format!(
+        "Withdraw {} sat on-chain to `{}`\nEstimated fees: {} sat",
+        amount.to_sat(),
+        address,
+        fees.amount().to_sat()
+    ))
+}
+
+// On-chain amounts are whole sats, so the option's msats must be too
+fn parse_amount(options: &CommandOptions) -> Result<Amount, CommandError> {
+    let amount_msat: u64 = get_required_option_as(options, "amount_msat")?;
+    if amount_msat % 1000 != 0 {
+        return Err(CommandError::invalid_option(
+            "amount_msat",
+            "whole number of sats",
+            format!("{} msat is not a multiple of 1000", amount_msat),
+        ));
+    }
+    Ok(Amount::from_sat(amount_msat / 1000))
+}
+
+fn parse_address(options: &CommandOptions) -> Result<bitcoin::Address, CommandError> {
+    let address: String = get_required_option_as(options, "address")?;
+    bitcoin::Address::from_str(&address)
+        .map_err(|e| CommandError::invalid_option("address", "bitcoin address", e))
 }
 
 pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
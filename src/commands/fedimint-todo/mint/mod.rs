@@ -1,5 +0,0 @@
-pub mod combine;
-pub mod reissue;
-pub mod spend;
-pub mod split;
-pub mod validate;
@@ -1,4 +0,0 @@
-pub mod admin;
-pub mod ln;
-pub mod mint;
-pub mod wallet;
@@ -0,0 +1,81 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::LightningBackend;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
+use crate::utils::to_codeblock;
+
+pub async fn run(options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let channel_point: String = get_required_option_as(options, "channel_point")?;
+    let force: bool = get_option_as(options, "force")?.unwrap_or(false);
+
+    let res = lightning
+        .close_channel(channel_point, force)
+        .await
+        .map_err(CommandError::rpc)?;
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+/// Describes the channel `run` would close, found among the node's channels
+pub async fn summarize(
+    options: &CommandOptions,
+    lightning: &dyn LightningBackend,
+) -> Result<String, CommandError> {
+    let channel_point: String = get_required_option_as(options, "channel_point")?;
+    let force: bool = get_option_as(options, "force")?.unwrap_or(false);
+
+    let funds = lightning.list_funds().await.map_err(CommandError::rpc)?;
+    let mut summary = match funds
+        .channels
+        .iter()
+        .find(|channel| channel.channel_point == channel_point)
+    {
+        Some(channel) => format!(
+            "Close channel `{}` with peer `{}`\nOur balance: {} msat\nCapacity: {} msat",
+            channel.channel_point, channel.peer_id, channel.our_amount_msat, channel.amount_msat
+        ),
+        None => format!(
+            "Close channel `{}`\nNo channel with this channel point was found.",
+            channel_point
+        ),
+    };
+    if force {
+        summary.push_str("\nThe channel is closed unilaterally.");
+    }
+
+    Ok(summary)
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![
+        CommandOptionInfo {
+            name: "channel_point",
+            description: "The channel point of the channel, as <txid>:<vout>",
+            kind: CommandOptionType::String,
+            required: true,
+        },
+        CommandOptionInfo {
+            name: "force",
+            description: "Whether to close the channel unilaterally",
+            kind: CommandOptionType::Boolean,
+            required: false,
+        },
+    ];
+
+    command
+        .name("ln_close")
+        .description("Close a channel of the Lightning node");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
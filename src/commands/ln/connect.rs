@@ -0,0 +1,44 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+
+use crate::commands::cln::connect::ConnectionString;
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::LightningBackend;
+use crate::utils::get_option_as::get_required_option_as;
+
+pub async fn run(options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let connection_string: String = get_required_option_as(options, "connection_string")?;
+    let cs = ConnectionString::from_string(&connection_string)
+        .map_err(|e| CommandError::invalid_option("connection_string", "connection string", e))?;
+
+    let id = lightning
+        .connect(cs.id, cs.host, cs.port)
+        .await
+        .map_err(CommandError::rpc)?;
+
+    Ok(format!("Connected to `{}`", id).into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![CommandOptionInfo {
+        name: "connection_string",
+        description: "The peer to connect to, as <id>@<host>:<port>",
+        kind: CommandOptionType::String,
+        required: true,
+    }];
+
+    command
+        .name("ln_connect")
+        .description("Connect the Lightning node to a peer");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
@@ -0,0 +1,42 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::LightningBackend;
+use crate::utils::get_option_as::get_required_option_as;
+use crate::utils::to_codeblock;
+
+pub async fn run(options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let bolt11: String = get_required_option_as(options, "bolt11")?;
+
+    let res = lightning
+        .decode_invoice(bolt11)
+        .await
+        .map_err(CommandError::rpc)?;
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![CommandOptionInfo {
+        name: "bolt11",
+        description: "The invoice to decode",
+        kind: CommandOptionType::String,
+        required: true,
+    }];
+
+    command
+        .name("ln_decodepay")
+        .description("Decode a bolt11 invoice");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
@@ -0,0 +1,58 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::LightningBackend;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
+use crate::utils::to_codeblock;
+
+pub async fn run(options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let id: String = get_required_option_as(options, "id")?;
+    let amount_sat: u64 = get_required_option_as(options, "amount_sat")?;
+    let push_msat: Option<u64> = get_option_as(options, "push_msat")?;
+
+    let res = lightning
+        .fund_channel(id, amount_sat, push_msat)
+        .await
+        .map_err(CommandError::rpc)?;
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![
+        CommandOptionInfo {
+            name: "id",
+            description: "The node id of the peer",
+            kind: CommandOptionType::String,
+            required: true,
+        },
+        CommandOptionInfo {
+            name: "amount_sat",
+            description: "The channel capacity in satoshis",
+            kind: CommandOptionType::Integer,
+            required: true,
+        },
+        CommandOptionInfo {
+            name: "push_msat",
+            description: "The amount to give the peer in millisatoshis",
+            kind: CommandOptionType::Integer,
+            required: false,
+        },
+    ];
+
+    command
+        .name("ln_fundchannel")
+        .description("Open a channel from the Lightning node");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
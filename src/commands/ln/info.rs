@@ -0,0 +1,22 @@
+use serenity::builder::CreateApplicationCommand;
+
+use crate::commands::{CommandError, CommandOptions, CommandResult};
+use crate::lightning::LightningBackend;
+use crate::utils::to_codeblock;
+
+pub async fn run(_options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let res = lightning.info().await.map_err(CommandError::rpc)?;
+
+    Ok(format!(
+        "{} node\n{}",
+        lightning.name(),
+        to_codeblock(serde_json::to_string_pretty(&res).unwrap())
+    )
+    .into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("ln_info")
+        .description("Get info about the Lightning node")
+}
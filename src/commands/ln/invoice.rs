@@ -0,0 +1,58 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::LightningBackend;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
+use crate::utils::to_codeblock;
+
+pub async fn run(options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let amount_msat: Option<u64> = get_option_as(options, "amount_msat")?;
+    let description: String = get_required_option_as(options, "description")?;
+    let expiry: Option<u64> = get_option_as(options, "expiry")?;
+
+    let res = lightning
+        .invoice(amount_msat, description, expiry)
+        .await
+        .map_err(CommandError::rpc)?;
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![
+        CommandOptionInfo {
+            name: "description",
+            description: "The description of the invoice",
+            kind: CommandOptionType::String,
+            required: true,
+        },
+        CommandOptionInfo {
+            name: "amount_msat",
+            description: "The amount in millisatoshis, any amount if omitted",
+            kind: CommandOptionType::Integer,
+            required: false,
+        },
+        CommandOptionInfo {
+            name: "expiry",
+            description: "Seconds until the invoice expires",
+            kind: CommandOptionType::Integer,
+            required: false,
+        },
+    ];
+
+    command
+        .name("ln_invoice")
+        .description("Create an invoice on the Lightning node");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
@@ -0,0 +1,66 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::LightningBackend;
+use crate::utils::get_option_as::get_required_option_as;
+use crate::utils::to_codeblock;
+
+pub async fn run(options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let destination: String = get_required_option_as(options, "destination")?;
+    let amount_msat: u64 = get_required_option_as(options, "amount_msat")?;
+
+    let res = lightning
+        .keysend(destination, amount_msat)
+        .await
+        .map_err(CommandError::rpc)?;
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+pub fn summarize(
+    options: &CommandOptions,
+    lightning: &dyn LightningBackend,
+) -> Result<String, CommandError> {
+    let destination: String = get_required_option_as(options, "destination")?;
+    let amount_msat: u64 = get_required_option_as(options, "amount_msat")?;
+
+    Ok(format!(
+        "Keysend {} msat to `{}` from the {} node",
+        amount_msat,
+        destination,
+        lightning.name()
+    ))
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![
+        CommandOptionInfo {
+            name: "destination",
+            description: "The node id to pay",
+            kind: CommandOptionType::String,
+            required: true,
+        },
+        CommandOptionInfo {
+            name: "amount_msat",
+            description: "The amount in millisatoshis",
+            kind: CommandOptionType::Integer,
+            required: true,
+        },
+    ];
+
+    command
+        .name("ln_keysend")
+        .description("Send a spontaneous payment from the Lightning node");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
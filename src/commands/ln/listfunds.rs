@@ -0,0 +1,20 @@
+use serenity::builder::CreateApplicationCommand;
+
+use crate::commands::{CommandError, CommandOptions, CommandResult};
+use crate::components::pagination::PagedList;
+use crate::lightning::LightningBackend;
+
+pub async fn run(_options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let res = lightning.list_funds().await.map_err(CommandError::rpc)?;
+
+    Ok(PagedList::new("Funds")
+        .with_items("Output", "outpoint", res.outputs)
+        .with_items("Channel", "channel_point", res.channels)
+        .into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("ln_listfunds")
+        .description("List the Lightning node's on-chain outputs and channels")
+}
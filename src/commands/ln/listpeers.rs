@@ -0,0 +1,19 @@
+use serenity::builder::CreateApplicationCommand;
+
+use crate::commands::{CommandError, CommandOptions, CommandResult};
+use crate::components::pagination::PagedList;
+use crate::lightning::LightningBackend;
+
+pub async fn run(_options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let peers = lightning.list_peers().await.map_err(CommandError::rpc)?;
+
+    Ok(PagedList::new("Peers")
+        .with_items("Peer", "id", peers)
+        .into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("ln_listpeers")
+        .description("List the Lightning node's peers")
+}
@@ -0,0 +1,150 @@
+use serenity::builder::CreateApplicationCommand;
+
+use crate::commands::{subcommands, CommandError, CommandOptions, CommandResult};
+use crate::lightning::LightningBackend;
+use crate::permissions::Permission;
+
+pub mod close;
+pub mod connect;
+pub mod decodepay;
+pub mod fundchannel;
+pub mod info;
+pub mod invoice;
+pub mod keysend;
+pub mod listfunds;
+pub mod listpeers;
+pub mod newaddr;
+pub mod pay;
+
+/// Commands that work the same whichever Lightning node the bot is configured
+/// with
+pub enum LnCommand {
+    Info,
+    Invoice,
+    Pay,
+    KeySend,
+    ListFunds,
+    ListPeers,
+    NewAddr,
+    Connect,
+    FundChannel,
+    Close,
+    DecodePay,
+    Unknown,
+}
+
+impl From<&str> for LnCommand {
+    fn from(s: &str) -> Self {
+        match s {
+            "ln_info" => Self::Info,
+            "ln_invoice" => Self::Invoice,
+            "ln_pay" => Self::Pay,
+            "ln_keysend" => Self::KeySend,
+            "ln_listfunds" => Self::ListFunds,
+            "ln_listpeers" => Self::ListPeers,
+            "ln_newaddr" => Self::NewAddr,
+            "ln_connect" => Self::Connect,
+            "ln_fundchannel" => Self::FundChannel,
+            "ln_close" => Self::Close,
+            "ln_decodepay" => Self::DecodePay,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl LnCommand {
+    pub fn permission(&self) -> Permission {
+        match self {
+            Self::Info | Self::ListFunds | Self::ListPeers | Self::DecodePay => {
+                Permission::ReadOnly
+            }
+            Self::Invoice | Self::Pay | Self::KeySend | Self::NewAddr | Self::FundChannel => {
+                Permission::Spending
+            }
+            Self::Connect | Self::Close | Self::Unknown => Permission::Admin,
+        }
+    }
+
+    /// Commands that move funds out of the node, which the invoking user has
+    /// to confirm after seeing a summary of what they will do
+    pub fn needs_confirmation(&self) -> bool {
+        matches!(self, Self::Pay | Self::KeySend | Self::Close)
+    }
+}
+
+/// The `ln_*` commands under their flat names, as the API and the CLI run them
+pub fn commands() -> Vec<CreateApplicationCommand> {
+    let registers = vec![
+        info::register,
+        invoice::register,
+        pay::register,
+        keysend::register,
+        listfunds::register,
+        listpeers::register,
+        newaddr::register,
+        connect::register,
+        fundchannel::register,
+        close::register,
+        decodepay::register,
+    ];
+
+    registers
+        .into_iter()
+        .map(|register| {
+            let mut command = CreateApplicationCommand::default();
+            register(&mut command);
+            command
+        })
+        .collect()
+}
+
+/// The `/ln` command Discord lists, with the `ln_*` commands as subcommands,
+/// e.g. `/ln pay`
+pub fn tree() -> CreateApplicationCommand {
+    subcommands::tree(
+        "ln",
+        "Run a command on the bot's Lightning node",
+        &[],
+        commands(),
+        |name| (None, name.trim_start_matches("ln_").to_string()),
+    )
+}
+
+/// The flat name of a `/ln` subcommand
+pub fn command_name(subcommand: &str) -> String {
+    format!("ln_{}", subcommand)
+}
+
+pub async fn handle_summary(
+    command_name: &str,
+    options: &CommandOptions,
+    lightning: &dyn LightningBackend,
+) -> Result<String, CommandError> {
+    match LnCommand::from(command_name) {
+        LnCommand::Pay => pay::summarize(options, lightning).await,
+        LnCommand::KeySend => keysend::summarize(options, lightning),
+        LnCommand::Close => close::summarize(options, lightning).await,
+        _ => Ok(format!("Run `{}`", command_name)),
+    }
+}
+
+pub async fn handle_run(
+    command_name: &str,
+    options: &CommandOptions,
+    lightning: &dyn LightningBackend,
+) -> CommandResult {
+    match LnCommand::from(command_name) {
+        LnCommand::Info => info::run(options, lightning).await,
+        LnCommand::Invoice => invoice::run(options, lightning).await,
+        LnCommand::Pay => pay::run(options, lightning).await,
+        LnCommand::KeySend => keysend::run(options, lightning).await,
+        LnCommand::ListFunds => listfunds::run(options, lightning).await,
+        LnCommand::ListPeers => listpeers::run(options, lightning).await,
+        LnCommand::NewAddr => newaddr::run(options, lightning).await,
+        LnCommand::Connect => connect::run(options, lightning).await,
+        LnCommand::FundChannel => fundchannel::run(options, lightning).await,
+        LnCommand::Close => close::run(options, lightning).await,
+        LnCommand::DecodePay => decodepay::run(options, lightning).await,
+        LnCommand::Unknown => Ok(format!("Unknown command: {}", command_name).into()),
+    }
+}
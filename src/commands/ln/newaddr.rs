@@ -0,0 +1,16 @@
+use serenity::builder::CreateApplicationCommand;
+
+use crate::commands::{CommandError, CommandOptions, CommandResult};
+use crate::lightning::LightningBackend;
+
+pub async fn run(_options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let address = lightning.new_address().await.map_err(CommandError::rpc)?;
+
+    Ok(format!("`{}`", address).into())
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("ln_newaddr")
+        .description("Get a new on-chain address from the Lightning node")
+}
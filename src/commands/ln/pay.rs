@@ -0,0 +1,77 @@
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+
+use crate::commands::{CommandError, CommandOptionInfo, CommandOptions, CommandResult};
+use crate::lightning::LightningBackend;
+use crate::utils::get_option_as::{get_option_as, get_required_option_as};
+use crate::utils::to_codeblock;
+
+pub async fn run(options: &CommandOptions, lightning: &dyn LightningBackend) -> CommandResult {
+    let bolt11: String = get_required_option_as(options, "bolt11")?;
+    let amount_msat: Option<u64> = get_option_as(options, "amount_msat")?;
+
+    let res = lightning
+        .pay(bolt11, amount_msat)
+        .await
+        .map_err(CommandError::rpc)?;
+
+    Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into())
+}
+
+/// Describes the payment `run` would make from the decoded invoice
+pub async fn summarize(
+    options: &CommandOptions,
+    lightning: &dyn LightningBackend,
+) -> Result<String, CommandError> {
+    let bolt11: String = get_required_option_as(options, "bolt11")?;
+    let amount_msat: Option<u64> = get_option_as(options, "amount_msat")?;
+
+    let invoice = lightning
+        .decode_invoice(bolt11)
+        .await
+        .map_err(CommandError::rpc)?;
+    let amount = match amount_msat.or(invoice.amount_msat) {
+        Some(amount_msat) => format!("{} msat", amount_msat),
+        None => "an unspecified amount".to_string(),
+    };
+
+    Ok(format!(
+        "Pay {} to `{}` from the {} node\nDescription: {}",
+        amount,
+        invoice.payee,
+        lightning.name(),
+        invoice.description.as_deref().unwrap_or("none")
+    ))
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    let options = vec![
+        CommandOptionInfo {
+            name: "bolt11",
+            description: "The invoice to pay",
+            kind: CommandOptionType::String,
+            required: true,
+        },
+        CommandOptionInfo {
+            name: "amount_msat",
+            description: "The amount in millisatoshis, for invoices without one",
+            kind: CommandOptionType::Integer,
+            required: false,
+        },
+    ];
+
+    command
+        .name("ln_pay")
+        .description("Pay an invoice from the Lightning node");
+
+    for opt_info in options {
+        command.create_option(|opt| {
+            opt.name(opt_info.name)
+                .description(opt_info.description)
+                .kind(opt_info.kind)
+                .required(opt_info.required)
+        });
+    }
+
+    command
+}
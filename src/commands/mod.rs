@@ -1,12 +1,22 @@
+pub mod account;
+pub mod autocomplete;
 pub mod cln;
 pub mod custom;
+pub mod error;
 pub mod fed;
+pub mod ln;
+pub mod report;
+pub mod status;
+pub mod subcommands;
 use std::collections::HashMap;
 
 use serde_json::Value;
-use serenity::model::prelude::application_command::CommandDataOption;
+use serenity::model::prelude::application_command::{CommandDataOption, CommandDataOptionValue};
 use serenity::model::prelude::command::CommandOptionType;
 
+pub use self::error::{CommandError, CommandResult};
+use crate::components::pagination::PagedList;
+
 pub struct CommandOptionInfo {
     pub name: &'static str,
     pub description: &'static str,
@@ -14,11 +24,44 @@ pub struct CommandOptionInfo {
     pub required: bool,
 }
 
-pub fn discord_command_options_to_map(
-    options: &[CommandDataOption],
-) -> HashMap<String, Option<Value>> {
+/// What a command handler replies with: a plain message, or a list shown as
+/// paginated embeds
+#[derive(Debug)]
+pub enum CommandResponse {
+    Text(String),
+    List(PagedList),
+}
+
+impl From<String> for CommandResponse {
+    fn from(content: String) -> Self {
+        CommandResponse::Text(content)
+    }
+}
+
+impl From<PagedList> for CommandResponse {
+    fn from(list: PagedList) -> Self {
+        CommandResponse::List(list)
+    }
+}
+
+/// The options of a command invocation by name, as Discord sends their values.
+/// Handlers take this rather than serenity's types so the API and the CLI can
+/// run them too.
+pub type CommandOptions = HashMap<String, Option<Value>>;
+
+/// Attachment options are given as the URL of the uploaded file rather than
+/// its id, so handlers can download it without the interaction's resolved data
+pub fn discord_command_options_to_map(options: &[CommandDataOption]) -> CommandOptions {
     options
         .iter()
-        .map(|opt| (opt.name.clone(), opt.value.clone()))
-        .collect::<std::collections::HashMap<String, Option<Value>>>()
+        .map(|opt| {
+            let value = match &opt.resolved {
+                Some(CommandDataOptionValue::Attachment(attachment)) => {
+                    Some(Value::String(attachment.url.clone()))
+                }
+                _ => opt.value.clone(),
+            };
+            (opt.name.clone(), value)
+        })
+        .collect()
 }
@@ -0,0 +1,21 @@
+use serenity::builder::CreateApplicationCommand;
+
+use crate::commands::CommandResult;
+use crate::config::CONFIG;
+use crate::federations::Federations;
+use crate::lightning::ClnNodes;
+use crate::notifications::report;
+
+pub async fn run(cln_nodes: &ClnNodes, federations: &Federations) -> CommandResult {
+    Ok(
+        report::build(cln_nodes, federations, &CONFIG.report.sections)
+            .await
+            .into(),
+    )
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("report")
+        .description("Get the health report of the CLN nodes and federations")
+}
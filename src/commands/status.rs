@@ -0,0 +1,45 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serenity::builder::CreateApplicationCommand;
+
+use crate::commands::CommandResult;
+use crate::lightning::{ClnNodes, ConnectionState};
+
+pub fn run(cln_nodes: &ClnNodes) -> CommandResult {
+    if cln_nodes.is_empty() {
+        return Ok("No CLN node is configured".to_string().into());
+    }
+
+    let lines = cln_nodes
+        .list()
+        .iter()
+        .map(|node| format!("**{}**: {}", node.name, describe(&node.client.state())))
+        .collect::<Vec<_>>();
+    Ok(lines.join("\n").into())
+}
+
+pub fn describe(state: &ConnectionState) -> String {
+    match state {
+        ConnectionState::Connected { since } => {
+            format!("connected since {}", timestamp(*since))
+        }
+        ConnectionState::Disconnected { since, error } => {
+            format!("offline since {} ({})", timestamp(*since), error)
+        }
+    }
+}
+
+// Discord shows these relative to the reader's clock
+fn timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    format!("<t:{}:R>", secs)
+}
+
+pub fn register(command: &mut CreateApplicationCommand) -> &mut CreateApplicationCommand {
+    command
+        .name("status")
+        .description("Get the connection state of the CLN nodes")
+}
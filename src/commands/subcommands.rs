@@ -0,0 +1,181 @@
+//! The `cln_`, `ln_` and `fm_` commands are registered with Discord as
+//! subcommands of `/cln-*`, `/ln` and `/fm`, as ~90 top-level commands would
+//! flood the command picker and near Discord's cap of 100. Everywhere else
+//! they keep their flat names: the dispatcher resolves `/fm ln pay` back to
+//! `fm_ln_pay` before handling it.
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+use serenity::builder::CreateApplicationCommand;
+use serenity::model::application::command::CommandOptionType;
+use serenity::model::application::interaction::application_command::CommandData;
+
+use crate::commands::{cln, fed, ln};
+
+/// Where a flat command is listed under its top-level command: the
+/// subcommand group, if any, and the subcommand's name
+pub type SubcommandPath = (Option<&'static str>, String);
+
+/// Builds the top-level `name` command with each of the flat `commands` as a
+/// subcommand, placed by `path` in one of the `groups` (name, description)
+pub fn tree(
+    name: &str,
+    description: &str,
+    groups: &[(&'static str, &'static str)],
+    commands: Vec<CreateApplicationCommand>,
+    path: impl Fn(&str) -> SubcommandPath,
+) -> CreateApplicationCommand {
+    let mut grouped = BTreeMap::<&str, Vec<Value>>::new();
+    let mut options = Vec::new();
+    for command in commands {
+        let (group, subcommand) = path(command_name(&command));
+        let option = json!({
+            "type": CommandOptionType::SubCommand as u8,
+            "name": subcommand,
+            "description": command.0.get("description").cloned().unwrap_or_default(),
+            "options": command.0.get("options").cloned().unwrap_or_else(|| json!([])),
+        });
+        match group {
+            Some(group) => grouped.entry(group).or_default().push(option),
+            None => options.push(option),
+        }
+    }
+    for (group, group_description) in groups {
+        if let Some(subcommands) = grouped.remove(group) {
+            options.push(json!({
+                "type": CommandOptionType::SubCommandGroup as u8,
+                "name": group,
+                "description": group_description,
+                "options": subcommands,
+            }));
+        }
+    }
+
+    let mut command = CreateApplicationCommand::default();
+    command.name(name).description(description);
+    command.0.insert("options", Value::Array(options));
+    command
+}
+
+/// Turns a subcommand invocation into the flat command it stands for, taking
+/// the subcommand's name and options. Other commands are left as they are.
+pub fn flatten(data: &mut CommandData) {
+    let (group, subcommand, options) = match data.options.first() {
+        Some(group) if group.kind == CommandOptionType::SubCommandGroup => {
+            match group.options.first() {
+                Some(subcommand) => (
+                    Some(group.name.as_str()),
+                    subcommand.name.as_str(),
+                    subcommand.options.clone(),
+                ),
+                None => return,
+            }
+        }
+        Some(subcommand) if subcommand.kind == CommandOptionType::SubCommand => {
+            (None, subcommand.name.as_str(), subcommand.options.clone())
+        }
+        _ => return,
+    };
+    let name = match data.name.as_str() {
+        name if name.starts_with("cln-") => cln::command_name(subcommand),
+        "ln" => ln::command_name(subcommand),
+        "fm" => fed::command_name(group, subcommand),
+        _ => return,
+    };
+
+    data.name = name;
+    data.options = options;
+}
+
+fn command_name(command: &CreateApplicationCommand) -> &str {
+    command
+        .0
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fm_commands_round_trip_through_the_tree() {
+        let tree = fed::tree();
+        let options = tree.0["options"].as_array().unwrap();
+        let ln_group = options.iter().find(|o| o["name"] == "ln").unwrap();
+        assert_eq!(ln_group["type"], CommandOptionType::SubCommandGroup as u8);
+        assert!(ln_group["options"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|o| o["name"] == "pay"));
+        assert!(options.iter().any(|o| o["name"] == "info"));
+
+        let mut data: CommandData = serde_json::from_value(json!({
+            "id": "1",
+            "name": "fm",
+            "type": 1,
+            "options": [{
+                "name": "ln",
+                "type": 2,
+                "options": [{
+                    "name": "pay",
+                    "type": 1,
+                    "options": [{ "name": "payment_info", "type": 3, "value": "lnbc1" }],
+                }],
+            }],
+        }))
+        .unwrap();
+        flatten(&mut data);
+
+        assert_eq!(data.name, "fm_ln_pay");
+        assert_eq!(data.options.len(), 1);
+        assert_eq!(data.options[0].name, "payment_info");
+    }
+
+    // Discord counts the names, descriptions and choices of a command and
+    // all its options towards its limit
+    fn text_len(value: &Value) -> usize {
+        match value {
+            Value::Object(fields) => fields
+                .iter()
+                .map(|(key, value)| match (key.as_str(), value) {
+                    ("name" | "description" | "value", Value::String(text)) => text.chars().count(),
+                    ("value", value) => value.to_string().len(),
+                    _ => text_len(value),
+                })
+                .sum(),
+            Value::Array(values) => values.iter().map(text_len).sum(),
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn every_cln_command_is_in_a_tree() {
+        let trees = cln::trees();
+        let mut count = 0;
+        for tree in &trees {
+            assert!(tree.0["name"].as_str().unwrap().starts_with("cln-"));
+            let options = tree.0["options"].as_array().unwrap();
+            assert!(!options.is_empty() && options.len() <= 25);
+            for option in options {
+                assert_eq!(option["type"], CommandOptionType::SubCommand as u8);
+            }
+            count += options.len();
+        }
+        assert_eq!(count, cln::commands().len());
+    }
+
+    #[test]
+    fn trees_fit_in_a_discord_command() {
+        let mut trees = cln::trees();
+        trees.push(ln::tree());
+        trees.push(fed::tree());
+        for tree in trees {
+            let len = text_len(&json!(tree.0));
+            assert!(len <= 8000, "{} is {} characters", tree.0["name"], len);
+        }
+    }
+}
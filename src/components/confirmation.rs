@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serenity::client::Context;
+use serenity::model::application::component::ButtonStyle;
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+// How long the invoking user has to confirm before the action is dropped
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+pub const PREFIX: &str = "confirm";
+
+/// Commands waiting for their invoking user to press Confirm or Cancel, keyed
+/// by the id embedded in the buttons' custom ids
+#[derive(Clone, Default)]
+pub struct Confirmations {
+    pending: Arc<Mutex<HashMap<String, ApplicationCommandInteraction>>>,
+}
+
+impl Confirmations {
+    /// Replaces the deferred response with the summary of what the command is
+    /// about to do and Confirm/Cancel buttons, dropping the command if it isn't
+    /// confirmed within `TIMEOUT`
+    pub async fn ask(
+        &self,
+        ctx: &Context,
+        command: ApplicationCommandInteraction,
+        summary: String,
+    ) {
+        let key = uuid::Uuid::new_v4().to_string();
+        let content = format!(
+            "**Confirm `{}`**\n{}\n\nConfirm within {} seconds to continue.",
+            command.data.name,
+            summary,
+            TIMEOUT.as_secs()
+        );
+
+        if let Err(why) = command
+            .edit_original_interaction_response(&ctx.http, |response| {
+                response.content(content).components(|components| {
+                    components.create_action_row(|row| {
+                        row.create_button(|button| {
+                            button
+                                .custom_id(format!("{}:{}:confirm", PREFIX, key))
+                                .label("Confirm")
+                                .style(ButtonStyle::Danger)
+                        })
+                        .create_button(|button| {
+                            button
+                                .custom_id(format!("{}:{}:cancel", PREFIX, key))
+                                .label("Cancel")
+                                .style(ButtonStyle::Secondary)
+                        })
+                    })
+                })
+            })
+            .await
+        {
+            error!("Cannot ask for confirmation: {:?}", why);
+            return;
+        }
+        self.pending.lock().await.insert(key.clone(), command);
+
+        let pending = self.pending.clone();
+        let http = ctx.http.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(TIMEOUT).await;
+            let command = match pending.lock().await.remove(&key) {
+                Some(command) => command,
+                None => return,
+            };
+            info!("Confirmation of {} timed out", command.data.name);
+            let content = format!(
+                "`{}` was not confirmed in time and was not run.",
+                command.data.name
+            );
+            if let Err(why) = command
+                .edit_original_interaction_response(&http, |response| {
+                    response.content(content).components(|c| c)
+                })
+                .await
+            {
+                error!("Cannot expire confirmation: {:?}", why);
+            }
+        });
+    }
+
+    /// Handles the Confirm/Cancel buttons, returning the command to run when
+    /// its invoking user confirmed it
+    pub async fn handle_component(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+        key: &str,
+        action: &str,
+    ) -> Option<ApplicationCommandInteraction> {
+        let mut pending = self.pending.lock().await;
+        let owner = pending.get(key).map(|command| command.user.id);
+        let (content, confirmed) = match owner {
+            None => ("This confirmation has expired.".to_string(), None),
+            Some(owner) if owner != component.user.id => {
+                drop(pending);
+                not_owner(ctx, component).await;
+                return None;
+            }
+            Some(_) => {
+                let command = pending.remove(key)?;
+                match action {
+                    "confirm" => (format!("Running `{}`…", command.data.name), Some(command)),
+                    _ => (format!("Cancelled `{}`.", command.data.name), None),
+                }
+            }
+        };
+        drop(pending);
+
+        if let Err(why) = component
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|message| message.content(content).components(|c| c))
+            })
+            .await
+        {
+            error!("Cannot respond to confirmation: {:?}", why);
+        }
+
+        confirmed
+    }
+}
+
+async fn not_owner(ctx: &Context, component: &MessageComponentInteraction) {
+    if let Err(why) = component
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    message
+                        .content("Only the user who ran the command can confirm it.")
+                        .ephemeral(true)
+                })
+        })
+        .await
+    {
+        error!("Cannot respond to confirmation: {:?}", why);
+    }
+}
@@ -0,0 +1,62 @@
+use serenity::client::Context;
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::modal::ModalSubmitInteraction;
+use tracing::warn;
+
+use self::confirmation::Confirmations;
+use self::pagination::Paginator;
+
+pub mod confirmation;
+pub mod pagination;
+
+// Component custom ids are "<prefix>:<key>:<action>", where the prefix picks
+// the subsystem and the key identifies the state the component belongs to
+fn parse_custom_id(custom_id: &str) -> Option<(&str, &str, &str)> {
+    let mut parts = custom_id.splitn(3, ':');
+    Some((parts.next()?, parts.next()?, parts.next()?))
+}
+
+/// State behind the interactive components the bot attaches to its messages
+#[derive(Clone, Default)]
+pub struct Components {
+    pub paginator: Paginator,
+    pub confirmations: Confirmations,
+}
+
+impl Components {
+    /// Routes a button press to the subsystem that owns it. Returns the
+    /// command to run when the press confirmed a pending command.
+    pub async fn handle_component(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+    ) -> Option<ApplicationCommandInteraction> {
+        match parse_custom_id(&component.data.custom_id) {
+            Some((pagination::PREFIX, key, action)) => {
+                self.paginator
+                    .handle_component(ctx, component, key, action)
+                    .await;
+                None
+            }
+            Some((confirmation::PREFIX, key, action)) => {
+                self.confirmations
+                    .handle_component(ctx, component, key, action)
+                    .await
+            }
+            _ => {
+                warn!("Unknown component: {}", component.data.custom_id);
+                None
+            }
+        }
+    }
+
+    pub async fn handle_modal(&self, ctx: &Context, modal: &ModalSubmitInteraction) {
+        match parse_custom_id(&modal.data.custom_id) {
+            Some((pagination::PREFIX, key, _)) => {
+                self.paginator.handle_modal(ctx, modal, key).await
+            }
+            _ => warn!("Unknown modal: {}", modal.data.custom_id),
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use serenity::builder::{CreateComponents, CreateEmbed};
+use serenity::client::Context;
+use serenity::model::application::component::{ActionRowComponent, ButtonStyle, InputTextStyle};
+use serenity::model::application::interaction::application_command::ApplicationCommandInteraction;
+use serenity::model::application::interaction::message_component::MessageComponentInteraction;
+use serenity::model::application::interaction::modal::ModalSubmitInteraction;
+use serenity::model::application::interaction::InteractionResponseType;
+use serenity::model::id::UserId;
+use tokio::sync::Mutex;
+use tracing::error;
+
+// Items shown on each page of a list
+const PAGE_SIZE: usize = 5;
+
+// Buttons stop working after this long. Kept under the 15 minutes an
+// interaction token is valid for, so the buttons can still be removed.
+const EXPIRY: Duration = Duration::from_secs(10 * 60);
+
+// Discord's limit on the length of an embed field's value
+const FIELD_LIMIT: usize = 1024;
+
+pub const PREFIX: &str = "page";
+
+/// A list returned by a command, rendered as embeds with one page per
+/// `PAGE_SIZE` items
+#[derive(Debug, Clone)]
+pub struct PagedList {
+    title: String,
+    items: Vec<(String, Value)>,
+}
+
+impl PagedList {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            items: Vec::new(),
+        }
+    }
+
+    /// Appends items, naming each one `<kind> <label>` where the label is the
+    /// item's `label_key` field, falling back to its position in the list
+    pub fn with_items<T: Serialize>(
+        mut self,
+        kind: &str,
+        label_key: &str,
+        items: impl IntoIterator<Item = T>,
+    ) -> Self {
+        for item in items {
+            let value = json!(item);
+            let label = match &value[label_key] {
+                Value::String(label) => label.clone(),
+                Value::Null => format!("#{}", self.items.len() + 1),
+                label => label.to_string(),
+            };
+            self.items.push((format!("{} {}", kind, label), value));
+        }
+        self
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The items with their names, in order
+    pub fn items(&self) -> &[(String, Value)] {
+        &self.items
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn pages(&self) -> usize {
+        self.items.len().div_ceil(PAGE_SIZE)
+    }
+}
+
+struct Session {
+    list: PagedList,
+    page: usize,
+    owner: UserId,
+}
+
+/// Keeps the lists behind paginated messages so their buttons can flip pages,
+/// keyed by the id embedded in each button's custom id
+#[derive(Clone, Default)]
+pub struct Paginator {
+    sessions: Arc<Mutex<HashMap<String, Session>>>,
+}
+
+impl Paginator {
+    /// Replaces the deferred response with the first page of the list and
+    /// removes its buttons once they expire
+    pub async fn send(
+        &self,
+        ctx: &Context,
+        command: &ApplicationCommandInteraction,
+        list: PagedList,
+    ) {
+        if list.is_empty() {
+            let content = format!("No {} found.", list.title.to_lowercase());
+            if let Err(why) = command
+                .edit_original_interaction_response(&ctx.http, |response| response.content(content))
+                .await
+            {
+                error!("Cannot respond to slash command: {:?}", why);
+            }
+            return;
+        }
+
+        let key = uuid::Uuid::new_v4().to_string();
+        let session = Session {
+            list,
+            page: 0,
+            owner: command.user.id,
+        };
+        let mut embed = CreateEmbed::default();
+        let mut components = CreateComponents::default();
+        render(&key, &session, &mut embed, &mut components);
+        self.sessions.lock().await.insert(key.clone(), session);
+
+        if let Err(why) = command
+            .edit_original_interaction_response(&ctx.http, |response| {
+                response.set_embed(embed).components(|c| {
+                    *c = components;
+                    c
+                })
+            })
+            .await
+        {
+            error!("Cannot respond to slash command: {:?}", why);
+            self.sessions.lock().await.remove(&key);
+            return;
+        }
+
+        let sessions = self.sessions.clone();
+        let http = ctx.http.clone();
+        let command = command.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(EXPIRY).await;
+            sessions.lock().await.remove(&key);
+            if let Err(why) = command
+                .edit_original_interaction_response(&http, |response| response.components(|c| c))
+                .await
+            {
+                error!("Cannot remove expired page buttons: {:?}", why);
+            }
+        });
+    }
+
+    /// Handles the Previous/Next/Jump buttons. Jump asks for a page number in a
+    /// modal, which comes back through `handle_modal`.
+    pub async fn handle_component(
+        &self,
+        ctx: &Context,
+        component: &MessageComponentInteraction,
+        key: &str,
+        action: &str,
+    ) {
+        let mut sessions = self.sessions.lock().await;
+        let session = match sessions.get_mut(key) {
+            Some(session) => session,
+            None => {
+                drop(sessions);
+                return expired(ctx, component).await;
+            }
+        };
+        if component.user.id != session.owner {
+            drop(sessions);
+            return not_owner(ctx, component).await;
+        }
+
+        let page = match action {
+            "prev" => session.page.saturating_sub(1),
+            "next" => (session.page + 1).min(session.list.pages() - 1),
+            "jump" => {
+                let pages = session.list.pages();
+                drop(sessions);
+                return ask_page(ctx, component, key, pages).await;
+            }
+            _ => return,
+        };
+        session.page = page;
+
+        let mut embed = CreateEmbed::default();
+        let mut components = CreateComponents::default();
+        render(key, session, &mut embed, &mut components);
+        drop(sessions);
+
+        if let Err(why) = component
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|message| {
+                        message.set_embed(embed).set_components(components)
+                    })
+            })
+            .await
+        {
+            error!("Cannot update page: {:?}", why);
+        }
+    }
+
+    /// Handles the page number submitted from the Jump modal
+    pub async fn handle_modal(&self, ctx: &Context, modal: &ModalSubmitInteraction, key: &str) {
+        let input = modal
+            .data
+            .components
+            .iter()
+            .flat_map(|row| row.components.iter())
+            .find_map(|component| match component {
+                ActionRowComponent::InputText(input) => Some(input.value.trim().to_string()),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        let mut sessions = self.sessions.lock().await;
+        let (embed, components) = match sessions.get_mut(key) {
+            Some(session) if session.owner == modal.user.id => {
+                let pages = session.list.pages();
+                match input.parse::<usize>() {
+                    Ok(page) if (1..=pages).contains(&page) => {
+                        session.page = page - 1;
+                        let mut embed = CreateEmbed::default();
+                        let mut components = CreateComponents::default();
+                        render(key, session, &mut embed, &mut components);
+                        (embed, components)
+                    }
+                    _ => {
+                        drop(sessions);
+                        let content = format!("Enter a page number from 1 to {}.", pages);
+                        return respond_ephemeral(ctx, modal, content).await;
+                    }
+                }
+            }
+            Some(_) => {
+                drop(sessions);
+                let content = "Only the user who ran the command can change pages.";
+                return respond_ephemeral(ctx, modal, content).await;
+            }
+            None => {
+                drop(sessions);
+                let content = "This list has expired, run the command again.";
+                return respond_ephemeral(ctx, modal, content).await;
+            }
+        };
+        drop(sessions);
+
+        if let Err(why) = modal
+            .create_interaction_response(&ctx.http, |response| {
+                response
+                    .kind(InteractionResponseType::UpdateMessage)
+                    .interaction_response_data(|message| {
+                        message.set_embed(embed).set_components(components)
+                    })
+            })
+            .await
+        {
+            error!("Cannot update page: {:?}", why);
+        }
+    }
+}
+
+fn render(
+    key: &str,
+    session: &Session,
+    embed: &mut CreateEmbed,
+    components: &mut CreateComponents,
+) {
+    let pages = session.list.pages();
+    let start = session.page * PAGE_SIZE;
+    let end = (start + PAGE_SIZE).min(session.list.items.len());
+
+    embed.title(&session.list.title).footer(|footer| {
+        footer.text(format!(
+            "Page {}/{} · {} items",
+            session.page + 1,
+            pages,
+            session.list.items.len()
+        ))
+    });
+    for (name, value) in &session.list.items[start..end] {
+        embed.field(name, field_value(value), false);
+    }
+
+    components.create_action_row(|row| {
+        row.create_button(|button| {
+            button
+                .custom_id(format!("{}:{}:prev", PREFIX, key))
+                .label("Previous")
+                .style(ButtonStyle::Secondary)
+                .disabled(session.page == 0)
+        })
+        .create_button(|button| {
+            button
+                .custom_id(format!("{}:{}:next", PREFIX, key))
+                .label("Next")
+                .style(ButtonStyle::Secondary)
+                .disabled(session.page + 1 >= pages)
+        })
+        .create_button(|button| {
+            button
+                .custom_id(format!("{}:{}:jump", PREFIX, key))
+                .label("Jump")
+                .style(ButtonStyle::Primary)
+                .disabled(pages <= 1)
+        })
+    });
+}
+
+// Renders an item as a JSON code block, cut short to fit in an embed field
+fn field_value(value: &Value) -> String {
+    let json = serde_json::to_string_pretty(value).unwrap_or_default();
+    let budget = FIELD_LIMIT - "```json\n\n```".len();
+    if json.chars().count() <= budget {
+        return format!("```json\n{}\n```", json);
+    }
+
+    let truncated = json.chars().take(budget - 2).collect::<String>();
+    format!("```json\n{}\n…\n```", truncated)
+}
+
+async fn ask_page(ctx: &Context, component: &MessageComponentInteraction, key: &str, pages: usize) {
+    if let Err(why) = component
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::Modal)
+                .interaction_response_data(|modal| {
+                    modal
+                        .custom_id(format!("{}:{}:jump", PREFIX, key))
+                        .title("Jump to page")
+                        .components(|components| {
+                            components.create_action_row(|row| {
+                                row.create_input_text(|input| {
+                                    input
+                                        .custom_id("page")
+                                        .label(format!("Page (1-{})", pages))
+                                        .style(InputTextStyle::Short)
+                                        .required(true)
+                                })
+                            })
+                        })
+                })
+        })
+        .await
+    {
+        error!("Cannot open page modal: {:?}", why);
+    }
+}
+
+async fn expired(ctx: &Context, component: &MessageComponentInteraction) {
+    // Drop the dead buttons so nobody else tries them
+    if let Err(why) = component
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|message| {
+                    message.set_components(CreateComponents::default())
+                })
+        })
+        .await
+    {
+        error!("Cannot remove expired page buttons: {:?}", why);
+    }
+}
+
+async fn not_owner(ctx: &Context, component: &MessageComponentInteraction) {
+    if let Err(why) = component
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    message
+                        .content("Only the user who ran the command can change pages.")
+                        .ephemeral(true)
+                })
+        })
+        .await
+    {
+        error!("Cannot respond to page button: {:?}", why);
+    }
+}
+
+async fn respond_ephemeral(ctx: &Context, modal: &ModalSubmitInteraction, content: impl ToString) {
+    if let Err(why) = modal
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| message.content(content).ephemeral(true))
+        })
+        .await
+    {
+        error!("Cannot respond to page modal: {:?}", why);
+    }
+}
@@ -1,24 +1,65 @@
+use std::collections::HashMap;
 use std::env;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use fedimint_client::derivable_secret::DerivableSecret;
 use fedimint_client::secret::{PlainRootSecretStrategy, RootSecretStrategy};
 use fedimint_core::api::InviteCode;
+use serenity::model::id::{ChannelId, GuildId, RoleId, UserId};
+use time::Time;
 use tracing::info;
 
+use crate::lightning::{ClnNodeConfig, LightningConfig};
+use crate::notifications::cln_alerts;
+use crate::notifications::report::ReportSection;
+use crate::permissions::{Grants, PermissionsConfig};
+
 lazy_static::lazy_static! {
     pub static ref CONFIG: Config =
         Config::from_env().expect("Failed to load config from environment");
 }
 
 pub struct Config {
-    pub guild_id: String,
+    pub guild_ids: Vec<GuildId>,
     pub discord_client_token: String,
-    pub cln_rpc_path: PathBuf,
+    pub lightning: LightningConfig,
+    pub cln_nodes: Vec<ClnNodeConfig>,
     pub fm_db_path: PathBuf,
+    pub fm_federations_dir: PathBuf,
+    pub ledger_db_path: PathBuf,
     pub invite_code: InviteCode,
     pub root_secret: DerivableSecret,
+    pub permissions: PermissionsConfig,
+    pub tip_message_amount_msat: u64,
+    pub tip_reactions: HashMap<String, u64>,
+    pub api: Option<ApiConfig>,
+    pub invoice_channel_id: Option<ChannelId>,
+    pub operation_channel_id: Option<ChannelId>,
+    pub operation_kinds: Vec<String>,
+    pub cln_alert_channels: HashMap<String, ChannelId>,
+    pub report: ReportConfig,
+    pub admin_channel_id: Option<ChannelId>,
+}
+
+/// Settings of the health report, posted daily when `REPORT_CHANNEL_ID` is set
+/// and shown by `/report`
+pub struct ReportConfig {
+    pub channel_id: Option<ChannelId>,
+    /// When the daily report is posted, in UTC
+    pub time: Time,
+    pub sections: Vec<ReportSection>,
+}
+
+/// Settings of the HTTP API, served when `API_BIND_ADDR` is set
+pub struct ApiConfig {
+    pub bind_addr: SocketAddr,
+    pub token: String,
+    pub user_id: UserId,
+    /// The token `botimint-cln-plugin` posts notifications with, which is good
+    /// for nothing else
+    pub notify_token: Option<String>,
 }
 
 impl Config {
@@ -26,12 +67,18 @@ impl Config {
         dotenv::dotenv().ok();
         info!("Loaded environment variables");
 
-        let guild_id = env::var("GUILD_ID")?;
+        // Commands are registered globally unless guilds are configured
+        let guild_ids = parse_ids("GUILD_ID").into_iter().map(GuildId).collect();
         info!("Loaded GUILD_ID");
         let discord_client_token = env::var("DISCORD_CLIENT_TOKEN")?;
         info!("Loaded DISCORD_CLIENT_TOKEN");
-        let cln_rpc_path = PathBuf::from(env::var("CLN_RPC_PATH")?);
-        info!("Loaded CLN_RPC_PATH");
+        let cln_nodes = load_cln_nodes();
+        info!("Loaded CLN_NODES");
+        let lightning = load_lightning()?;
+        if matches!(lightning, LightningConfig::Cln) && cln_nodes.is_empty() {
+            panic!("LIGHTNING_BACKEND=cln needs CLN_RPC_PATH or CLN_NODES");
+        }
+        info!("Loaded LIGHTNING_BACKEND");
         let invite_code = match InviteCode::from_str(&env::var("FEDERATION_INVITE_CODE")?) {
             Ok(invite_code) => invite_code,
             Err(e) => panic!("Invalid invite code: {}", e),
@@ -41,6 +88,29 @@ impl Config {
         let fm_db_path = PathBuf::from(env::var("FM_DB_PATH")?);
         info!("Loaded FM_DB_PATH");
 
+        // The user ledger lives next to the fedimint client database unless
+        // configured otherwise
+        let ledger_db_path = match env::var("LEDGER_DB_PATH") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => {
+                let mut path = fm_db_path.clone().into_os_string();
+                path.push("_ledger");
+                PathBuf::from(path)
+            }
+        };
+        info!("Loaded LEDGER_DB_PATH");
+
+        // Federations joined with /fm_join get a client database each in here
+        let fm_federations_dir = match env::var("FM_FEDERATIONS_DIR") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => {
+                let mut path = fm_db_path.clone().into_os_string();
+                path.push("_federations");
+                PathBuf::from(path)
+            }
+        };
+        info!("Loaded FM_FEDERATIONS_DIR");
+
         // Read the secret from the environment
         let secret = env::var("SECRET_KEY").expect("SECRET_KEY must be set");
         info!("Loaded SECRET_KEY");
@@ -48,17 +118,287 @@ impl Config {
         let root_secret = create_root_secret(secret);
         info!("Created root secret");
 
+        let permissions = PermissionsConfig {
+            read_only: load_grants("READ_ONLY"),
+            spending: load_grants("SPENDING"),
+            admin: load_grants("ADMIN"),
+        };
+        info!("Loaded command permissions");
+
+        let tip_message_amount_msat = match env::var("TIP_MESSAGE_AMOUNT_MSAT") {
+            Ok(amount) => match amount.parse::<u64>() {
+                Ok(amount) => amount,
+                Err(e) => panic!("Invalid TIP_MESSAGE_AMOUNT_MSAT: {}", e),
+            },
+            Err(_) => 100_000,
+        };
+        info!("Loaded TIP_MESSAGE_AMOUNT_MSAT");
+
+        // Reaction tips aren't confirmed, so they are off unless configured
+        let tip_reactions = parse_tip_reactions(&env::var("TIP_REACTIONS").unwrap_or_default());
+        info!("Loaded TIP_REACTIONS");
+
+        let api = load_api();
+        info!("Loaded API_BIND_ADDR");
+
+        let invoice_channel_id = load_channel_id("INVOICE_CHANNEL_ID");
+        info!("Loaded INVOICE_CHANNEL_ID");
+
+        let operation_channel_id = load_channel_id("OPERATION_CHANNEL_ID");
+        info!("Loaded OPERATION_CHANNEL_ID");
+        // Kinds like `ln_pay` or whole modules like `wallet`, all if unset
+        let operation_kinds = env::var("OPERATION_KINDS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|kind| kind.trim().to_lowercase())
+            .filter(|kind| !kind.is_empty())
+            .collect();
+        info!("Loaded OPERATION_KINDS");
+
+        let cln_alert_channels =
+            parse_cln_alert_channels(&env::var("CLN_ALERT_CHANNELS").unwrap_or_default());
+        info!("Loaded CLN_ALERT_CHANNELS");
+
+        let report = load_report();
+        info!("Loaded REPORT_CHANNEL_ID");
+
+        let admin_channel_id = load_channel_id("ADMIN_CHANNEL_ID");
+        info!("Loaded ADMIN_CHANNEL_ID");
+
         Ok(Self {
-            guild_id,
+            guild_ids,
             discord_client_token,
-            cln_rpc_path,
+            lightning,
+            cln_nodes,
             fm_db_path,
+            fm_federations_dir,
+            ledger_db_path,
             invite_code,
             root_secret,
+            permissions,
+            tip_message_amount_msat,
+            tip_reactions,
+            api,
+            invoice_channel_id,
+            operation_channel_id,
+            operation_kinds,
+            cln_alert_channels,
+            report,
+            admin_channel_id,
         })
     }
 }
 
+// Reads the settings of the Lightning node selected by `LIGHTNING_BACKEND`,
+// which defaults to CLN
+fn load_lightning() -> Result<LightningConfig, env::VarError> {
+    let backend = env::var("LIGHTNING_BACKEND").unwrap_or_else(|_| "cln".to_string());
+    match backend.to_lowercase().as_str() {
+        "cln" => Ok(LightningConfig::Cln),
+        "lnd" => Ok(LightningConfig::Lnd {
+            rest_url: env::var("LND_REST_URL")?,
+            macaroon_path: PathBuf::from(env::var("LND_MACAROON_PATH")?),
+            tls_cert_path: env::var("LND_TLS_CERT_PATH").ok().map(PathBuf::from),
+        }),
+        backend => panic!("Invalid LIGHTNING_BACKEND: {}", backend),
+    }
+}
+
+// Reads the CLN nodes from `CLN_NODES`, comma separated `name=rpc_path` pairs,
+// falling back to a single node named `main` at `CLN_RPC_PATH`
+fn load_cln_nodes() -> Vec<ClnNodeConfig> {
+    let nodes = match env::var("CLN_NODES") {
+        Ok(nodes) => nodes,
+        Err(_) => {
+            return env::var("CLN_RPC_PATH")
+                .into_iter()
+                .map(|rpc_path| ClnNodeConfig {
+                    name: "main".to_string(),
+                    rpc_path: PathBuf::from(rpc_path),
+                })
+                .collect()
+        }
+    };
+
+    let mut configs: Vec<ClnNodeConfig> = Vec::new();
+    for node in nodes
+        .split(',')
+        .map(str::trim)
+        .filter(|node| !node.is_empty())
+    {
+        let (name, rpc_path) = match node.split_once('=') {
+            Some((name, rpc_path)) => (name.trim(), rpc_path.trim()),
+            None => panic!("Invalid CLN node, expected name=rpc_path: {}", node),
+        };
+        if configs
+            .iter()
+            .any(|config| config.name.eq_ignore_ascii_case(name))
+        {
+            panic!("Duplicate CLN node name: {}", name);
+        }
+        configs.push(ClnNodeConfig {
+            name: name.to_string(),
+            rpc_path: PathBuf::from(rpc_path),
+        });
+    }
+    configs
+}
+
+// The API is off unless `API_BIND_ADDR` is set, and then needs a token and
+// `API_USER_ID`, the ledger account its `fm_` commands use. CLN
+// notifications are only taken with their own `CLN_NOTIFY_TOKEN`.
+fn load_api() -> Option<ApiConfig> {
+    let bind_addr = env::var("API_BIND_ADDR").ok()?;
+    let bind_addr = match bind_addr.parse::<SocketAddr>() {
+        Ok(bind_addr) => bind_addr,
+        Err(e) => panic!("Invalid API_BIND_ADDR: {}", e),
+    };
+    let token = match env::var("API_TOKEN") {
+        Ok(token) if !token.trim().is_empty() => token.trim().to_string(),
+        _ => panic!("API_BIND_ADDR is set but API_TOKEN is missing"),
+    };
+    let user_id = match env::var("API_USER_ID") {
+        Ok(id) => match id.trim().parse::<u64>() {
+            Ok(id) => UserId(id),
+            Err(e) => panic!("Invalid API_USER_ID: {}", e),
+        },
+        Err(_) => panic!("API_BIND_ADDR is set but API_USER_ID is missing"),
+    };
+    let notify_token = match env::var("CLN_NOTIFY_TOKEN") {
+        Ok(notify_token) if notify_token.trim() == token => {
+            panic!("CLN_NOTIFY_TOKEN must differ from API_TOKEN")
+        }
+        Ok(notify_token) if !notify_token.trim().is_empty() => {
+            Some(notify_token.trim().to_string())
+        }
+        _ => None,
+    };
+
+    Some(ApiConfig {
+        bind_addr,
+        token,
+        user_id,
+        notify_token,
+    })
+}
+
+// The report is posted at 09:00 UTC with every section unless `REPORT_TIME`
+// (HH:MM) and `REPORT_SECTIONS` say otherwise
+fn load_report() -> ReportConfig {
+    let time = env::var("REPORT_TIME").unwrap_or_else(|_| "09:00".to_string());
+    let time = match time
+        .trim()
+        .split_once(':')
+        .and_then(|(hour, minute)| Some((hour.parse().ok()?, minute.parse().ok()?)))
+        .and_then(|(hour, minute)| Time::from_hms(hour, minute, 0).ok())
+    {
+        Some(time) => time,
+        None => panic!("Invalid REPORT_TIME, expected HH:MM: {}", time),
+    };
+    let sections = match env::var("REPORT_SECTIONS") {
+        Ok(sections) if !sections.trim().is_empty() => sections
+            .split(',')
+            .map(|section| match section.parse::<ReportSection>() {
+                Ok(section) => section,
+                Err(e) => panic!("Invalid REPORT_SECTIONS: {}", e),
+            })
+            .collect(),
+        _ => ReportSection::ALL.to_vec(),
+    };
+
+    ReportConfig {
+        channel_id: load_channel_id("REPORT_CHANNEL_ID"),
+        time,
+        sections,
+    }
+}
+
+// Reads the id of the channel notifications are posted to, if any
+fn load_channel_id(var: &str) -> Option<ChannelId> {
+    let id = env::var(var).ok().filter(|id| !id.trim().is_empty())?;
+    match id.trim().parse::<u64>() {
+        Ok(id) => Some(ChannelId(id)),
+        Err(e) => panic!("Invalid {}: {}", var, e),
+    }
+}
+
+// Reads the optional `<TIER>_ROLE_IDS` and `<TIER>_USER_IDS` comma separated
+// lists of Discord ids
+fn load_grants(tier: &str) -> Grants {
+    Grants {
+        role_ids: parse_ids(&format!("{}_ROLE_IDS", tier))
+            .into_iter()
+            .map(RoleId)
+            .collect(),
+        user_ids: parse_ids(&format!("{}_USER_IDS", tier))
+            .into_iter()
+            .map(UserId)
+            .collect(),
+    }
+}
+
+fn parse_ids(var: &str) -> Vec<u64> {
+    env::var(var)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| match id.parse::<u64>() {
+            Ok(id) => id,
+            Err(e) => panic!("Invalid id in {}: {}", var, e),
+        })
+        .collect()
+}
+
+// Parses `emoji=amount_msat` pairs separated by commas
+fn parse_tip_reactions(reactions: &str) -> HashMap<String, u64> {
+    reactions
+        .split(',')
+        .map(str::trim)
+        .filter(|reaction| !reaction.is_empty())
+        .map(|reaction| {
+            let (emoji, amount) = match reaction.split_once('=') {
+                Some(pair) => pair,
+                None => panic!("Invalid tip reaction: {}", reaction),
+            };
+            match amount.trim().parse::<u64>() {
+                Ok(amount) => (emoji.trim().to_string(), amount),
+                Err(e) => panic!("Invalid tip reaction amount for {}: {}", emoji, e),
+            }
+        })
+        .collect()
+}
+
+// Parses `topic=channel_id` pairs routing CLN notifications to channels, with
+// `*` for the topics without a channel of their own
+fn parse_cln_alert_channels(routes: &str) -> HashMap<String, ChannelId> {
+    routes
+        .split(',')
+        .map(str::trim)
+        .filter(|route| !route.is_empty())
+        .map(|route| {
+            let (topic, id) = match route.split_once('=') {
+                Some((topic, id)) => (topic.trim(), id.trim()),
+                None => panic!(
+                    "Invalid CLN alert route, expected topic=channel_id: {}",
+                    route
+                ),
+            };
+            if topic != "*" && !cln_alerts::TOPICS.contains(&topic) {
+                panic!(
+                    "Unknown CLN alert topic {}, expected one of {} or *",
+                    topic,
+                    cln_alerts::TOPICS.join(", ")
+                );
+            }
+            match id.parse::<u64>() {
+                Ok(id) => (topic.to_string(), ChannelId(id)),
+                Err(e) => panic!("Invalid CLN alert channel for {}: {}", topic, e),
+            }
+        })
+        .collect()
+}
+
 fn create_root_secret(secret: String) -> DerivableSecret {
     // Convert the secret to bytes
     let secret_bytes = secret.as_bytes();
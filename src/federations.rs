@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use fedimint_client::ClientArc;
+use fedimint_core::api::InviteCode;
+use fedimint_core::config::FederationId;
+use fedimint_core::Amount;
+use tracing::{error, info};
+
+use crate::config::CONFIG;
+use crate::ledger::Ledger;
+use crate::state::load_fedimint_client;
+
+// Marks the client database of a federation that was left. It is only moved
+// aside on the next start, when no client has it open.
+const LEFT_MARKER: &str = "LEFT";
+
+// Operations without an outcome are looked for among the client's latest ones
+const MAX_OPERATIONS: usize = 100;
+
+/// A federation the bot is a member of, with the ledger of the user balances
+/// it backs
+#[derive(Clone)]
+pub struct Federation {
+    pub client: ClientArc,
+    pub ledger: Ledger,
+}
+
+impl Federation {
+    pub fn id(&self) -> FederationId {
+        self.client.federation_id()
+    }
+
+    /// The name the federation gives itself in its config, if any
+    pub fn name(&self) -> Option<String> {
+        self.client
+            .get_config()
+            .global
+            .meta
+            .get("federation_name")
+            .cloned()
+    }
+
+    pub fn label(&self) -> String {
+        match self.name() {
+            Some(name) => format!("{} ({})", name, self.id()),
+            None => self.id().to_string(),
+        }
+    }
+}
+
+/// The federations the bot has joined, keyed by federation id. The one from
+/// `FEDERATION_INVITE_CODE` is the default for commands that don't pick one,
+/// the others are joined and left at runtime and each get their own client
+/// database under `FM_FEDERATIONS_DIR`.
+#[derive(Clone)]
+pub struct Federations {
+    default_id: FederationId,
+    federations: Arc<RwLock<BTreeMap<FederationId, Federation>>>,
+    dir: PathBuf,
+    ledger: Ledger,
+}
+
+impl Federations {
+    pub fn new(dir: PathBuf, default: Federation) -> Self {
+        let default_id = default.id();
+        let ledger = default.ledger.clone();
+        Self {
+            default_id,
+            federations: Arc::new(RwLock::new(BTreeMap::from([(default_id, default)]))),
+            dir,
+            ledger,
+        }
+    }
+
+    /// Loads the default federation and every federation joined in a previous
+    /// run. A joined federation that fails to load is skipped rather than
+    /// keeping the bot from starting.
+    pub async fn load(ledger: Ledger) -> Result<Self> {
+        let client =
+            load_fedimint_client(&CONFIG.fm_db_path, Some(CONFIG.invite_code.clone())).await?;
+        let federations = Self::new(
+            CONFIG.fm_federations_dir.clone(),
+            Federation { client, ledger },
+        );
+
+        if !federations.dir.exists() {
+            return Ok(federations);
+        }
+        for entry in std::fs::read_dir(&federations.dir)? {
+            let path = entry?.path();
+            // Left federations are renamed, so only directories named after a
+            // federation id are loaded
+            let federation_id = match path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| FederationId::from_str(name).ok())
+            {
+                Some(federation_id) => federation_id,
+                None => continue,
+            };
+            if path.join(LEFT_MARKER).exists() {
+                if let Err(e) = move_aside(&path) {
+                    error!("Cannot move left federation {}: {:?}", federation_id, e);
+                }
+                continue;
+            }
+            match load_fedimint_client(&path, None).await {
+                Ok(client) => {
+                    federations.insert(client);
+                }
+                Err(e) => error!("Cannot load federation {}: {:?}", federation_id, e),
+            }
+        }
+
+        Ok(federations)
+    }
+
+    fn insert(&self, client: ClientArc) -> Federation {
+        let federation_id = client.federation_id();
+        let federation = Federation {
+            client,
+            ledger: self.ledger.for_federation(federation_id),
+        };
+        self.federations
+            .write()
+            .unwrap()
+            .insert(federation_id, federation.clone());
+        info!("Loaded federation {}", federation_id);
+
+        federation
+    }
+
+    pub fn get(&self, federation_id: FederationId) -> Option<Federation> {
+        self.federations
+            .read()
+            .unwrap()
+            .get(&federation_id)
+            .cloned()
+    }
+
+    pub fn default_federation(&self) -> Federation {
+        self.federations.read().unwrap()[&self.default_id].clone()
+    }
+
+    pub fn is_default(&self, federation_id: FederationId) -> bool {
+        federation_id == self.default_id
+    }
+
+    /// Every federation, the default one first
+    pub fn list(&self) -> Vec<Federation> {
+        let federations = self.federations.read().unwrap();
+        let mut list = federations.values().cloned().collect::<Vec<_>>();
+        list.sort_by_key(|federation| !self.is_default(federation.id()));
+        list
+    }
+
+    /// Finds a federation by its id, a unique prefix of it or its name, or
+    /// returns the default federation when none is selected
+    pub fn select(&self, selector: Option<&str>) -> Result<Federation> {
+        let selector = match selector.map(str::trim) {
+            Some(selector) if !selector.is_empty() => selector,
+            _ => return Ok(self.default_federation()),
+        };
+
+        let matches = self
+            .list()
+            .into_iter()
+            .filter(|federation| {
+                federation.id().to_string().starts_with(selector)
+                    || federation
+                        .name()
+                        .map_or(false, |name| name.eq_ignore_ascii_case(selector))
+            })
+            .collect::<Vec<_>>();
+        match matches.as_slice() {
+            [federation] => Ok(federation.clone()),
+            [] => bail!("The bot isn't a member of federation {}", selector),
+            _ => bail!("{} matches more than one federation", selector),
+        }
+    }
+
+    /// Joins the federation of `invite_code`, storing its client database in
+    /// a new directory named after the federation id
+    pub async fn join(&self, invite_code: InviteCode) -> Result<Federation> {
+        let federation_id = invite_code.federation_id();
+        if self
+            .federations
+            .read()
+            .unwrap()
+            .contains_key(&federation_id)
+        {
+            bail!(
+                "The bot is already a member of federation {}",
+                federation_id
+            );
+        }
+
+        let path = self.dir.join(federation_id.to_string());
+        if path.join(LEFT_MARKER).exists() {
+            bail!(
+                "Federation {} was left since the bot started, restart it before joining again",
+                federation_id
+            );
+        }
+        if path.exists() {
+            bail!("Federation directory {:?} already exists", path);
+        }
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Cannot create {:?}", self.dir))?;
+        let client = match load_fedimint_client(&path, Some(invite_code)).await {
+            Ok(client) => client,
+            Err(e) => {
+                if let Err(why) = std::fs::remove_dir_all(&path) {
+                    error!("Cannot clean up {:?}: {:?}", path, why);
+                }
+                return Err(e);
+            }
+        };
+
+        Ok(self.insert(client))
+    }
+
+    /// Stops using a joined federation. Its client database is renamed on the
+    /// next start rather than deleted, so ecash that arrives later can still be
+    /// recovered. The client may still be in use until then, so federations
+    /// with operations in flight can't be left.
+    pub async fn leave(&self, federation_id: FederationId) -> Result<()> {
+        if self.is_default(federation_id) {
+            bail!("Cannot leave the default federation");
+        }
+        let federation = self
+            .get(federation_id)
+            .with_context(|| format!("The bot isn't a member of federation {}", federation_id))?;
+
+        let balance = federation.client.get_balance().await;
+        if balance > Amount::ZERO {
+            bail!(
+                "The bot still holds {} in federation {}, withdraw it before leaving",
+                balance,
+                federation_id
+            );
+        }
+
+        let pending = federation
+            .client
+            .operation_log()
+            .list_operations(MAX_OPERATIONS, None)
+            .await
+            .iter()
+            .filter(|(_, operation)| operation.outcome::<serde_json::Value>().is_none())
+            .count()
+            + federation.ledger.pending_operations().list().await.len()
+            + federation.ledger.pending_claims().await.len();
+        if pending > 0 {
+            bail!(
+                "Federation {} still has {} operations in flight, wait for them before leaving",
+                federation_id,
+                pending
+            );
+        }
+
+        let path = self.dir.join(federation_id.to_string());
+        std::fs::write(path.join(LEFT_MARKER), "")
+            .with_context(|| format!("Cannot mark {:?} as left", path))?;
+        self.federations.write().unwrap().remove(&federation_id);
+        info!("Left federation {}", federation_id);
+
+        Ok(())
+    }
+}
+
+// Renames the client database of a left federation, out of the way of loading
+// and joining
+fn move_aside(path: &Path) -> Result<()> {
+    let left_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let mut left_path = path.as_os_str().to_os_string();
+    left_path.push(format!(".left-{}", left_at));
+    std::fs::rename(path, &left_path)
+        .with_context(|| format!("Cannot move {:?} to {:?}", path, left_path))
+}
@@ -0,0 +1,410 @@
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use fedimint_core::config::FederationId;
+use fedimint_core::core::OperationId;
+use fedimint_core::db::{AutocommitError, Database};
+use fedimint_core::encoding::{Decodable, Encodable};
+use fedimint_core::{impl_db_lookup, impl_db_record, Amount};
+use futures::StreamExt;
+use serenity::model::id::UserId;
+use tracing::info;
+
+use crate::pending::{PendingOperationKey, PendingOperations};
+
+// Writes conflicting with a concurrent one are retried this many times
+pub(crate) const MAX_DB_ATTEMPTS: usize = 10;
+
+#[repr(u8)]
+#[derive(Clone, Debug)]
+pub enum DbKeyPrefix {
+    UserBalance = 0x01,
+    // Prefixes the records of federations joined at runtime
+    Federation = 0x02,
+    // Operations to resume after a restart, see `pending`
+    PendingOperation = 0x03,
+    // Failed operations whose cost was credited back to the user
+    RefundedOperation = 0x04,
+    // Deposit invoices to credit once the federation claims their payment
+    PendingClaim = 0x05,
+}
+
+#[derive(Debug, Clone, Encodable, Decodable)]
+pub struct UserBalanceKey(pub u64);
+
+#[derive(Debug, Encodable, Decodable)]
+pub struct UserBalanceKeyPrefix;
+
+impl_db_record!(
+    key = UserBalanceKey,
+    value = Amount,
+    db_prefix = DbKeyPrefix::UserBalance
+);
+impl_db_lookup!(key = UserBalanceKey, query_prefix = UserBalanceKeyPrefix);
+
+#[derive(Debug, Clone, Encodable, Decodable)]
+pub struct RefundedOperationKey(pub OperationId);
+
+impl_db_record!(
+    key = RefundedOperationKey,
+    value = Amount,
+    db_prefix = DbKeyPrefix::RefundedOperation
+);
+
+#[derive(Debug, Clone, Encodable, Decodable)]
+pub struct PendingClaimKey(pub OperationId);
+
+#[derive(Debug, Encodable, Decodable)]
+pub struct PendingClaimKeyPrefix;
+
+/// Who a deposit invoice credits, and how much
+#[derive(Debug, Clone, Encodable, Decodable)]
+pub struct PendingClaim {
+    pub user_id: u64,
+    pub amount: Amount,
+}
+
+impl_db_record!(
+    key = PendingClaimKey,
+    value = PendingClaim,
+    db_prefix = DbKeyPrefix::PendingClaim
+);
+impl_db_lookup!(key = PendingClaimKey, query_prefix = PendingClaimKeyPrefix);
+
+/// Custodial sub-accounts for Discord users, backed by the shared fedimint
+/// client's ecash. Every balance change happens in a single database
+/// transaction so concurrent commands can't overdraw an account.
+#[derive(Clone)]
+pub struct Ledger {
+    db: Database,
+}
+
+impl Ledger {
+    pub fn open(path: &Path) -> Result<Self> {
+        let db = Database::new(fedimint_rocksdb::RocksDb::open(path)?, Default::default());
+        Ok(Self { db })
+    }
+
+    /// The ledger of a federation joined with `/fm_join`, kept under its own
+    /// prefix so balances in different federations never mix. The default
+    /// federation uses the unprefixed ledger.
+    pub fn for_federation(&self, federation_id: FederationId) -> Self {
+        let mut prefix = vec![DbKeyPrefix::Federation as u8];
+        prefix.extend_from_slice(federation_id.to_string().as_bytes());
+        Self {
+            db: self.db.with_prefix(prefix),
+        }
+    }
+
+    /// The operations users are waiting on in this ledger's federation
+    pub fn pending_operations(&self) -> PendingOperations {
+        PendingOperations::new(self.db.clone())
+    }
+
+    pub async fn balance(&self, user_id: UserId) -> Amount {
+        self.db
+            .begin_transaction_nc()
+            .await
+            .get_value(&UserBalanceKey(user_id.0))
+            .await
+            .unwrap_or(Amount::ZERO)
+    }
+
+    /// Adds `amount` to the user's balance, returning the new balance
+    pub async fn credit(&self, user_id: UserId, amount: Amount) -> Result<Amount> {
+        let balance = self
+            .db
+            .autocommit(
+                |dbtx, _| {
+                    Box::pin(async move {
+                        let key = UserBalanceKey(user_id.0);
+                        let balance = dbtx.get_value(&key).await.unwrap_or(Amount::ZERO) + amount;
+                        dbtx.insert_entry(&key, &balance).await;
+                        Ok::<_, anyhow::Error>(balance)
+                    })
+                },
+                Some(MAX_DB_ATTEMPTS),
+            )
+            .await
+            .map_err(autocommit_error)?;
+
+        info!(
+            "Credited {} to user {}, balance {}",
+            amount, user_id, balance
+        );
+        Ok(balance)
+    }
+
+    /// Credits `amount` back to the user for a failed operation and forgets
+    /// the operation as pending, in a single transaction. An operation is
+    /// refunded at most once however often this is called for it, so the
+    /// command and the resumed operation can't both refund it. Returns whether
+    /// it was refunded now.
+    pub async fn refund_operation(
+        &self,
+        operation_id: OperationId,
+        user_id: UserId,
+        amount: Amount,
+    ) -> Result<bool> {
+        let refunded = self
+            .db
+            .autocommit(
+                |dbtx, _| {
+                    Box::pin(async move {
+                        dbtx.remove_entry(&PendingOperationKey(operation_id)).await;
+                        let refunded_key = RefundedOperationKey(operation_id);
+                        if dbtx.get_value(&refunded_key).await.is_some() {
+                            return Ok::<_, anyhow::Error>(false);
+                        }
+                        dbtx.insert_entry(&refunded_key, &amount).await;
+                        let key = UserBalanceKey(user_id.0);
+                        let balance = dbtx.get_value(&key).await.unwrap_or(Amount::ZERO) + amount;
+                        dbtx.insert_entry(&key, &balance).await;
+                        Ok(true)
+                    })
+                },
+                Some(MAX_DB_ATTEMPTS),
+            )
+            .await
+            .map_err(autocommit_error)?;
+
+        if refunded {
+            info!(
+                "Refunded {} to user {} for operation {}",
+                amount, user_id, operation_id
+            );
+        }
+        Ok(refunded)
+    }
+
+    /// Remembers to credit `amount` to the user once the federation claims the
+    /// payment of the invoice created by `operation_id`
+    pub async fn expect_claim(
+        &self,
+        operation_id: OperationId,
+        user_id: UserId,
+        amount: Amount,
+    ) -> Result<()> {
+        let claim = PendingClaim {
+            user_id: user_id.0,
+            amount,
+        };
+        self.db
+            .autocommit(
+                |dbtx, _| {
+                    let claim = claim.clone();
+                    Box::pin(async move {
+                        dbtx.insert_entry(&PendingClaimKey(operation_id), &claim)
+                            .await;
+                        Ok::<_, anyhow::Error>(())
+                    })
+                },
+                Some(MAX_DB_ATTEMPTS),
+            )
+            .await
+            .map_err(autocommit_error)
+    }
+
+    /// Credits a claimed deposit to its user and forgets it in a single
+    /// transaction, so it is credited once even if it is seen claimed again
+    /// after a restart. Returns the claim if it was credited now.
+    pub async fn settle_claim(&self, operation_id: OperationId) -> Result<Option<PendingClaim>> {
+        let claim = self
+            .db
+            .autocommit(
+                |dbtx, _| {
+                    Box::pin(async move {
+                        let claim = match dbtx.remove_entry(&PendingClaimKey(operation_id)).await {
+                            Some(claim) => claim,
+                            None => return Ok::<_, anyhow::Error>(None),
+                        };
+                        let key = UserBalanceKey(claim.user_id);
+                        let balance =
+                            dbtx.get_value(&key).await.unwrap_or(Amount::ZERO) + claim.amount;
+                        dbtx.insert_entry(&key, &balance).await;
+                        Ok(Some(claim))
+                    })
+                },
+                Some(MAX_DB_ATTEMPTS),
+            )
+            .await
+            .map_err(autocommit_error)?;
+
+        if let Some(claim) = &claim {
+            info!(
+                "Credited {} to user {} for deposit {}",
+                claim.amount, claim.user_id, operation_id
+            );
+        }
+        Ok(claim)
+    }
+
+    /// Forgets a deposit whose invoice won't be paid anymore
+    pub async fn cancel_claim(&self, operation_id: OperationId) -> Result<()> {
+        self.db
+            .autocommit(
+                |dbtx, _| {
+                    Box::pin(async move {
+                        dbtx.remove_entry(&PendingClaimKey(operation_id)).await;
+                        Ok::<_, anyhow::Error>(())
+                    })
+                },
+                Some(MAX_DB_ATTEMPTS),
+            )
+            .await
+            .map_err(autocommit_error)
+    }
+
+    /// The deposits waiting for their invoice to be paid and claimed
+    pub async fn pending_claims(&self) -> Vec<OperationId> {
+        self.db
+            .begin_transaction_nc()
+            .await
+            .find_by_prefix(&PendingClaimKeyPrefix)
+            .await
+            .map(|(key, _)| key.0)
+            .collect::<Vec<_>>()
+            .await
+    }
+
+    /// Removes `amount` from the user's balance, failing without changes if
+    /// the balance is too low. Returns the new balance.
+    pub async fn debit(&self, user_id: UserId, amount: Amount) -> Result<Amount> {
+        let balance = self
+            .db
+            .autocommit(
+                |dbtx, _| {
+                    Box::pin(async move {
+                        let key = UserBalanceKey(user_id.0);
+                        let balance = dbtx.get_value(&key).await.unwrap_or(Amount::ZERO);
+                        if balance < amount {
+                            return Err(insufficient_balance(balance, amount));
+                        }
+                        let balance = balance - amount;
+                        dbtx.insert_entry(&key, &balance).await;
+                        Ok(balance)
+                    })
+                },
+                Some(MAX_DB_ATTEMPTS),
+            )
+            .await
+            .map_err(autocommit_error)?;
+
+        info!(
+            "Debited {} from user {}, balance {}",
+            amount, user_id, balance
+        );
+        Ok(balance)
+    }
+
+    /// Moves `amount` from one user's balance to another's in a single
+    /// transaction, failing without changes if the sender's balance is too low
+    pub async fn transfer(&self, from: UserId, to: UserId, amount: Amount) -> Result<()> {
+        if from == to {
+            return Err(Rejected("Cannot transfer to yourself".to_string()).into());
+        }
+
+        self.db
+            .autocommit(
+                |dbtx, _| {
+                    Box::pin(async move {
+                        let from_key = UserBalanceKey(from.0);
+                        let to_key = UserBalanceKey(to.0);
+                        let from_balance = dbtx.get_value(&from_key).await.unwrap_or(Amount::ZERO);
+                        if from_balance < amount {
+                            return Err(insufficient_balance(from_balance, amount));
+                        }
+                        let to_balance = dbtx.get_value(&to_key).await.unwrap_or(Amount::ZERO);
+                        dbtx.insert_entry(&from_key, &(from_balance - amount)).await;
+                        dbtx.insert_entry(&to_key, &(to_balance + amount)).await;
+                        Ok(())
+                    })
+                },
+                Some(MAX_DB_ATTEMPTS),
+            )
+            .await
+            .map_err(autocommit_error)?;
+
+        info!("Transferred {} from user {} to user {}", amount, from, to);
+        Ok(())
+    }
+}
+
+/// A request the ledger refuses, like spending more than the balance, as
+/// opposed to the ledger failing
+#[derive(Debug)]
+pub struct Rejected(pub String);
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+fn insufficient_balance(balance: Amount, required: Amount) -> anyhow::Error {
+    Rejected(format!(
+        "Insufficient balance: {} available, {} required",
+        balance, required
+    ))
+    .into()
+}
+
+/// Unwraps the error of a write done with `Database::autocommit`: the one the
+/// write itself returned, or why it couldn't be committed
+pub(crate) fn autocommit_error(e: AutocommitError<anyhow::Error>) -> anyhow::Error {
+    match e {
+        AutocommitError::ClosureError { error, .. } => error,
+        AutocommitError::CommitFailed {
+            attempts,
+            last_error,
+        } => anyhow!(
+            "Cannot commit to the ledger after {} attempts: {}",
+            attempts,
+            last_error
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fedimint_core::db::mem_impl::MemDatabase;
+
+    use super::*;
+
+    fn ledger() -> Ledger {
+        Ledger {
+            db: Database::new(MemDatabase::new(), Default::default()),
+        }
+    }
+
+    #[tokio::test]
+    async fn credits_deposits_and_refunds_once() {
+        let ledger = ledger();
+        let user_id = UserId(1);
+        let deposit = OperationId([1; 32]);
+        let payment = OperationId([2; 32]);
+
+        ledger
+            .expect_claim(deposit, user_id, Amount::from_sats(10))
+            .await
+            .unwrap();
+        assert!(ledger.settle_claim(deposit).await.unwrap().is_some());
+        assert!(ledger.settle_claim(deposit).await.unwrap().is_none());
+        assert!(ledger.pending_claims().await.is_empty());
+
+        let refund = Amount::from_sats(5);
+        assert!(ledger
+            .refund_operation(payment, user_id, refund)
+            .await
+            .unwrap());
+        assert!(!ledger
+            .refund_operation(payment, user_id, refund)
+            .await
+            .unwrap());
+
+        assert_eq!(ledger.balance(user_id).await, Amount::from_sats(15));
+    }
+}
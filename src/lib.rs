@@ -0,0 +1,20 @@
+//! The Discord bot's commands and the services they run against, shared by
+//! the `botimint` bot and the `botimint-cli` binary
+
+pub mod api;
+pub mod botimint;
+pub mod commands;
+pub mod components;
+pub mod config;
+pub mod federations;
+pub mod ledger;
+pub mod lightning;
+pub mod notifications;
+pub mod pending;
+pub mod permissions;
+pub mod state;
+#[cfg(test)]
+mod testing;
+pub mod utils;
+
+pub use crate::config::CONFIG;
@@ -0,0 +1,310 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::Result;
+use cln_rpc::{ClnRpc, Request, Response, RpcError};
+use tokio::sync::{watch, Mutex, Semaphore};
+use tracing::{info, warn};
+
+// Read-only calls are retried this many times when the connection breaks,
+// waiting twice as long before each attempt
+const MAX_RETRIES: u32 = 3;
+const FIRST_BACKOFF: Duration = Duration::from_millis(500);
+
+// Short calls share this many connections, waiting for one to be free when
+// they are all busy
+const POOL_SIZE: usize = 4;
+
+// How often nodes are checked when no command is talking to them
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Whether the client could last talk to lightningd
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    Connected { since: SystemTime },
+    Disconnected { since: SystemTime, error: String },
+}
+
+impl ConnectionState {
+    pub fn is_connected(&self) -> bool {
+        matches!(self, ConnectionState::Connected { .. })
+    }
+}
+
+/// Connections to a CLN node's RPC socket that survive lightningd restarting.
+/// A call that fails because the connection broke reconnects and, when the
+/// call is read-only and so safe to repeat, is retried with backoff.
+///
+/// `ClnRpc` answers one call at a time, so calls are spread over a pool of
+/// connections. Calls that can take minutes, like the `wait*` calls, payments
+/// and channel opens and closes, get a connection of their own instead of
+/// holding one of the pool's.
+#[derive(Clone)]
+pub struct ClnClient {
+    path: PathBuf,
+    idle: Arc<Mutex<Vec<ClnRpc>>>,
+    permits: Arc<Semaphore>,
+    state: Arc<watch::Sender<ConnectionState>>,
+}
+
+impl ClnClient {
+    /// Connects to the socket at `path`. If lightningd isn't there the client
+    /// starts out disconnected, and connects on the next call or health check.
+    pub async fn connect(path: &Path) -> Self {
+        let since = SystemTime::now();
+        let (idle, state) = match ClnRpc::new(path).await {
+            Ok(rpc) => (vec![rpc], ConnectionState::Connected { since }),
+            Err(e) => {
+                let e = connection_error(e);
+                warn!("Cannot connect to CLN at {:?}: {}", path, e.message);
+                let state = ConnectionState::Disconnected {
+                    since,
+                    error: e.message,
+                };
+                (Vec::new(), state)
+            }
+        };
+        let (state, _) = watch::channel(state);
+
+        Self {
+            path: path.to_path_buf(),
+            idle: Arc::new(Mutex::new(idle)),
+            permits: Arc::new(Semaphore::new(POOL_SIZE)),
+            state: Arc::new(state),
+        }
+    }
+
+    pub async fn call(&self, req: Request) -> Result<Response, RpcError> {
+        let retries = if is_read_only(&req) { MAX_RETRIES } else { 0 };
+        let mut backoff = FIRST_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            let e = match self.try_call(req.clone()).await {
+                Err(e) if is_connection_error(&e) => e,
+                res => return res,
+            };
+            if attempt >= retries {
+                return Err(e);
+            }
+            attempt += 1;
+            warn!(
+                "Retrying call to CLN at {:?} in {:?}: {}",
+                self.path, backoff, e.message
+            );
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+
+    async fn try_call(&self, req: Request) -> Result<Response, RpcError> {
+        let pooled = !is_blocking(&req);
+        let _permit = if pooled {
+            Some(self.permits.acquire().await.expect("never closed"))
+        } else {
+            None
+        };
+        let idle = if pooled {
+            self.idle.lock().await.pop()
+        } else {
+            None
+        };
+        let mut rpc = match idle {
+            Some(rpc) => rpc,
+            None => ClnRpc::new(&self.path).await.map_err(|e| {
+                let e = connection_error(e);
+                self.set_disconnected(&e);
+                e
+            })?,
+        };
+
+        let res = rpc.call(req).await;
+        match &res {
+            // The other idle connections were most likely broken along with
+            // this one, so the next calls open new ones
+            Err(e) if is_connection_error(e) => {
+                self.idle.lock().await.clear();
+                self.set_disconnected(e);
+            }
+            _ => {
+                self.set_connected();
+                if pooled {
+                    self.idle.lock().await.push(rpc);
+                }
+            }
+        }
+        res
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state.borrow().clone()
+    }
+
+    /// Receives the connection state whenever it changes
+    pub fn subscribe(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    /// Checks the node now and every so often, so the state is current and a
+    /// node that was down is connected to even when no command is run
+    pub fn spawn_health_check(&self) {
+        let client = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let req = cln_rpc::model::requests::GetinfoRequest {};
+                let _ = client.try_call(Request::Getinfo(req)).await;
+                tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+            }
+        });
+    }
+
+    fn set_connected(&self) {
+        self.state.send_if_modified(|state| {
+            if state.is_connected() {
+                return false;
+            }
+            info!("Reconnected to CLN at {:?}", self.path);
+            *state = ConnectionState::Connected {
+                since: SystemTime::now(),
+            };
+            true
+        });
+    }
+
+    fn set_disconnected(&self, e: &RpcError) {
+        self.state.send_if_modified(|state| {
+            if !state.is_connected() {
+                return false;
+            }
+            warn!(
+                "Lost the connection to CLN at {:?}: {}",
+                self.path, e.message
+            );
+            *state = ConnectionState::Disconnected {
+                since: SystemTime::now(),
+                error: e.message.clone(),
+            };
+            true
+        });
+    }
+}
+
+// lightningd's own errors always have a code, failures to reach it don't
+fn is_connection_error(e: &RpcError) -> bool {
+    e.code.is_none()
+}
+
+fn connection_error(e: anyhow::Error) -> RpcError {
+    RpcError {
+        code: None,
+        message: format!("Cannot connect to lightningd: {}", e),
+        data: None,
+    }
+}
+
+// Calls that wait for something to happen on the node, a peer or the network
+// before answering
+fn is_blocking(req: &Request) -> bool {
+    matches!(
+        req,
+        Request::WaitInvoice(_)
+            | Request::WaitAnyInvoice(_)
+            | Request::WaitSendPay(_)
+            | Request::Pay(_)
+            | Request::KeySend(_)
+            | Request::Close(_)
+            | Request::FundChannel(_)
+            | Request::Connect(_)
+            | Request::Ping(_)
+    )
+}
+
+// Calls that only read the node's state, which can be repeated safely
+fn is_read_only(req: &Request) -> bool {
+    matches!(
+        req,
+        Request::Getinfo(_)
+            | Request::ListPeers(_)
+            | Request::ListFunds(_)
+            | Request::ListChannels(_)
+            | Request::ListPeerChannels(_)
+            | Request::ListClosedChannels(_)
+            | Request::ListInvoices(_)
+            | Request::ListPays(_)
+            | Request::ListSendPays(_)
+            | Request::ListForwards(_)
+            | Request::ListHtlcs(_)
+            | Request::ListNodes(_)
+            | Request::ListDatastore(_)
+            | Request::ListTransactions(_)
+            | Request::Feerates(_)
+            | Request::Decode(_)
+            | Request::DecodePay(_)
+            | Request::GetRoute(_)
+            | Request::CheckMessage(_)
+            | Request::Ping(_)
+            | Request::WaitInvoice(_)
+            | Request::WaitAnyInvoice(_)
+            | Request::WaitSendPay(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use cln_rpc::model::requests::{GetinfoRequest, WaitanyinvoiceRequest};
+
+    use super::*;
+    use crate::testing::cln::{getinfo, MockCln};
+
+    #[tokio::test]
+    async fn reconnects_after_lightningd_restarts() {
+        let (cln, client) = MockCln::new().respond("getinfo", getinfo()).start().await;
+        let states = client.subscribe();
+        let req = || Request::Getinfo(GetinfoRequest {});
+        assert!(client.call(req()).await.is_ok());
+
+        // Drop the connection as a restarting lightningd would
+        cln.disconnect();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(client.call(req()).await.is_ok());
+        // It went offline and came back
+        assert!(states.has_changed().unwrap());
+        assert!(client.state().is_connected());
+        assert_eq!(cln.requests("getinfo").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn starts_disconnected_until_lightningd_is_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let client = ClnClient::connect(&dir.path().join("lightning-rpc")).await;
+
+        assert!(!client.state().is_connected());
+        let res = client.try_call(Request::Getinfo(GetinfoRequest {})).await;
+        assert!(matches!(res, Err(e) if is_connection_error(&e)));
+    }
+
+    #[tokio::test]
+    async fn waiting_leaves_the_pool_to_other_calls() {
+        let (cln, client) = MockCln::new()
+            .respond("getinfo", getinfo())
+            .hang("waitanyinvoice")
+            .start()
+            .await;
+        for _ in 0..POOL_SIZE + 1 {
+            let client = client.clone();
+            tokio::spawn(async move {
+                let req = WaitanyinvoiceRequest {
+                    lastpay_index: None,
+                    timeout: None,
+                };
+                client.call(Request::WaitAnyInvoice(req)).await
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let info = client.call(Request::Getinfo(GetinfoRequest {}));
+        let res = tokio::time::timeout(Duration::from_secs(5), info).await;
+        assert!(res.expect("getinfo waited on waitanyinvoice").is_ok());
+        assert_eq!(cln.requests("waitanyinvoice").len(), POOL_SIZE + 1);
+    }
+}
@@ -0,0 +1,314 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use cln_rpc::model::requests::{
+    CloseRequest, ConnectRequest, DecodepayRequest, FundchannelRequest, GetinfoRequest,
+    InvoiceRequest, KeysendRequest, ListfundsRequest, ListpeersRequest, NewaddrAddresstype,
+    NewaddrRequest, PayRequest,
+};
+use cln_rpc::primitives::{Amount, AmountOrAll, AmountOrAny, PublicKey};
+use cln_rpc::{Request, Response};
+use serde_json::{json, Value};
+
+use super::{
+    json_array, json_opt_str, json_opt_u64, json_str, json_u64, ClnClient, ClosedChannel,
+    CreatedInvoice, DecodedInvoice, Funds, FundsChannel, FundsOutput, LightningBackend, NodeInfo,
+    OpenedChannel, Payment, Peer,
+};
+
+// How long a forced close waits for the peer to agree before closing
+// unilaterally
+const FORCE_CLOSE_TIMEOUT_SECS: u32 = 1;
+
+/// Core Lightning, over the node's JSON-RPC unix socket
+#[derive(Clone)]
+pub struct ClnBackend {
+    client: ClnClient,
+}
+
+impl ClnBackend {
+    pub fn new(client: ClnClient) -> Self {
+        Self { client }
+    }
+
+    // Sends the request and returns the JSON of the response it expects
+    async fn call(&self, req: Request) -> Result<Value> {
+        let res = self.client.call(req).await.map_err(|e| anyhow!("{}", e))?;
+
+        match res {
+            Response::Getinfo(res) => Ok(json!(res)),
+            Response::Invoice(res) => Ok(json!(res)),
+            Response::Pay(res) => Ok(json!(res)),
+            Response::KeySend(res) => Ok(json!(res)),
+            Response::ListFunds(res) => Ok(json!(res)),
+            Response::ListPeers(res) => Ok(json!(res)),
+            Response::NewAddr(res) => Ok(json!(res)),
+            Response::Connect(res) => Ok(json!(res)),
+            Response::FundChannel(res) => Ok(json!(res)),
+            Response::Close(res) => Ok(json!(res)),
+            Response::DecodePay(res) => Ok(json!(res)),
+            res => bail!("Unexpected response from CLN: {:?}", res),
+        }
+    }
+
+    // CLN closes channels by peer id or short channel id, so look the channel
+    // point up among the node's channels
+    async fn find_channel(&self, channel_point: &str) -> Result<String> {
+        let res = self
+            .call(Request::ListFunds(ListfundsRequest { spent: Some(false) }))
+            .await?;
+        let channel = json_array(&res, "channels")
+            .iter()
+            .find(|channel| funding_outpoint(channel) == channel_point)
+            .with_context(|| format!("No channel with channel point {}", channel_point))?;
+
+        Ok(json_opt_str(channel, "short_channel_id")
+            .unwrap_or_else(|| json_str(channel, "peer_id")))
+    }
+}
+
+#[async_trait]
+impl LightningBackend for ClnBackend {
+    fn name(&self) -> &'static str {
+        "CLN"
+    }
+
+    async fn info(&self) -> Result<NodeInfo> {
+        let res = self.call(Request::Getinfo(GetinfoRequest {})).await?;
+
+        Ok(NodeInfo {
+            id: json_str(&res, "id"),
+            alias: json_str(&res, "alias"),
+            version: json_str(&res, "version"),
+            network: json_str(&res, "network"),
+            block_height: json_u64(&res, "blockheight"),
+            num_peers: json_u64(&res, "num_peers"),
+            num_active_channels: json_u64(&res, "num_active_channels"),
+        })
+    }
+
+    async fn invoice(
+        &self,
+        amount_msat: Option<u64>,
+        description: String,
+        expiry_secs: Option<u64>,
+    ) -> Result<CreatedInvoice> {
+        let amount_msat = match amount_msat {
+            Some(amount_msat) => AmountOrAny::Amount(Amount::from_msat(amount_msat)),
+            None => AmountOrAny::Any,
+        };
+        let req = InvoiceRequest {
+            amount_msat,
+            label: uuid::Uuid::new_v4().to_string(),
+            description,
+            expiry: expiry_secs,
+            fallbacks: None,
+            preimage: None,
+            cltv: None,
+            deschashonly: None,
+        };
+        let res = self.call(Request::Invoice(req)).await?;
+
+        Ok(CreatedInvoice {
+            bolt11: json_str(&res, "bolt11"),
+            payment_hash: json_str(&res, "payment_hash"),
+            expires_at: json_u64(&res, "expires_at"),
+        })
+    }
+
+    async fn pay(&self, bolt11: String, amount_msat: Option<u64>) -> Result<Payment> {
+        let req = PayRequest {
+            bolt11,
+            amount_msat: amount_msat.map(Amount::from_msat),
+            label: None,
+            riskfactor: None,
+            maxfeepercent: None,
+            retry_for: None,
+            maxdelay: None,
+            exemptfee: None,
+            localinvreqid: None,
+            exclude: None,
+            maxfee: None,
+            description: None,
+        };
+        let res = self.call(Request::Pay(req)).await?;
+
+        Ok(payment(&res))
+    }
+
+    async fn keysend(&self, destination: String, amount_msat: u64) -> Result<Payment> {
+        let destination = PublicKey::from_str(&destination).context("Invalid node id")?;
+        let req = KeysendRequest {
+            destination,
+            amount_msat: Amount::from_msat(amount_msat),
+            label: None,
+            maxfeepercent: None,
+            retry_for: None,
+            exemptfee: None,
+            maxdelay: None,
+            routehints: None,
+            extratlvs: None,
+        };
+        let res = self.call(Request::KeySend(req)).await?;
+
+        Ok(payment(&res))
+    }
+
+    async fn list_funds(&self) -> Result<Funds> {
+        let res = self
+            .call(Request::ListFunds(ListfundsRequest { spent: Some(false) }))
+            .await?;
+
+        let outputs = json_array(&res, "outputs")
+            .iter()
+            .map(|output| FundsOutput {
+                outpoint: format!(
+                    "{}:{}",
+                    json_str(output, "txid"),
+                    json_u64(output, "output")
+                ),
+                address: json_opt_str(output, "address"),
+                amount_msat: json_u64(output, "amount_msat"),
+                confirmed: json_str(output, "status") == "confirmed",
+            })
+            .collect();
+        let channels = json_array(&res, "channels")
+            .iter()
+            .map(|channel| FundsChannel {
+                peer_id: json_str(channel, "peer_id"),
+                channel_point: funding_outpoint(channel),
+                our_amount_msat: json_u64(channel, "our_amount_msat"),
+                amount_msat: json_u64(channel, "amount_msat"),
+                active: channel["connected"].as_bool().unwrap_or_default()
+                    && json_str(channel, "state") == "CHANNELD_NORMAL",
+            })
+            .collect();
+
+        Ok(Funds { outputs, channels })
+    }
+
+    async fn list_peers(&self) -> Result<Vec<Peer>> {
+        let req = ListpeersRequest {
+            id: None,
+            level: None,
+        };
+        let res = self.call(Request::ListPeers(req)).await?;
+
+        Ok(json_array(&res, "peers")
+            .iter()
+            .map(|peer| Peer {
+                id: json_str(peer, "id"),
+                connected: peer["connected"].as_bool().unwrap_or_default(),
+                addresses: json_array(peer, "netaddr")
+                    .iter()
+                    .filter_map(|addr| addr.as_str().map(str::to_string))
+                    .collect(),
+            })
+            .collect())
+    }
+
+    async fn new_address(&self) -> Result<String> {
+        let req = NewaddrRequest {
+            addresstype: Some(NewaddrAddresstype::BECH32),
+        };
+        let res = self.call(Request::NewAddr(req)).await?;
+
+        json_opt_str(&res, "bech32").context("CLN did not return a bech32 address")
+    }
+
+    async fn connect(&self, id: String, host: String, port: u16) -> Result<String> {
+        let req = ConnectRequest {
+            id,
+            host: Some(host),
+            port: Some(port),
+        };
+        let res = self.call(Request::Connect(req)).await?;
+
+        Ok(json_str(&res, "id"))
+    }
+
+    async fn fund_channel(
+        &self,
+        id: String,
+        amount_sat: u64,
+        push_msat: Option<u64>,
+    ) -> Result<OpenedChannel> {
+        let id = PublicKey::from_str(&id).context("Invalid node id")?;
+        let req = FundchannelRequest {
+            id,
+            amount: AmountOrAll::Amount(Amount::from_sat(amount_sat)),
+            feerate: None,
+            announce: None,
+            minconf: None,
+            push_msat: push_msat.map(Amount::from_msat),
+            close_to: None,
+            request_amt: None,
+            compact_lease: None,
+            utxos: None,
+            mindepth: None,
+            reserve: None,
+        };
+        let res = self.call(Request::FundChannel(req)).await?;
+
+        let txid = json_str(&res, "txid");
+        Ok(OpenedChannel {
+            channel_point: format!("{}:{}", txid, json_u64(&res, "outnum")),
+            txid,
+        })
+    }
+
+    async fn close_channel(&self, channel_point: String, force: bool) -> Result<ClosedChannel> {
+        let id = self.find_channel(&channel_point).await?;
+        let req = CloseRequest {
+            id,
+            unilateraltimeout: force.then_some(FORCE_CLOSE_TIMEOUT_SECS),
+            destination: None,
+            fee_negotiation_step: None,
+            wrong_funding: None,
+            force_lease_closed: None,
+            feerange: None,
+        };
+        let res = self.call(Request::Close(req)).await?;
+
+        Ok(ClosedChannel {
+            txid: json_opt_str(&res, "txid"),
+            kind: json_str(&res, "type"),
+        })
+    }
+
+    async fn decode_invoice(&self, bolt11: String) -> Result<DecodedInvoice> {
+        let req = DecodepayRequest {
+            bolt11,
+            description: None,
+        };
+        let res = self.call(Request::DecodePay(req)).await?;
+
+        Ok(DecodedInvoice {
+            payee: json_str(&res, "payee"),
+            payment_hash: json_str(&res, "payment_hash"),
+            amount_msat: json_opt_u64(&res, "amount_msat"),
+            description: json_opt_str(&res, "description"),
+            created_at: json_u64(&res, "created_at"),
+            expiry: json_u64(&res, "expiry"),
+        })
+    }
+}
+
+fn payment(res: &Value) -> Payment {
+    let amount_msat = json_u64(res, "amount_msat");
+    Payment {
+        payment_hash: json_str(res, "payment_hash"),
+        payment_preimage: json_str(res, "payment_preimage"),
+        amount_msat,
+        fee_msat: json_u64(res, "amount_sent_msat").saturating_sub(amount_msat),
+    }
+}
+
+fn funding_outpoint(channel: &Value) -> String {
+    format!(
+        "{}:{}",
+        json_str(channel, "funding_txid"),
+        json_u64(channel, "funding_output")
+    )
+}
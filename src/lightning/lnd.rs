@@ -0,0 +1,317 @@
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use bitcoin::hashes::{sha256, Hash};
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::{Method, RequestBuilder};
+use serde_json::{json, Value};
+
+use super::{
+    json_array, json_opt_str, json_opt_u64, json_str, json_u64, ClosedChannel, CreatedInvoice,
+    DecodedInvoice, Funds, FundsChannel, FundsOutput, LightningBackend, NodeInfo, OpenedChannel,
+    Payment, Peer,
+};
+
+// TLV record LND reads a keysend payment's preimage from
+const KEYSEND_RECORD: &str = "5482373484";
+
+// LND's expiry for invoices created without one
+const DEFAULT_INVOICE_EXPIRY_SECS: u64 = 86400;
+
+/// LND, over its REST API authenticated with an admin macaroon
+#[derive(Clone)]
+pub struct LndBackend {
+    client: reqwest::Client,
+    rest_url: String,
+}
+
+impl LndBackend {
+    pub fn new(rest_url: &str, macaroon_path: &Path, tls_cert_path: Option<&Path>) -> Result<Self> {
+        let macaroon = std::fs::read(macaroon_path)
+            .with_context(|| format!("Cannot read macaroon at {:?}", macaroon_path))?;
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Grpc-Metadata-macaroon",
+            HeaderValue::from_str(&hex::encode(macaroon))?,
+        );
+
+        let mut builder = reqwest::Client::builder().default_headers(headers);
+        // LND serves its API with a self-signed certificate
+        if let Some(tls_cert_path) = tls_cert_path {
+            let cert = std::fs::read(tls_cert_path)
+                .with_context(|| format!("Cannot read TLS certificate at {:?}", tls_cert_path))?;
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&cert)?);
+        }
+
+        Ok(Self {
+            client: builder.build()?,
+            rest_url: rest_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.rest_url, path))
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Value> {
+        let res = request.send().await?;
+        let status = res.status();
+        let body: Value = res.json().await?;
+        if !status.is_success() {
+            bail!("LND returned {}: {}", status, json_str(&body, "message"));
+        }
+
+        Ok(body)
+    }
+
+    async fn get(&self, path: &str) -> Result<Value> {
+        self.send(self.request(Method::GET, path)).await
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<Value> {
+        self.send(self.request(Method::POST, path).json(&body))
+            .await
+    }
+
+    // Pays with the synchronous send endpoint, which reports routing failures
+    // in the response body rather than its status
+    async fn send_payment(&self, body: Value) -> Result<Payment> {
+        let res = self.post("/v1/channels/transactions", body).await?;
+        if let Some(error) = json_opt_str(&res, "payment_error") {
+            bail!("Payment failed: {}", error);
+        }
+
+        let route = &res["payment_route"];
+        let fee_msat = json_u64(route, "total_fees_msat");
+        Ok(Payment {
+            payment_hash: base64_to_hex(&json_str(&res, "payment_hash"))?,
+            payment_preimage: base64_to_hex(&json_str(&res, "payment_preimage"))?,
+            amount_msat: json_u64(route, "total_amt_msat").saturating_sub(fee_msat),
+            fee_msat,
+        })
+    }
+}
+
+#[async_trait]
+impl LightningBackend for LndBackend {
+    fn name(&self) -> &'static str {
+        "LND"
+    }
+
+    async fn info(&self) -> Result<NodeInfo> {
+        let res = self.get("/v1/getinfo").await?;
+        let network = json_array(&res, "chains")
+            .first()
+            .map(|chain| json_str(chain, "network"))
+            .unwrap_or_default();
+
+        Ok(NodeInfo {
+            id: json_str(&res, "identity_pubkey"),
+            alias: json_str(&res, "alias"),
+            version: json_str(&res, "version"),
+            network,
+            block_height: json_u64(&res, "block_height"),
+            num_peers: json_u64(&res, "num_peers"),
+            num_active_channels: json_u64(&res, "num_active_channels"),
+        })
+    }
+
+    async fn invoice(
+        &self,
+        amount_msat: Option<u64>,
+        description: String,
+        expiry_secs: Option<u64>,
+    ) -> Result<CreatedInvoice> {
+        let expiry_secs = expiry_secs.unwrap_or(DEFAULT_INVOICE_EXPIRY_SECS);
+        let body = json!({
+            "value_msat": amount_msat.unwrap_or_default().to_string(),
+            "memo": description,
+            "expiry": expiry_secs.to_string(),
+        });
+        let res = self.post("/v1/invoices", body).await?;
+
+        Ok(CreatedInvoice {
+            bolt11: json_str(&res, "payment_request"),
+            payment_hash: base64_to_hex(&json_str(&res, "r_hash"))?,
+            expires_at: now_secs() + expiry_secs,
+        })
+    }
+
+    async fn pay(&self, bolt11: String, amount_msat: Option<u64>) -> Result<Payment> {
+        let mut body = json!({ "payment_request": bolt11 });
+        if let Some(amount_msat) = amount_msat {
+            body["amt_msat"] = json!(amount_msat.to_string());
+        }
+
+        self.send_payment(body).await
+    }
+
+    async fn keysend(&self, destination: String, amount_msat: u64) -> Result<Payment> {
+        let destination = hex::decode(&destination).context("Invalid node id")?;
+        let preimage: [u8; 32] = rand::random();
+        let payment_hash = sha256::Hash::hash(&preimage);
+        let body = json!({
+            "dest": BASE64.encode(destination),
+            "amt_msat": amount_msat.to_string(),
+            "payment_hash": BASE64.encode(payment_hash.into_inner()),
+            "dest_custom_records": { KEYSEND_RECORD: BASE64.encode(preimage) },
+        });
+
+        self.send_payment(body).await
+    }
+
+    async fn list_funds(&self) -> Result<Funds> {
+        let utxos = self
+            .get("/v1/utxos?min_confs=0&max_confs=2147483647")
+            .await?;
+        let channels = self.get("/v1/channels").await?;
+
+        let outputs = json_array(&utxos, "utxos")
+            .iter()
+            .map(|utxo| FundsOutput {
+                outpoint: format!(
+                    "{}:{}",
+                    json_str(&utxo["outpoint"], "txid_str"),
+                    json_u64(&utxo["outpoint"], "output_index")
+                ),
+                address: json_opt_str(utxo, "address"),
+                amount_msat: json_u64(utxo, "amount_sat") * 1000,
+                confirmed: json_u64(utxo, "confirmations") > 0,
+            })
+            .collect();
+        let channels = json_array(&channels, "channels")
+            .iter()
+            .map(|channel| FundsChannel {
+                peer_id: json_str(channel, "remote_pubkey"),
+                channel_point: json_str(channel, "channel_point"),
+                our_amount_msat: json_u64(channel, "local_balance") * 1000,
+                amount_msat: json_u64(channel, "capacity") * 1000,
+                active: channel["active"].as_bool().unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(Funds { outputs, channels })
+    }
+
+    async fn list_peers(&self) -> Result<Vec<Peer>> {
+        let res = self.get("/v1/peers").await?;
+
+        // LND only lists connected peers
+        Ok(json_array(&res, "peers")
+            .iter()
+            .map(|peer| Peer {
+                id: json_str(peer, "pub_key"),
+                connected: true,
+                addresses: json_opt_str(peer, "address").into_iter().collect(),
+            })
+            .collect())
+    }
+
+    async fn new_address(&self) -> Result<String> {
+        let res = self.get("/v1/newaddress?type=WITNESS_PUBKEY_HASH").await?;
+
+        json_opt_str(&res, "address").context("LND did not return an address")
+    }
+
+    async fn connect(&self, id: String, host: String, port: u16) -> Result<String> {
+        let body = json!({
+            "addr": { "pubkey": id, "host": format!("{}:{}", host, port) },
+            "perm": false,
+        });
+        self.post("/v1/peers", body).await?;
+
+        Ok(id)
+    }
+
+    async fn fund_channel(
+        &self,
+        id: String,
+        amount_sat: u64,
+        push_msat: Option<u64>,
+    ) -> Result<OpenedChannel> {
+        let node_pubkey = hex::decode(&id).context("Invalid node id")?;
+        let body = json!({
+            "node_pubkey": BASE64.encode(node_pubkey),
+            "local_funding_amount": amount_sat.to_string(),
+            "push_sat": (push_msat.unwrap_or_default() / 1000).to_string(),
+        });
+        let res = self.post("/v1/channels", body).await?;
+
+        let txid = txid_from_base64(&json_str(&res, "funding_txid_bytes"))?;
+        Ok(OpenedChannel {
+            channel_point: format!("{}:{}", txid, json_u64(&res, "output_index")),
+            txid,
+        })
+    }
+
+    async fn close_channel(&self, channel_point: String, force: bool) -> Result<ClosedChannel> {
+        let (txid, output_index) = channel_point
+            .split_once(':')
+            .context("Channel point must be <txid>:<vout>")?;
+        let path = format!("/v1/channels/{}/{}?force={}", txid, output_index, force);
+        let mut res = self.request(Method::DELETE, &path).send().await?;
+        if !res.status().is_success() {
+            let body: Value = res.json().await?;
+            bail!("LND returned an error: {}", json_str(&body, "message"));
+        }
+
+        // Closing streams updates until the close confirms, the first one
+        // carries the closing transaction
+        let mut line = Vec::new();
+        while let Some(chunk) = res.chunk().await? {
+            line.extend_from_slice(&chunk);
+            if let Some(end) = line.iter().position(|b| *b == b'\n') {
+                line.truncate(end);
+                break;
+            }
+        }
+        let update: Value = serde_json::from_slice(&line)?;
+        if let Some(error) = json_opt_str(&update["error"], "message") {
+            bail!("Cannot close channel: {}", error);
+        }
+
+        let pending = &update["result"]["close_pending"];
+        Ok(ClosedChannel {
+            txid: json_opt_str(pending, "txid")
+                .map(|txid| txid_from_base64(&txid))
+                .transpose()?,
+            kind: if force { "unilateral" } else { "mutual" }.to_string(),
+        })
+    }
+
+    async fn decode_invoice(&self, bolt11: String) -> Result<DecodedInvoice> {
+        let res = self.get(&format!("/v1/payreq/{}", bolt11)).await?;
+
+        Ok(DecodedInvoice {
+            payee: json_str(&res, "destination"),
+            payment_hash: json_str(&res, "payment_hash"),
+            amount_msat: json_opt_u64(&res, "num_msat").filter(|amount| *amount > 0),
+            description: json_opt_str(&res, "description"),
+            created_at: json_u64(&res, "timestamp"),
+            expiry: json_u64(&res, "expiry"),
+        })
+    }
+}
+
+fn base64_to_hex(value: &str) -> Result<String> {
+    Ok(hex::encode(BASE64.decode(value)?))
+}
+
+// LND returns txids as raw bytes, which are displayed in reverse order
+fn txid_from_base64(value: &str) -> Result<String> {
+    let mut bytes = BASE64.decode(value)?;
+    bytes.reverse();
+    Ok(hex::encode(bytes))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
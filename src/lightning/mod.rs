@@ -0,0 +1,214 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::info;
+
+pub use self::client::{ClnClient, ConnectionState};
+pub use self::cln::ClnBackend;
+pub use self::lnd::LndBackend;
+pub use self::nodes::{ClnNode, ClnNodeConfig, ClnNodes};
+
+pub mod client;
+pub mod cln;
+pub mod lnd;
+pub mod nodes;
+
+pub async fn new_cln(path: &PathBuf) -> ClnClient {
+    ClnClient::connect(path).await
+}
+
+/// The Lightning node the bot drives, picked with `LIGHTNING_BACKEND`
+#[derive(Debug, Clone)]
+pub enum LightningConfig {
+    /// The default CLN node
+    Cln,
+    Lnd {
+        rest_url: String,
+        macaroon_path: PathBuf,
+        tls_cert_path: Option<PathBuf>,
+    },
+}
+
+/// Builds the backend of the `ln_*` commands, on the default CLN node or LND
+pub fn backend(
+    config: &LightningConfig,
+    cln_nodes: &ClnNodes,
+) -> Result<Arc<dyn LightningBackend>> {
+    match config {
+        LightningConfig::Cln => {
+            let node = cln_nodes
+                .default_node()
+                .context("No CLN node is configured")?;
+            info!("Using CLN node {} for the ln_* commands", node.name);
+            Ok(Arc::new(ClnBackend::new(node.client.clone())))
+        }
+        LightningConfig::Lnd {
+            rest_url,
+            macaroon_path,
+            tls_cert_path,
+        } => {
+            let lnd = LndBackend::new(rest_url, macaroon_path, tls_cert_path.as_deref())?;
+            info!("Using the LND REST API at {}", rest_url);
+            Ok(Arc::new(lnd))
+        }
+    }
+}
+
+/// The operations the `ln_*` commands need from a Lightning node, implemented
+/// for each node implementation the bot can talk to
+#[async_trait]
+pub trait LightningBackend: Send + Sync {
+    /// The node implementation's name, shown in command responses
+    fn name(&self) -> &'static str;
+
+    async fn info(&self) -> Result<NodeInfo>;
+
+    /// Creates an invoice, for any amount when `amount_msat` is `None`
+    async fn invoice(
+        &self,
+        amount_msat: Option<u64>,
+        description: String,
+        expiry_secs: Option<u64>,
+    ) -> Result<CreatedInvoice>;
+
+    /// Pays an invoice, `amount_msat` is required for invoices without one
+    async fn pay(&self, bolt11: String, amount_msat: Option<u64>) -> Result<Payment>;
+
+    async fn keysend(&self, destination: String, amount_msat: u64) -> Result<Payment>;
+
+    async fn list_funds(&self) -> Result<Funds>;
+
+    async fn list_peers(&self) -> Result<Vec<Peer>>;
+
+    /// Returns a new segwit address from the node's on-chain wallet
+    async fn new_address(&self) -> Result<String>;
+
+    /// Connects to a peer, returning its node id
+    async fn connect(&self, id: String, host: String, port: u16) -> Result<String>;
+
+    async fn fund_channel(
+        &self,
+        id: String,
+        amount_sat: u64,
+        push_msat: Option<u64>,
+    ) -> Result<OpenedChannel>;
+
+    /// Closes the channel with the given channel point (`<txid>:<vout>`),
+    /// unilaterally when `force` is set
+    async fn close_channel(&self, channel_point: String, force: bool) -> Result<ClosedChannel>;
+
+    async fn decode_invoice(&self, bolt11: String) -> Result<DecodedInvoice>;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeInfo {
+    pub id: String,
+    pub alias: String,
+    pub version: String,
+    pub network: String,
+    pub block_height: u64,
+    pub num_peers: u64,
+    pub num_active_channels: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedInvoice {
+    pub bolt11: String,
+    pub payment_hash: String,
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Payment {
+    pub payment_hash: String,
+    pub payment_preimage: String,
+    pub amount_msat: u64,
+    pub fee_msat: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Funds {
+    pub outputs: Vec<FundsOutput>,
+    pub channels: Vec<FundsChannel>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FundsOutput {
+    pub outpoint: String,
+    pub address: Option<String>,
+    pub amount_msat: u64,
+    pub confirmed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FundsChannel {
+    pub peer_id: String,
+    pub channel_point: String,
+    pub our_amount_msat: u64,
+    pub amount_msat: u64,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Peer {
+    pub id: String,
+    pub connected: bool,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenedChannel {
+    pub txid: String,
+    pub channel_point: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ClosedChannel {
+    pub txid: Option<String>,
+    pub kind: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DecodedInvoice {
+    pub payee: String,
+    pub payment_hash: String,
+    pub amount_msat: Option<u64>,
+    pub description: Option<String>,
+    pub created_at: u64,
+    pub expiry: u64,
+}
+
+// Both nodes answer with JSON, but LND encodes 64 bit integers as strings, so
+// fields are read leniently from either representation
+
+fn json_str(value: &Value, key: &str) -> String {
+    match &value[key] {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn json_opt_str(value: &Value, key: &str) -> Option<String> {
+    Some(json_str(value, key)).filter(|s| !s.is_empty())
+}
+
+fn json_u64(value: &Value, key: &str) -> u64 {
+    json_opt_u64(value, key).unwrap_or_default()
+}
+
+fn json_opt_u64(value: &Value, key: &str) -> Option<u64> {
+    match &value[key] {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn json_array<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value[key].as_array().map(Vec::as_slice).unwrap_or_default()
+}
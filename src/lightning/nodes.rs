@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use tracing::{info, warn};
+
+use super::{new_cln, ClnClient};
+
+/// A CLN node from `CLN_NODES`, or the one at `CLN_RPC_PATH`
+#[derive(Debug, Clone)]
+pub struct ClnNodeConfig {
+    pub name: String,
+    pub rpc_path: PathBuf,
+}
+
+#[derive(Clone)]
+pub struct ClnNode {
+    pub name: String,
+    pub client: ClnClient,
+}
+
+/// The CLN nodes the `cln_*` commands can drive, in configuration order. The
+/// first one is the default for commands that don't pick a node.
+#[derive(Clone, Default)]
+pub struct ClnNodes {
+    nodes: Vec<ClnNode>,
+}
+
+impl ClnNodes {
+    pub fn new(nodes: Vec<ClnNode>) -> Self {
+        Self { nodes }
+    }
+
+    /// Connects to every node. Nodes that are down don't keep the others from
+    /// being used, and are connected to once they are up.
+    pub async fn connect(configs: &[ClnNodeConfig]) -> Self {
+        let mut nodes = Vec::new();
+        for config in configs {
+            let client = new_cln(&config.rpc_path).await;
+            if client.state().is_connected() {
+                info!(
+                    "Connected to CLN node {} at {:?}",
+                    config.name, config.rpc_path
+                );
+            } else {
+                warn!(
+                    "CLN node {} at {:?} is down, connecting once it's up",
+                    config.name, config.rpc_path
+                );
+            }
+            nodes.push(ClnNode {
+                name: config.name.clone(),
+                client,
+            });
+        }
+
+        Self::new(nodes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn list(&self) -> &[ClnNode] {
+        &self.nodes
+    }
+
+    pub fn default_node(&self) -> Option<&ClnNode> {
+        self.nodes.first()
+    }
+
+    /// Finds a node by name, or returns the default node when none is selected
+    pub fn select(&self, name: Option<&str>) -> Result<&ClnNode> {
+        let name = match name.map(str::trim) {
+            Some(name) if !name.is_empty() => name,
+            _ => return self.default_node().context("No CLN node is configured"),
+        };
+
+        match self
+            .nodes
+            .iter()
+            .find(|node| node.name.eq_ignore_ascii_case(name))
+        {
+            Some(node) => Ok(node),
+            None => bail!(
+                "No CLN node named {}, the nodes are: {}",
+                name,
+                self.names().join(", ")
+            ),
+        }
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.nodes.iter().map(|node| node.name.as_str()).collect()
+    }
+}
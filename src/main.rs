@@ -1,19 +1,17 @@
-use botimint::Botimint;
-// use serenity::model::prelude::GuildId;
+use std::sync::Arc;
+
+use botimint::api::{self, ApiState};
+use botimint::botimint::Botimint;
+use botimint::commands::fed::ln::invoice;
+use botimint::federations::Federations;
+use botimint::ledger::Ledger;
+use botimint::lightning::{self, ClnNodes};
+use botimint::notifications;
+use botimint::pending;
+use botimint::CONFIG;
 use serenity::prelude::GatewayIntents;
 use serenity::Client;
 
-use crate::config::CONFIG;
-use crate::lightning::new_cln;
-use crate::state::load_fedimint_client;
-
-mod botimint;
-mod commands;
-mod config;
-mod lightning;
-mod state;
-mod utils;
-
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let subscriber = tracing_subscriber::fmt()
@@ -22,32 +20,96 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 
-    let cln_client = new_cln(&CONFIG.cln_rpc_path).await?;
-    tracing::info!("Connected to C-Lightning RPC at {:?}", &CONFIG.cln_rpc_path);
+    // The cln_* commands need the CLN clients themselves, the ln_* commands
+    // work with any backend
+    let cln_nodes = ClnNodes::connect(&CONFIG.cln_nodes).await;
+    let lightning = lightning::backend(&CONFIG.lightning, &cln_nodes)?;
+    for node in cln_nodes.list() {
+        node.client.spawn_health_check();
+    }
 
     // let reqwest_client = reqwest::Client::new();
     // tracing::info!("Created new Reqwest HTTP client");
 
-    let fm_client = load_fedimint_client().await?;
-    tracing::info!("Connected to Fedimint: {:?}", fm_client.federation_id());
+    let ledger = Ledger::open(&CONFIG.ledger_db_path)?;
+    tracing::info!("Opened user ledger at {:?}", &CONFIG.ledger_db_path);
+
+    let federations = Federations::load(ledger).await?;
+    for federation in federations.list() {
+        tracing::info!("Connected to Fedimint: {}", federation.label());
+    }
 
     // Set gateway intents, which decides what events the bot will be notified about
     let intents = GatewayIntents::GUILD_MESSAGES
         | GatewayIntents::DIRECT_MESSAGES
-        | GatewayIntents::MESSAGE_CONTENT;
+        | GatewayIntents::MESSAGE_CONTENT
+        | GatewayIntents::GUILD_MESSAGE_REACTIONS;
 
     // Create a new instance of the Botimint Client, logging in as a bot. This will
     // automatically prepend your bot token with "Bot ", which is a requirement
     // by Discord for bot users.
     let mut botimint = Client::builder(&CONFIG.discord_client_token, intents)
         .event_handler(Botimint::new(
-            cln_client, fm_client,
-            // GuildId(CONFIG.guild_id.parse().unwrap()),
+            cln_nodes.clone(),
+            lightning.clone(),
+            federations.clone(),
         ))
         .await
         .expect("Err creating client");
     tracing::info!("Created new Botimint client");
 
+    if let Some(api) = &CONFIG.api {
+        let state = ApiState {
+            cln_nodes: cln_nodes.clone(),
+            lightning,
+            federations: federations.clone(),
+            token: Arc::new(api.token.clone()),
+            notify_token: api.notify_token.clone().map(Arc::new),
+            user_id: api.user_id,
+            http: botimint.cache_and_http.http.clone(),
+            alert_channels: Arc::new(CONFIG.cln_alert_channels.clone()),
+        };
+        let bind_addr = api.bind_addr;
+        tokio::spawn(async move {
+            if let Err(e) = api::serve(bind_addr, state).await {
+                tracing::error!("API server failed: {:?}", e);
+            }
+        });
+    }
+
+    notifications::invoices::spawn(
+        botimint.cache_and_http.http.clone(),
+        &CONFIG.cln_nodes,
+        CONFIG.invoice_channel_id,
+    );
+    if let Some(channel_id) = CONFIG.admin_channel_id {
+        notifications::node_status::spawn(
+            botimint.cache_and_http.http.clone(),
+            &cln_nodes,
+            channel_id,
+        );
+    }
+    pending::resume(botimint.cache_and_http.http.clone(), federations.clone());
+    invoice::resume_claims(&federations);
+    if let Some(channel_id) = CONFIG.report.channel_id {
+        notifications::report::spawn(
+            botimint.cache_and_http.http.clone(),
+            cln_nodes.clone(),
+            federations.clone(),
+            channel_id,
+            CONFIG.report.time,
+            CONFIG.report.sections.clone(),
+        );
+    }
+    if let Some(channel_id) = CONFIG.operation_channel_id {
+        notifications::operations::spawn(
+            botimint.cache_and_http.http.clone(),
+            federations,
+            channel_id,
+            CONFIG.operation_kinds.clone(),
+        );
+    }
+
     if let Err(why) = botimint.start().await {
         tracing::error!("Client error: {:?}", why);
     }
@@ -0,0 +1,247 @@
+//! Alerts for what happens on the CLN nodes: peers coming and going, channels
+//! opening and changing state, forwards, payments and coin movements.
+//!
+//! CLN only tells plugins about these, so the `botimint-cln-plugin` binary,
+//! loaded into each node, forwards them to the API's `POST /cln/notifications`
+//! and they are posted to the channels `CLN_ALERT_CHANNELS` routes them to.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+use serenity::builder::CreateEmbed;
+use serenity::http::Http;
+use serenity::model::id::ChannelId;
+use serenity::utils::Colour;
+use tracing::{error, info};
+
+use crate::utils::display_value;
+
+/// The notifications the plugin subscribes to
+pub const TOPICS: [&str; 8] = [
+    "channel_opened",
+    "channel_state_changed",
+    "connect",
+    "disconnect",
+    "forward_event",
+    "sendpay_success",
+    "sendpay_failure",
+    "coin_movement",
+];
+
+/// The channel alerts about `topic` go to: its own if it has one, otherwise
+/// the `*` one
+pub fn channel_for(channels: &HashMap<String, ChannelId>, topic: &str) -> Option<ChannelId> {
+    channels.get(topic).or_else(|| channels.get("*")).copied()
+}
+
+/// Posts the `topic` notification CLN node `node` sent, if alerts about it are
+/// routed anywhere. Returns whether it was posted.
+pub async fn post(
+    http: &Http,
+    channels: &HashMap<String, ChannelId>,
+    node: &str,
+    topic: &str,
+    payload: &Value,
+) -> bool {
+    let channel_id = match channel_for(channels, topic) {
+        Some(channel_id) => channel_id,
+        None => return false,
+    };
+    info!("Posting {} alert from CLN node {}", topic, node);
+    let embed = embed(node, topic, payload);
+    match channel_id
+        .send_message(http, |message| message.set_embed(embed))
+        .await
+    {
+        Ok(_) => true,
+        Err(e) => {
+            error!("Cannot post {} alert: {:?}", topic, e);
+            false
+        }
+    }
+}
+
+pub fn embed(node: &str, topic: &str, payload: &Value) -> CreateEmbed {
+    // Most notifications wrap their fields in an object named after them
+    let event = payload.get(topic).unwrap_or(payload);
+
+    let (title, colour, fields): (String, Colour, Vec<(&str, &str)>) = match topic {
+        "channel_opened" => (
+            "Channel opened".to_string(),
+            Colour::DARK_GREEN,
+            vec![
+                ("Peer", "/id"),
+                ("Funding", "/funding_msat"),
+                ("Funding transaction", "/funding_txid"),
+            ],
+        ),
+        "channel_state_changed" => {
+            let (title, colour) = if is_force_close(event) {
+                ("Channel force-closed", Colour::RED)
+            } else {
+                ("Channel state changed", Colour::BLUE)
+            };
+            (
+                title.to_string(),
+                colour,
+                vec![
+                    ("Channel", "/short_channel_id"),
+                    ("Channel id", "/channel_id"),
+                    ("Peer", "/peer_id"),
+                    ("From", "/old_state"),
+                    ("To", "/new_state"),
+                    ("Cause", "/cause"),
+                    ("Message", "/message"),
+                ],
+            )
+        }
+        "connect" => (
+            "Peer connected".to_string(),
+            Colour::DARK_GREEN,
+            vec![
+                ("Peer", "/id"),
+                ("Direction", "/direction"),
+                ("Address", "/address/address"),
+            ],
+        ),
+        "disconnect" => (
+            "Peer disconnected".to_string(),
+            Colour::ORANGE,
+            vec![("Peer", "/id")],
+        ),
+        "forward_event" => (
+            format!("Forward {}", display_value(&event["status"])),
+            match event["status"].as_str() {
+                Some("settled") => Colour::DARK_GREEN,
+                Some("failed") | Some("local_failed") => Colour::RED,
+                _ => Colour::BLUE,
+            },
+            vec![
+                ("In", "/in_channel"),
+                ("Out", "/out_channel"),
+                ("Received", "/in_msat"),
+                ("Sent", "/out_msat"),
+                ("Fee", "/fee_msat"),
+                ("Failure", "/failreason"),
+            ],
+        ),
+        "sendpay_success" => (
+            "Payment sent".to_string(),
+            Colour::DARK_GREEN,
+            vec![
+                ("Payment hash", "/payment_hash"),
+                ("Destination", "/destination"),
+                ("Amount", "/amount_msat"),
+                ("Sent", "/amount_sent_msat"),
+            ],
+        ),
+        "sendpay_failure" => (
+            "Payment failed".to_string(),
+            Colour::RED,
+            vec![
+                ("Payment hash", "/data/payment_hash"),
+                ("Destination", "/data/destination"),
+                ("Amount", "/data/amount_msat"),
+                ("Error", "/message"),
+            ],
+        ),
+        "coin_movement" => (
+            "Coin movement".to_string(),
+            Colour::BLUE,
+            vec![
+                ("Account", "/account_id"),
+                ("Type", "/type"),
+                ("Credit", "/credit_msat"),
+                ("Debit", "/debit_msat"),
+                ("Tags", "/tags"),
+            ],
+        ),
+        _ => (topic.to_string(), Colour::default(), Vec::new()),
+    };
+
+    let mut embed = CreateEmbed::default();
+    embed.title(title).colour(colour).field("Node", node, true);
+    for (name, pointer) in fields {
+        if let Some(value) = event.pointer(pointer).filter(|value| !value.is_null()) {
+            embed.field(name, display_value(value), true);
+        }
+    }
+    embed
+}
+
+// Every close ends with the funding output spent, so a spend only means a
+// force-close when it didn't follow a mutual close or our own unilateral one,
+// which is flagged when it starts
+fn is_force_close(event: &Value) -> bool {
+    let old_state = display_value(&event["old_state"]);
+    match display_value(&event["new_state"]).as_str() {
+        "AWAITING_UNILATERAL" => true,
+        "FUNDING_SPEND_SEEN" => {
+            !old_state.starts_with("CLOSINGD_") && old_state != "AWAITING_UNILATERAL"
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn routes_topics_to_their_channel_or_the_default() {
+        let channels = HashMap::from([
+            ("forward_event".to_string(), ChannelId(1)),
+            ("*".to_string(), ChannelId(2)),
+        ]);
+        assert_eq!(channel_for(&channels, "forward_event"), Some(ChannelId(1)));
+        assert_eq!(channel_for(&channels, "disconnect"), Some(ChannelId(2)));
+        assert_eq!(channel_for(&HashMap::new(), "disconnect"), None);
+    }
+
+    #[test]
+    fn force_closes_stand_out() {
+        let embed = embed(
+            "main",
+            "channel_state_changed",
+            &json!({ "channel_state_changed": {
+                "peer_id": "02aa",
+                "channel_id": "ab".repeat(32),
+                "short_channel_id": "103x1x0",
+                "old_state": "CHANNELD_NORMAL",
+                "new_state": "AWAITING_UNILATERAL",
+                "cause": "user",
+                "message": "Forcibly closed by `close` command timeout",
+            } }),
+        );
+
+        assert_eq!(embed.0["title"], "Channel force-closed");
+        assert_eq!(embed.0["color"], Colour::RED.0);
+        assert_eq!(embed.0["fields"].as_array().unwrap().len(), 8);
+
+        let state_change = |old_state: &str, new_state: &str| {
+            super::embed(
+                "main",
+                "channel_state_changed",
+                &json!({ "old_state": old_state, "new_state": new_state }),
+            )
+            .0["title"]
+                .clone()
+        };
+        // The peer closing on its own
+        assert_eq!(
+            state_change("CHANNELD_NORMAL", "FUNDING_SPEND_SEEN"),
+            "Channel force-closed"
+        );
+        // A mutual close
+        assert_eq!(
+            state_change("CLOSINGD_COMPLETE", "FUNDING_SPEND_SEEN"),
+            "Channel state changed"
+        );
+        assert_eq!(
+            state_change("FUNDING_SPEND_SEEN", "ONCHAIN"),
+            "Channel state changed"
+        );
+    }
+}
@@ -0,0 +1,286 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use cln_rpc::model::requests::{
+    DatastoreMode, DatastoreRequest, DeldatastoreRequest, ListdatastoreRequest,
+    ListinvoicesRequest, WaitanyinvoiceRequest,
+};
+use cln_rpc::{Request, Response};
+use serde_json::{json, Value};
+use serenity::builder::CreateEmbed;
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, UserId};
+use serenity::utils::Colour;
+use tracing::{error, info};
+
+use crate::commands::CommandOptions;
+use crate::lightning::{new_cln, ClnClient, ClnNodeConfig, ClnNodes};
+use crate::utils::display_value;
+use crate::utils::get_option_as::get_option_as;
+
+// Datastore keys, under the bot's own prefix
+const LASTPAY_INDEX_KEY: [&str; 2] = ["botimint", "lastpay_index"];
+const CREATORS_KEY: [&str; 2] = ["botimint", "invoice_creators"];
+
+// How long to wait before reconnecting after the watcher fails
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Remembers which Discord user created an invoice with `cln_invoice`, so
+/// they can be told when it's paid
+pub async fn remember_creator(nodes: &ClnNodes, options: &CommandOptions, user_id: UserId) {
+    let label = match get_option_as::<String>(options, "label") {
+        Ok(Some(label)) => label,
+        _ => return,
+    };
+    let node_name = get_option_as::<String>(options, "node").ok().flatten();
+    let node = match nodes.select(node_name.as_deref()) {
+        Ok(node) => node,
+        Err(_) => return,
+    };
+
+    let req = DatastoreRequest {
+        key: creator_key(&label),
+        string: Some(user_id.to_string()),
+        hex: None,
+        mode: Some(DatastoreMode::CREATE_OR_REPLACE),
+        generation: None,
+    };
+    if let Err(e) = node.client.call(Request::Datastore(req)).await {
+        error!("Cannot remember who created invoice {}: {}", label, e);
+    }
+}
+
+/// Watches every configured CLN node for paid invoices, posting each to
+/// `channel_id` and to the user who created it from Discord
+pub fn spawn(http: Arc<Http>, nodes: &[ClnNodeConfig], channel_id: Option<ChannelId>) {
+    for node in nodes.iter().cloned() {
+        let http = http.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = watch(&http, &node, channel_id).await {
+                    error!("Invoice watcher for CLN node {} failed: {:?}", node.name, e);
+                }
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        });
+    }
+}
+
+async fn watch(http: &Http, node: &ClnNodeConfig, channel_id: Option<ChannelId>) -> Result<()> {
+    // waitanyinvoice blocks until an invoice is paid, so the watcher gets a
+    // connection of its own rather than holding the commands' one
+    let client = new_cln(&node.rpc_path).await;
+    let mut lastpay_index = load_lastpay_index(&client).await?;
+    info!(
+        "Watching CLN node {} for invoices paid after #{}",
+        node.name, lastpay_index
+    );
+
+    loop {
+        let req = WaitanyinvoiceRequest {
+            lastpay_index: Some(lastpay_index),
+            timeout: None,
+        };
+        let invoice = call(&client, Request::WaitAnyInvoice(req)).await?;
+        lastpay_index = invoice["pay_index"]
+            .as_u64()
+            .ok_or_else(|| anyhow!("Paid invoice without a pay_index"))?;
+
+        notify(http, &client, node, channel_id, &invoice).await;
+        store_lastpay_index(&client, lastpay_index).await?;
+    }
+}
+
+async fn call(client: &ClnClient, req: Request) -> Result<Value> {
+    let res = client.call(req).await.map_err(|e| anyhow!("{}", e))?;
+
+    match res {
+        Response::WaitAnyInvoice(res) => Ok(json!(res)),
+        Response::ListInvoices(res) => Ok(json!(res)),
+        Response::ListDatastore(res) => Ok(json!(res)),
+        Response::Datastore(res) => Ok(json!(res)),
+        Response::DelDatastore(res) => Ok(json!(res)),
+        res => bail!("Unexpected response from CLN: {:?}", res),
+    }
+}
+
+async fn read_datastore(client: &ClnClient, key: Vec<String>) -> Result<Option<String>> {
+    let req = ListdatastoreRequest { key: Some(key) };
+    let res = call(client, Request::ListDatastore(req)).await?;
+
+    Ok(res["datastore"][0]["string"].as_str().map(str::to_string))
+}
+
+// Starts after the last invoice notified about, or after the invoices paid
+// before the watcher first ran so it doesn't post the node's whole history
+async fn load_lastpay_index(client: &ClnClient) -> Result<u64> {
+    if let Some(index) = read_datastore(client, key(&LASTPAY_INDEX_KEY)).await? {
+        return index
+            .parse()
+            .map_err(|e| anyhow!("Invalid stored lastpay_index {}: {}", index, e));
+    }
+
+    let req = ListinvoicesRequest {
+        label: None,
+        invstring: None,
+        payment_hash: None,
+        offer_id: None,
+        index: None,
+        start: None,
+        limit: None,
+    };
+    let res = call(client, Request::ListInvoices(req)).await?;
+    let index = res["invoices"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|invoice| invoice["pay_index"].as_u64())
+        .max()
+        .unwrap_or_default();
+    store_lastpay_index(client, index).await?;
+
+    Ok(index)
+}
+
+async fn store_lastpay_index(client: &ClnClient, index: u64) -> Result<()> {
+    let req = DatastoreRequest {
+        key: key(&LASTPAY_INDEX_KEY),
+        string: Some(index.to_string()),
+        hex: None,
+        mode: Some(DatastoreMode::CREATE_OR_REPLACE),
+        generation: None,
+    };
+    call(client, Request::Datastore(req)).await?;
+
+    Ok(())
+}
+
+// Posts the paid invoice to the channel and its creator. Failing to tell them
+// doesn't stop the watcher, as the invoice is paid either way.
+async fn notify(
+    http: &Http,
+    client: &ClnClient,
+    node: &ClnNodeConfig,
+    channel_id: Option<ChannelId>,
+    invoice: &Value,
+) {
+    let label = display_value(&invoice["label"]);
+    info!("Invoice {} paid on CLN node {}", label, node.name);
+    let embed = embed(&node.name, invoice);
+
+    if let Some(channel_id) = channel_id {
+        let embed = embed.clone();
+        if let Err(e) = channel_id
+            .send_message(http, |message| message.set_embed(embed))
+            .await
+        {
+            error!("Cannot post paid invoice {}: {:?}", label, e);
+        }
+    }
+
+    let creator = match read_datastore(client, creator_key(&label)).await {
+        Ok(creator) => creator.and_then(|id| id.parse::<u64>().ok()).map(UserId),
+        Err(e) => {
+            error!("Cannot look up who created invoice {}: {:?}", label, e);
+            None
+        }
+    };
+    if let Some(user_id) = creator {
+        let sent = match user_id.create_dm_channel(http).await {
+            Ok(dm) => {
+                dm.send_message(http, |message| message.set_embed(embed))
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = sent {
+            error!(
+                "Cannot tell user {} invoice {} was paid: {:?}",
+                user_id, label, e
+            );
+        }
+
+        let req = DeldatastoreRequest {
+            key: creator_key(&label),
+            generation: None,
+        };
+        if let Err(e) = call(client, Request::DelDatastore(req)).await {
+            error!("Cannot forget who created invoice {}: {:?}", label, e);
+        }
+    }
+}
+
+fn embed(node_name: &str, invoice: &Value) -> CreateEmbed {
+    let amount = invoice["amount_received_msat"]
+        .as_u64()
+        .or_else(|| invoice["amount_msat"].as_u64())
+        .map(|amount_msat| format!("{} msat", amount_msat))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut embed = CreateEmbed::default();
+    embed
+        .title("Invoice paid")
+        .colour(Colour::DARK_GREEN)
+        .field("Label", display_value(&invoice["label"]), true)
+        .field("Amount", amount, true)
+        .field("Node", node_name, true);
+    if let Some(description) = invoice["description"].as_str() {
+        embed.field("Description", description, false);
+    }
+    // Only BOLT12 payers can leave a note
+    if let Some(payer_note) = invoice["payer_note"].as_str() {
+        embed.field("Payer note", payer_note, false);
+    }
+    embed
+}
+
+fn key(key: &[&str]) -> Vec<String> {
+    key.iter().map(|part| part.to_string()).collect()
+}
+
+fn creator_key(label: &str) -> Vec<String> {
+    let mut key = key(&CREATORS_KEY);
+    key.push(label.to_string());
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::cln::MockCln;
+
+    #[tokio::test]
+    async fn starts_after_the_invoices_paid_before_the_first_run() {
+        let (cln, client) = MockCln::new()
+            .respond("listdatastore", json!({ "datastore": [] }))
+            .respond(
+                "listinvoices",
+                json!({ "invoices": [
+                    {
+                        "label": "coffee",
+                        "payment_hash": "00".repeat(32),
+                        "status": "paid",
+                        "expires_at": 1700000000,
+                        "pay_index": 7,
+                    },
+                    {
+                        "label": "tea",
+                        "payment_hash": "11".repeat(32),
+                        "status": "unpaid",
+                        "expires_at": 1700000000,
+                    },
+                ] }),
+            )
+            .respond(
+                "datastore",
+                json!({ "key": ["botimint", "lastpay_index"], "string": "7" }),
+            )
+            .start()
+            .await;
+
+        assert_eq!(load_lastpay_index(&client).await.unwrap(), 7);
+        assert_eq!(cln.requests("datastore")[0]["string"], "7");
+    }
+}
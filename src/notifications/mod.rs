@@ -0,0 +1,7 @@
+//! Background tasks posting what happens on the bot's nodes to Discord
+
+pub mod cln_alerts;
+pub mod invoices;
+pub mod node_status;
+pub mod operations;
+pub mod report;
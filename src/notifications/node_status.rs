@@ -0,0 +1,28 @@
+//! Tells admins in `ADMIN_CHANNEL_ID` when the bot loses the connection to a
+//! CLN node and when it gets it back.
+
+use std::sync::Arc;
+
+use serenity::http::Http;
+use serenity::model::id::ChannelId;
+use tracing::error;
+
+use crate::commands::status::describe;
+use crate::lightning::ClnNodes;
+
+pub fn spawn(http: Arc<Http>, cln_nodes: &ClnNodes, channel_id: ChannelId) {
+    for node in cln_nodes.list() {
+        let http = http.clone();
+        let name = node.name.clone();
+        let mut states = node.client.subscribe();
+        tokio::spawn(async move {
+            while states.changed().await.is_ok() {
+                let state = states.borrow_and_update().clone();
+                let message = format!("CLN node **{}** is {}", name, describe(&state));
+                if let Err(e) = channel_id.say(&http, message).await {
+                    error!("Cannot post the status of CLN node {}: {:?}", name, e);
+                }
+            }
+        });
+    }
+}
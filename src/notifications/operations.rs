@@ -0,0 +1,305 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use fedimint_client::oplog::UpdateStreamOrOutcome;
+use fedimint_core::config::FederationId;
+use fedimint_core::core::OperationId;
+use fedimint_ln_client::LightningClientModule;
+use fedimint_mint_client::MintClientModule;
+use fedimint_wallet_client::{WalletClientModule, WalletOperationMeta, WalletOperationMetaVariant};
+use futures::StreamExt;
+use lightning_invoice::Bolt11Invoice;
+use serde_json::Value;
+use serenity::builder::CreateEmbed;
+use serenity::http::Http;
+use serenity::model::id::ChannelId;
+use serenity::utils::Colour;
+use tracing::{error, info};
+
+use crate::federations::{Federation, Federations};
+use crate::utils::display_value;
+
+// How often the operation logs are checked for new operations
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// An operation as the feed shows it
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationEvent {
+    pub id: OperationId,
+    /// The client module and the kind of operation within it, e.g. `ln_pay`
+    pub kind: String,
+    pub amount: Option<String>,
+    pub fee: Option<String>,
+}
+
+impl OperationEvent {
+    pub fn new(id: OperationId, module: &str, meta: &Value) -> Self {
+        // Module metas tag their operation kind, e.g. `{"variant": {"pay": ...}}`
+        let variant = meta["variant"]
+            .as_object()
+            .and_then(|variant| variant.keys().next())
+            .map(|variant| variant.to_lowercase().replace('_', ""));
+        let kind = match variant {
+            Some(variant) => format!("{}_{}", module, variant),
+            None => module.to_string(),
+        };
+        // On-chain amounts are in sats, the federation's in msats
+        let unit = if module == "wallet" { "sat" } else { "msat" };
+        let amount = find_number(meta, &["amount", "amount_msat"])
+            .or_else(|| invoice_amount_msat(meta))
+            .map(|amount| format!("{} {}", amount, unit));
+        let fee = withdrawal_fee_sat(meta)
+            .or_else(|| find_number(meta, &["fee", "fees"]))
+            .map(|fee| format!("{} {}", fee, unit));
+
+        Self {
+            id,
+            kind,
+            amount,
+            fee,
+        }
+    }
+
+    /// Whether the `kinds` filter lets the operation through: every operation
+    /// when it's empty, otherwise those of a listed kind or module
+    pub fn is_included(&self, kinds: &[String]) -> bool {
+        kinds.is_empty()
+            || kinds.iter().any(|kind| {
+                self.kind == *kind
+                    || self
+                        .kind
+                        .strip_prefix(kind.as_str())
+                        .map_or(false, |rest| rest.starts_with('_'))
+            })
+    }
+
+    fn embed(&self, federation: &Federation, title: &str, outcome: Option<&str>) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        embed
+            .title(format!("{} {}", self.kind, title))
+            .colour(match outcome {
+                Some(_) => Colour::DARK_GREEN,
+                None => Colour::BLUE,
+            })
+            .field("Federation", federation.label(), false)
+            .field("Operation", self.id.to_string(), false);
+        if let Some(amount) = &self.amount {
+            embed.field("Amount", amount, true);
+        }
+        if let Some(fee) = &self.fee {
+            embed.field("Fee", fee, true);
+        }
+        if let Some(outcome) = outcome {
+            embed.field("Outcome", outcome, false);
+        }
+        embed
+    }
+}
+
+/// Posts every operation the bot's federation clients start to `channel_id`,
+/// and again with its outcome when it completes. Operations already in the
+/// logs when the feed starts are skipped.
+pub fn spawn(http: Arc<Http>, federations: Federations, channel_id: ChannelId, kinds: Vec<String>) {
+    tokio::spawn(async move {
+        let mut seen = HashSet::<(FederationId, OperationId)>::new();
+        let mut known_federations = HashSet::<FederationId>::new();
+        loop {
+            for federation in federations.list() {
+                // Federations joined at runtime start from their current log too
+                let is_new = known_federations.insert(federation.id());
+                for event in poll(&federation, &mut seen).await {
+                    if is_new || !event.is_included(&kinds) {
+                        continue;
+                    }
+                    info!("Operation {} started: {}", event.id, event.kind);
+                    post(&http, channel_id, event.embed(&federation, "started", None)).await;
+
+                    let http = http.clone();
+                    let federation = federation.clone();
+                    tokio::spawn(async move {
+                        let outcome = match final_state(&federation, &event).await {
+                            Ok(Some(outcome)) => outcome,
+                            Ok(None) => return,
+                            Err(e) => format!("Cannot follow the operation: {}", e),
+                        };
+                        let embed = event.embed(&federation, "completed", Some(&outcome));
+                        post(&http, channel_id, embed).await;
+                    });
+                }
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+// The operations in the federation's log that weren't seen before
+async fn poll(
+    federation: &Federation,
+    seen: &mut HashSet<(FederationId, OperationId)>,
+) -> Vec<OperationEvent> {
+    let operations = federation
+        .client
+        .operation_log()
+        .list_operations(100, None)
+        .await;
+
+    let mut events = operations
+        .into_iter()
+        .filter(|(key, _)| seen.insert((federation.id(), key.operation_id)))
+        .map(|(key, operation)| {
+            OperationEvent::new(
+                key.operation_id,
+                operation.operation_module_kind(),
+                &operation.meta(),
+            )
+        })
+        .collect::<Vec<_>>();
+    // The log lists the newest operations first
+    events.reverse();
+    events
+}
+
+// Follows the operation's updates to the last one, for the kinds of operation
+// the feed knows how to follow
+async fn final_state(federation: &Federation, event: &OperationEvent) -> Result<Option<String>> {
+    let client = &federation.client;
+    let id = event.id;
+    let outcome = match event.kind.as_str() {
+        "ln_pay" => {
+            let ln = client.get_first_module::<LightningClientModule>();
+            last_update(ln.subscribe_ln_pay(id).await?).await
+        }
+        "ln_receive" => {
+            let ln = client.get_first_module::<LightningClientModule>();
+            last_update(ln.subscribe_ln_receive(id).await?).await
+        }
+        "mint_reissuance" => {
+            let mint = client.get_first_module::<MintClientModule>();
+            last_update(mint.subscribe_reissue_external_notes(id).await?).await
+        }
+        "mint_spendoob" => {
+            let mint = client.get_first_module::<MintClientModule>();
+            last_update(mint.subscribe_spend_notes(id).await?).await
+        }
+        "wallet_deposit" => {
+            let wallet = client.get_first_module::<WalletClientModule>();
+            last_update(wallet.subscribe_deposit_updates(id).await?).await
+        }
+        "wallet_withdraw" => {
+            let wallet = client.get_first_module::<WalletClientModule>();
+            last_update(wallet.subscribe_withdraw_updates(id).await?).await
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(outcome)
+}
+
+async fn last_update<U>(updates: UpdateStreamOrOutcome<U>) -> Option<String>
+where
+    U: Debug + Send + Sync + 'static,
+{
+    let mut updates = updates.into_stream();
+    let mut last = None;
+    while let Some(update) = updates.next().await {
+        last = Some(update);
+    }
+    last.map(|update| format!("{:?}", update))
+}
+
+async fn post(http: &Http, channel_id: ChannelId, embed: CreateEmbed) {
+    if let Err(e) = channel_id
+        .send_message(http, |message| message.set_embed(embed))
+        .await
+    {
+        error!("Cannot post operation event: {:?}", e);
+    }
+}
+
+// Finds the first number under one of `keys` anywhere in `value`
+fn find_number(value: &Value, keys: &[&str]) -> Option<String> {
+    match value {
+        Value::Object(map) => keys
+            .iter()
+            .find_map(|key| match map.get(*key) {
+                Some(number @ Value::Number(_)) => Some(display_value(number)),
+                _ => None,
+            })
+            .or_else(|| map.values().find_map(|value| find_number(value, keys))),
+        Value::Array(values) => values.iter().find_map(|value| find_number(value, keys)),
+        _ => None,
+    }
+}
+
+// Withdrawals record the fee rate and the transaction's weight rather than the
+// fee itself
+fn withdrawal_fee_sat(meta: &Value) -> Option<String> {
+    match serde_json::from_value::<WalletOperationMeta>(meta.clone())
+        .ok()?
+        .variant
+    {
+        WalletOperationMetaVariant::Withdraw { fee, .. } => Some(fee.amount().to_sat().to_string()),
+        _ => None,
+    }
+}
+
+// Lightning operations only record the invoice, which carries the amount
+fn invoice_amount_msat(meta: &Value) -> Option<String> {
+    let invoice = meta["variant"]
+        .as_object()?
+        .values()
+        .find_map(|variant| variant["invoice"].as_str())?;
+    let amount_msat = Bolt11Invoice::from_str(invoice)
+        .ok()?
+        .amount_milli_satoshis()?;
+
+    Some(amount_msat.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn summarizes_operation_metas() {
+        let id = OperationId([1; 32]);
+        let event = OperationEvent::new(
+            id,
+            "wallet",
+            &json!({
+                "variant": { "withdraw": {
+                    "address": "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq",
+                    "amount": 5000,
+                    "fee": { "fee_rate": { "sats_per_kvb": 2000 }, "total_weight": 800 },
+                } },
+                "extra_meta": null,
+            }),
+        );
+
+        assert_eq!(event.kind, "wallet_withdraw");
+        assert_eq!(event.amount.as_deref(), Some("5000 sat"));
+        // 200 vbytes at 2 sat/vbyte
+        assert_eq!(event.fee.as_deref(), Some("400 sat"));
+    }
+
+    #[test]
+    fn filters_by_kind_or_module() {
+        let event = OperationEvent::new(
+            OperationId([1; 32]),
+            "ln",
+            &json!({ "variant": { "receive": {} } }),
+        );
+
+        assert!(event.is_included(&[]));
+        assert!(event.is_included(&["ln".to_string()]));
+        assert!(event.is_included(&["ln_receive".to_string(), "mint".to_string()]));
+        assert!(!event.is_included(&["ln_pay".to_string()]));
+        assert!(!event.is_included(&["l".to_string()]));
+    }
+}
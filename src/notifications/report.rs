@@ -0,0 +1,334 @@
+//! A digest of how the CLN nodes and federations are doing, posted every day to
+//! `REPORT_CHANNEL_ID` at `REPORT_TIME` and shown on demand with `/report`.
+//! `REPORT_SECTIONS` picks which sections it has and in what order.
+
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Result};
+use cln_rpc::model::requests::{ListforwardsRequest, ListpaysRequest, ListpaysStatus};
+use cln_rpc::{Request, Response};
+use fedimint_client::ClientArc;
+use serde_json::{json, Value};
+use serenity::http::Http;
+use serenity::model::id::ChannelId;
+use time::{OffsetDateTime, Time};
+use tracing::{error, info};
+
+use crate::commands::fed::info::note_summary;
+use crate::federations::Federations;
+use crate::lightning::{ClnBackend, ClnClient, ClnNode, ClnNodes, LightningBackend};
+use crate::utils::response::{prepare_reply, Reply};
+
+// Forwards and payments are counted over the day before the report
+const WINDOW_SECS: u64 = 24 * 60 * 60;
+
+// Operations are looked for among the client's latest ones
+const MAX_OPERATIONS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportSection {
+    /// CLN block height, peers and channels
+    Node,
+    /// CLN on-chain and channel balances
+    Funds,
+    /// CLN forwards and fees earned
+    Forwards,
+    /// CLN payments that failed
+    Payments,
+    /// The notes each federation client holds
+    Notes,
+    /// Federation operations without an outcome yet
+    Operations,
+}
+
+impl ReportSection {
+    pub const ALL: [ReportSection; 6] = [
+        ReportSection::Node,
+        ReportSection::Funds,
+        ReportSection::Forwards,
+        ReportSection::Payments,
+        ReportSection::Notes,
+        ReportSection::Operations,
+    ];
+
+    fn is_cln(&self) -> bool {
+        !matches!(self, ReportSection::Notes | ReportSection::Operations)
+    }
+}
+
+impl FromStr for ReportSection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "node" => Ok(ReportSection::Node),
+            "funds" => Ok(ReportSection::Funds),
+            "forwards" => Ok(ReportSection::Forwards),
+            "payments" => Ok(ReportSection::Payments),
+            "notes" => Ok(ReportSection::Notes),
+            "operations" => Ok(ReportSection::Operations),
+            s => Err(format!(
+                "Unknown report section {}, expected node, funds, forwards, payments, notes or operations",
+                s
+            )),
+        }
+    }
+}
+
+/// Writes the report's `sections` for every CLN node and federation. A
+/// section that can't be fetched says so instead of failing the report.
+pub async fn build(
+    cln_nodes: &ClnNodes,
+    federations: &Federations,
+    sections: &[ReportSection],
+) -> String {
+    let since = now_secs().saturating_sub(WINDOW_SECS);
+    let mut report = Vec::new();
+
+    if sections.iter().any(ReportSection::is_cln) {
+        for node in cln_nodes.list() {
+            report.push(format!("**CLN node {}**", node.name));
+            for section in sections.iter().filter(|section| section.is_cln()) {
+                report.push(line(*section, cln_line(node, *section, since).await));
+            }
+        }
+    }
+    if sections.iter().any(|section| !section.is_cln()) {
+        for federation in federations.list() {
+            report.push(format!("**Federation {}**", federation.label()));
+            for section in sections.iter().filter(|section| !section.is_cln()) {
+                let res = fm_line(&federation.client, *section).await;
+                report.push(line(*section, res));
+            }
+        }
+    }
+
+    if report.is_empty() {
+        "Nothing to report".to_string()
+    } else {
+        report.join("\n")
+    }
+}
+
+/// Posts the report to `channel_id` every day at `at`, in UTC
+pub fn spawn(
+    http: Arc<Http>,
+    cln_nodes: ClnNodes,
+    federations: Federations,
+    channel_id: ChannelId,
+    at: Time,
+    sections: Vec<ReportSection>,
+) {
+    tokio::spawn(async move {
+        loop {
+            let wait = until_next(OffsetDateTime::now_utc(), at);
+            info!("Posting the next daily report in {}s", wait.as_secs());
+            tokio::time::sleep(wait).await;
+
+            let report = build(&cln_nodes, &federations, &sections).await;
+            post(&http, channel_id, format!("__Daily report__\n{}", report)).await;
+        }
+    });
+}
+
+// How long until the clock next reads `at`
+fn until_next(now: OffsetDateTime, at: Time) -> Duration {
+    let mut next = now.replace_time(at);
+    if next <= now {
+        next += time::Duration::DAY;
+    }
+    (next - now).try_into().unwrap_or_default()
+}
+
+async fn post(http: &Http, channel_id: ChannelId, report: String) {
+    let pages = match prepare_reply("report", report) {
+        Reply::Pages(pages) => pages,
+        Reply::Attachment { summary, .. } => vec![summary],
+    };
+    for page in pages {
+        if let Err(e) = channel_id.say(http, page).await {
+            error!("Cannot post the daily report: {:?}", e);
+            return;
+        }
+    }
+}
+
+fn line(section: ReportSection, res: Result<String>) -> String {
+    match res {
+        Ok(line) => line,
+        Err(e) => format!("{:?}: unavailable ({})", section, e),
+    }
+}
+
+async fn cln_line(node: &ClnNode, section: ReportSection, since: u64) -> Result<String> {
+    match section {
+        ReportSection::Node => {
+            let info = ClnBackend::new(node.client.clone()).info().await?;
+            Ok(format!(
+                "Block height {}, peers {}, active channels {}",
+                info.block_height, info.num_peers, info.num_active_channels
+            ))
+        }
+        ReportSection::Funds => {
+            let funds = ClnBackend::new(node.client.clone()).list_funds().await?;
+            let (confirmed, unconfirmed) =
+                funds
+                    .outputs
+                    .iter()
+                    .fold((0, 0), |(confirmed, unconfirmed), output| {
+                        if output.confirmed {
+                            (confirmed + output.amount_msat, unconfirmed)
+                        } else {
+                            (confirmed, unconfirmed + output.amount_msat)
+                        }
+                    });
+            let ours = funds
+                .channels
+                .iter()
+                .map(|c| c.our_amount_msat)
+                .sum::<u64>();
+            let total = funds.channels.iter().map(|c| c.amount_msat).sum::<u64>();
+            Ok(format!(
+                "On-chain {} msat confirmed, {} msat unconfirmed. Channels {} msat ours of {} msat",
+                confirmed, unconfirmed, ours, total
+            ))
+        }
+        ReportSection::Forwards => {
+            let req = ListforwardsRequest {
+                status: None,
+                in_channel: None,
+                out_channel: None,
+            };
+            let res = call(&node.client, Request::ListForwards(req)).await?;
+            let forwards = recent(&res["forwards"], "received_time", since);
+            let settled = forwards
+                .iter()
+                .filter(|forward| forward["status"] == "settled")
+                .collect::<Vec<_>>();
+            let failed = forwards
+                .iter()
+                .filter(|forward| {
+                    forward["status"] == "failed" || forward["status"] == "local_failed"
+                })
+                .count();
+            let fees = settled
+                .iter()
+                .filter_map(|forward| forward["fee_msat"].as_u64())
+                .sum::<u64>();
+            Ok(format!(
+                "Forwards in the last 24h: {} settled, {} failed, {} msat earned in fees",
+                settled.len(),
+                failed,
+                fees
+            ))
+        }
+        ReportSection::Payments => {
+            let req = ListpaysRequest {
+                bolt11: None,
+                payment_hash: None,
+                status: Some(ListpaysStatus::FAILED),
+            };
+            let res = call(&node.client, Request::ListPays(req)).await?;
+            let failed = recent(&res["pays"], "created_at", since);
+            Ok(format!("Failed payments in the last 24h: {}", failed.len()))
+        }
+        section => bail!("{:?} is not a CLN section", section),
+    }
+}
+
+async fn fm_line(fm_client: &ClientArc, section: ReportSection) -> Result<String> {
+    match section {
+        ReportSection::Notes => {
+            let summary = note_summary(fm_client).await;
+            Ok(format!(
+                "Notes: {} msat in {} notes",
+                summary.total_amount().msats,
+                summary.count_items()
+            ))
+        }
+        ReportSection::Operations => {
+            let operations = fm_client
+                .operation_log()
+                .list_operations(MAX_OPERATIONS, None)
+                .await;
+            let pending = operations
+                .iter()
+                .filter(|(_, operation)| operation.outcome::<Value>().is_none())
+                .count();
+            Ok(format!("Pending operations: {}", pending))
+        }
+        section => bail!("{:?} is not a federation section", section),
+    }
+}
+
+async fn call(client: &ClnClient, req: Request) -> Result<Value> {
+    let res = client.call(req).await.map_err(|e| anyhow!("{}", e))?;
+
+    match res {
+        Response::ListForwards(res) => Ok(json!(res)),
+        Response::ListPays(res) => Ok(json!(res)),
+        res => bail!("Unexpected response from CLN: {:?}", res),
+    }
+}
+
+// The entries of `list` whose `time_key` is at or after `since`
+fn recent<'a>(list: &'a Value, time_key: &str, since: u64) -> Vec<&'a Value> {
+    list.as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(|entry| {
+            entry[time_key]
+                .as_f64()
+                .map_or(false, |t| t >= since as f64)
+        })
+        .collect()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::cln::{getinfo, listfunds, MockCln};
+
+    #[test]
+    fn waits_for_the_next_report_time() {
+        let at = Time::from_hms(9, 0, 0).unwrap();
+        // 2023-11-01 08:30 and 09:00 UTC
+        let before = OffsetDateTime::from_unix_timestamp(1698827400).unwrap();
+        let on_time = OffsetDateTime::from_unix_timestamp(1698829200).unwrap();
+
+        assert_eq!(until_next(before, at), Duration::from_secs(30 * 60));
+        assert_eq!(until_next(on_time, at), Duration::from_secs(24 * 60 * 60));
+    }
+
+    #[tokio::test]
+    async fn reports_node_and_funds() {
+        let (_cln, client) = MockCln::new()
+            .respond("getinfo", getinfo())
+            .respond("listfunds", listfunds(2))
+            .start()
+            .await;
+        let node = ClnNode {
+            name: "main".to_string(),
+            client,
+        };
+
+        assert_eq!(
+            cln_line(&node, ReportSection::Node, 0).await.unwrap(),
+            "Block height 800000, peers 1, active channels 1"
+        );
+        assert_eq!(
+            cln_line(&node, ReportSection::Funds, 0).await.unwrap(),
+            "On-chain 200000000 msat confirmed, 0 msat unconfirmed. Channels 0 msat ours of 0 msat"
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! Operations Discord users are waiting on, kept in their federation's ledger
+//! so that the outcome still reaches them when the bot restarts before the
+//! operation completes. Commands report the operations they wait on through
+//! their `Progress`, and on startup the bot waits on whatever is left and
+//! replies to the original message with the result.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use fedimint_core::config::FederationId;
+use fedimint_core::core::OperationId;
+use fedimint_core::db::Database;
+use fedimint_core::encoding::{Decodable, Encodable};
+use fedimint_core::{impl_db_lookup, impl_db_record, Amount};
+use futures::StreamExt;
+use serde_json::Value;
+use serenity::builder::CreateEmbed;
+use serenity::http::Http;
+use serenity::model::id::{ChannelId, MessageId, UserId};
+use tracing::{error, info};
+
+use crate::commands::fed::{ln, wallet};
+use crate::commands::{CommandOptions, CommandResponse, CommandResult};
+use crate::federations::{Federation, Federations};
+use crate::ledger::{autocommit_error, DbKeyPrefix, MAX_DB_ATTEMPTS};
+use crate::utils::progress::Progress;
+use crate::utils::response::{prepare_reply, Reply};
+use crate::utils::to_codeblock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encodable, Decodable)]
+pub enum OperationKind {
+    LnPay,
+    LnReceive,
+    Deposit,
+    Withdraw,
+}
+
+impl OperationKind {
+    /// The command that waits on operations of this kind
+    pub fn command_name(&self) -> &'static str {
+        match self {
+            OperationKind::LnPay => "fm_ln_pay",
+            OperationKind::LnReceive => "fm_ln_await_invoice",
+            OperationKind::Deposit => "fm_wallet_await_deposit",
+            OperationKind::Withdraw => "fm_wallet_withdraw",
+        }
+    }
+}
+
+/// An operation a command waits on, as it reports it through `Progress`
+#[derive(Debug, Clone)]
+pub struct StartedOperation {
+    pub federation_id: FederationId,
+    pub operation_id: OperationId,
+    pub kind: OperationKind,
+    /// What the user's balance is credited if the operation fails
+    pub refund: Amount,
+    /// Whether the operation outlives the command, which then doesn't forget
+    /// it once it replies. Whatever follows the operation does instead.
+    pub background: bool,
+}
+
+#[derive(Debug, Clone, Encodable, Decodable)]
+pub struct PendingOperationKey(pub OperationId);
+
+#[derive(Debug, Encodable, Decodable)]
+pub struct PendingOperationKeyPrefix;
+
+/// An operation and where its outcome goes
+#[derive(Debug, Clone, Encodable, Decodable)]
+pub struct PendingOperation {
+    pub federation_id: FederationId,
+    pub kind: OperationKind,
+    pub user_id: u64,
+    /// Where the command was run, unless its response was only shown to the
+    /// user, whose outcome is then sent to them directly
+    pub channel_id: Option<u64>,
+    /// The command's response, which the outcome replies to
+    pub message_id: Option<u64>,
+    pub refund: Amount,
+}
+
+impl_db_record!(
+    key = PendingOperationKey,
+    value = PendingOperation,
+    db_prefix = DbKeyPrefix::PendingOperation
+);
+impl_db_lookup!(
+    key = PendingOperationKey,
+    query_prefix = PendingOperationKeyPrefix
+);
+
+#[derive(Clone)]
+pub struct PendingOperations {
+    db: Database,
+}
+
+impl PendingOperations {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub async fn insert(
+        &self,
+        operation_id: OperationId,
+        operation: &PendingOperation,
+    ) -> Result<()> {
+        self.db
+            .autocommit(
+                |dbtx, _| {
+                    Box::pin(async move {
+                        dbtx.insert_entry(&PendingOperationKey(operation_id), operation)
+                            .await;
+                        Ok::<_, anyhow::Error>(())
+                    })
+                },
+                Some(MAX_DB_ATTEMPTS),
+            )
+            .await
+            .map_err(autocommit_error)
+    }
+
+    pub async fn remove(&self, operation_id: OperationId) -> Result<()> {
+        self.db
+            .autocommit(
+                |dbtx, _| {
+                    Box::pin(async move {
+                        dbtx.remove_entry(&PendingOperationKey(operation_id)).await;
+                        Ok::<_, anyhow::Error>(())
+                    })
+                },
+                Some(MAX_DB_ATTEMPTS),
+            )
+            .await
+            .map_err(autocommit_error)
+    }
+
+    pub async fn list(&self) -> Vec<(OperationId, PendingOperation)> {
+        self.db
+            .begin_transaction_nc()
+            .await
+            .find_by_prefix(&PendingOperationKeyPrefix)
+            .await
+            .map(|(key, operation)| (key.0, operation))
+            .collect::<Vec<_>>()
+            .await
+    }
+}
+
+/// Waits on every operation left pending by the previous run and delivers its
+/// outcome to the user who started it
+pub fn resume(http: Arc<Http>, federations: Federations) {
+    for federation in federations.list() {
+        let http = http.clone();
+        tokio::spawn(async move {
+            let pending = federation.ledger.pending_operations();
+            for (operation_id, operation) in pending.list().await {
+                info!(
+                    "Resuming {} operation {} for user {}",
+                    operation.kind.command_name(),
+                    operation_id,
+                    operation.user_id
+                );
+                let http = http.clone();
+                let federation = federation.clone();
+                let pending = pending.clone();
+                tokio::spawn(async move {
+                    let res = match outcome(&federation, operation_id, &operation).await {
+                        Some(res) => res,
+                        None => {
+                            info!("Operation {} is still unsettled", operation_id);
+                            return;
+                        }
+                    };
+                    deliver(&http, operation_id, &operation, res).await;
+                    if let Err(e) = pending.remove(operation_id).await {
+                        error!("Cannot remove pending operation {}: {:?}", operation_id, e);
+                    }
+                });
+            }
+        });
+    }
+}
+
+// Waits on the operation with the command that would have, refunding the user
+// if it fails. A payment that may still succeed isn't refunded but left
+// pending, and gives no outcome.
+async fn outcome(
+    federation: &Federation,
+    operation_id: OperationId,
+    operation: &PendingOperation,
+) -> Option<CommandResult> {
+    let fm_client = &federation.client;
+    let options: CommandOptions = HashMap::from([(
+        "operation_id".to_string(),
+        Some(Value::String(operation_id.to_string())),
+    )]);
+    let progress = Progress::default();
+    let res = match operation.kind {
+        OperationKind::LnPay => match ln::await_pay::wait(fm_client, operation_id, &progress).await
+        {
+            Ok(res) => Ok(to_codeblock(serde_json::to_string_pretty(&res).unwrap()).into()),
+            Err(e) if ln::is_payment_failed(&e) => Err(e.into()),
+            Err(e) => {
+                error!(
+                    "Cannot learn the outcome of payment {}: {}",
+                    operation_id, e
+                );
+                return None;
+            }
+        },
+        OperationKind::LnReceive => ln::await_invoice::run(&options, fm_client, &progress).await,
+        OperationKind::Deposit => wallet::await_deposit::run(&options, fm_client, &progress).await,
+        OperationKind::Withdraw => {
+            wallet::withdraw::await_withdrawal(fm_client, operation_id, None, &progress).await
+        }
+    };
+
+    if res.is_err() && operation.refund > Amount::ZERO {
+        let user_id = UserId(operation.user_id);
+        let refunded = federation
+            .ledger
+            .refund_operation(operation_id, user_id, operation.refund)
+            .await;
+        if let Err(e) = refunded {
+            error!(
+                "Cannot refund {} to user {}: {}",
+                operation.refund, user_id, e
+            );
+        }
+    }
+    Some(res)
+}
+
+// Replies to the command's response in its channel, or messages the user when
+// it was only shown to them or that isn't possible anymore
+async fn deliver(
+    http: &Http,
+    operation_id: OperationId,
+    operation: &PendingOperation,
+    res: CommandResult,
+) {
+    let command_name = operation.kind.command_name();
+    let intro = format!(
+        "<@{}> `{}` operation {} finished while the bot was restarting",
+        operation.user_id, command_name, operation_id
+    );
+    let (pages, embed) = match res {
+        Ok(CommandResponse::Text(content)) => match prepare_reply(command_name, content) {
+            Reply::Pages(pages) => (pages, None),
+            Reply::Attachment { summary, .. } => (vec![summary], None),
+        },
+        Ok(CommandResponse::List(list)) => (vec![list.title().to_string()], None),
+        Err(e) => (Vec::new(), Some(e.embed(command_name))),
+    };
+
+    if let Some(channel_id) = operation.channel_id.map(ChannelId) {
+        let reference = operation
+            .message_id
+            .map(|message_id| (channel_id, MessageId(message_id)));
+        if send(http, channel_id, reference, &intro, &pages, embed.clone())
+            .await
+            .is_ok()
+        {
+            return;
+        }
+    }
+
+    let user_id = UserId(operation.user_id);
+    let sent = match user_id.create_dm_channel(http).await {
+        Ok(dm) => send(http, dm.id, None, &intro, &pages, embed).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = sent {
+        error!(
+            "Cannot deliver operation {} to user {}: {:?}",
+            operation_id, user_id, e
+        );
+    }
+}
+
+/// Posts `intro` to the channel, replying to `reference` if given, followed by
+/// `pages`
+pub(crate) async fn send(
+    http: &Http,
+    channel_id: ChannelId,
+    reference: Option<(ChannelId, MessageId)>,
+    intro: &str,
+    pages: &[String],
+    embed: Option<CreateEmbed>,
+) -> serenity::Result<()> {
+    channel_id
+        .send_message(http, |message| {
+            message.content(intro);
+            if let Some(reference) = reference {
+                message.reference_message(reference);
+            }
+            if let Some(embed) = embed {
+                message.set_embed(embed);
+            }
+            message
+        })
+        .await?;
+    for page in pages {
+        channel_id.say(http, page).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use serenity::model::id::{RoleId, UserId};
+
+use crate::config::CONFIG;
+
+/// The tier of access a command requires. Tiers are ordered, so a user granted
+/// `Admin` can also run `Spending` and `ReadOnly` commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Permission {
+    ReadOnly,
+    Spending,
+    Admin,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ReadOnly => "read-only",
+            Permission::Spending => "spending",
+            Permission::Admin => "admin",
+        }
+    }
+}
+
+/// Discord roles and users that are granted a permission tier
+#[derive(Debug, Clone, Default)]
+pub struct Grants {
+    pub role_ids: Vec<RoleId>,
+    pub user_ids: Vec<UserId>,
+}
+
+impl Grants {
+    pub fn is_empty(&self) -> bool {
+        self.role_ids.is_empty() && self.user_ids.is_empty()
+    }
+
+    fn matches(&self, user_id: UserId, role_ids: &[RoleId]) -> bool {
+        self.user_ids.contains(&user_id) || role_ids.iter().any(|r| self.role_ids.contains(r))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PermissionsConfig {
+    pub read_only: Grants,
+    pub spending: Grants,
+    pub admin: Grants,
+}
+
+impl PermissionsConfig {
+    /// Returns the highest tier granted to the user, if any. Read-only access
+    /// is open to everyone unless read-only roles or users are configured.
+    pub fn granted(&self, user_id: UserId, role_ids: &[RoleId]) -> Option<Permission> {
+        if self.admin.matches(user_id, role_ids) {
+            Some(Permission::Admin)
+        } else if self.spending.matches(user_id, role_ids) {
+            Some(Permission::Spending)
+        } else if self.read_only.is_empty() || self.read_only.matches(user_id, role_ids) {
+            Some(Permission::ReadOnly)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_authorized(
+        &self,
+        required: Permission,
+        user_id: UserId,
+        role_ids: &[RoleId],
+    ) -> bool {
+        self.granted(user_id, role_ids)
+            .map_or(false, |granted| granted >= required)
+    }
+}
+
+/// Checks the invoking user against the configured grants, returning the
+/// message to show them when they are not allowed to run the command.
+pub fn authorize(
+    command_name: &str,
+    required: Permission,
+    user_id: UserId,
+    role_ids: &[RoleId],
+) -> Result<(), String> {
+    if CONFIG
+        .permissions
+        .is_authorized(required, user_id, role_ids)
+    {
+        return Ok(());
+    }
+
+    Err(format!(
+        "You are not authorized to run `{}`: it requires {} access.",
+        command_name,
+        required.as_str()
+    ))
+}
@@ -1,5 +1,8 @@
-use anyhow::Result;
+use std::path::Path;
+
+use anyhow::{Context, Result};
 use fedimint_client::{get_config_from_db, ClientArc, FederationInfo};
+use fedimint_core::api::InviteCode;
 use fedimint_core::db::Database;
 use fedimint_ln_client::LightningClientInit;
 use fedimint_mint_client::MintClientInit;
@@ -7,14 +10,21 @@ use fedimint_wallet_client::WalletClientInit;
 
 use crate::CONFIG;
 
-pub async fn load_fedimint_client() -> Result<ClientArc> {
+/// Opens the client database at `db_path`, joining the federation with
+/// `invite_code` if the database doesn't hold a federation config yet
+pub async fn load_fedimint_client(
+    db_path: &Path,
+    invite_code: Option<InviteCode>,
+) -> Result<ClientArc> {
     let db = Database::new(
-        fedimint_rocksdb::RocksDb::open(&CONFIG.fm_db_path)?,
+        fedimint_rocksdb::RocksDb::open(db_path)?,
         Default::default(),
     );
     let mut client_builder = fedimint_client::Client::builder();
     if get_config_from_db(&db).await.is_none() {
-        let federation_info = FederationInfo::from_invite_code(CONFIG.invite_code.clone()).await?;
+        let invite_code =
+            invite_code.with_context(|| format!("No federation config in {:?}", db_path))?;
+        let federation_info = FederationInfo::from_invite_code(invite_code).await?;
         client_builder.with_federation_info(federation_info);
     };
     client_builder.with_database(db);
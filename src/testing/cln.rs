@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::watch;
+
+use crate::lightning::{new_cln, ClnClient};
+
+type Requests = Arc<StdMutex<Vec<(String, Value)>>>;
+
+/// A fake lightningd answering JSON-RPC calls on a unix socket with canned
+/// results per method, so handlers can run against a real `ClnRpc`
+pub struct MockCln {
+    responses: HashMap<String, Value>,
+    requests: Requests,
+    dir: TempDir,
+}
+
+impl MockCln {
+    pub fn new() -> Self {
+        Self {
+            responses: HashMap::new(),
+            requests: Default::default(),
+            dir: TempDir::new().expect("Cannot create socket directory"),
+        }
+    }
+
+    /// Answers calls to `method` with `result`
+    pub fn respond(mut self, method: &str, result: Value) -> Self {
+        self.responses
+            .insert(method.to_string(), json!({ "result": result }));
+        self
+    }
+
+    /// Answers calls to `method` with a JSON-RPC error
+    pub fn fail(mut self, method: &str, code: i32, message: &str) -> Self {
+        self.responses.insert(
+            method.to_string(),
+            json!({ "error": { "code": code, "message": message } }),
+        );
+        self
+    }
+
+    /// Never answers calls to `method`, like a `wait*` call whose event
+    /// doesn't happen
+    pub fn hang(mut self, method: &str) -> Self {
+        self.responses.insert(method.to_string(), Value::Null);
+        self
+    }
+
+    /// Starts serving and connects a client to the socket. Methods without a
+    /// canned response fail like unknown commands do in lightningd.
+    pub async fn start(self) -> (MockClnHandle, ClnClient) {
+        let path = self.dir.path().join("lightning-rpc");
+        let listener = UnixListener::bind(&path).expect("Cannot bind mock CLN socket");
+        let responses = Arc::new(self.responses);
+        let requests = self.requests.clone();
+        let (disconnect, disconnected) = watch::channel(());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let serving = serve(stream, responses.clone(), requests.clone());
+                let mut disconnected = disconnected.clone();
+                // Only disconnects after this connection was made close it
+                disconnected.borrow_and_update();
+                tokio::spawn(async move {
+                    tokio::select! {
+                        _ = serving => {}
+                        _ = disconnected.changed() => {}
+                    }
+                });
+            }
+        });
+
+        let client = new_cln(&path).await;
+        let handle = MockClnHandle {
+            requests: self.requests,
+            disconnect,
+            _dir: self.dir,
+            path,
+        };
+
+        (handle, client)
+    }
+}
+
+impl Default for MockCln {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Keeps the socket alive and exposes the calls the mock received
+pub struct MockClnHandle {
+    requests: Requests,
+    disconnect: watch::Sender<()>,
+    _dir: TempDir,
+    pub path: PathBuf,
+}
+
+impl MockClnHandle {
+    /// The params of every call made to `method`, oldest first
+    pub fn requests(&self, method: &str) -> Vec<Value> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(m, _)| m == method)
+            .map(|(_, params)| params.clone())
+            .collect()
+    }
+
+    /// Closes the connections made so far, as lightningd restarting would
+    pub fn disconnect(&self) {
+        self.disconnect.send_replace(());
+    }
+}
+
+async fn serve(mut stream: UnixStream, responses: Arc<HashMap<String, Value>>, requests: Requests) {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        // Requests aren't delimited reliably, so take each complete JSON value
+        // off the front of the buffer
+        let mut values = serde_json::Deserializer::from_slice(&buf).into_iter::<Value>();
+        let request = match values.next() {
+            Some(Ok(request)) => {
+                let consumed = values.byte_offset();
+                buf.drain(..consumed);
+                request
+            }
+            Some(Err(e)) if !e.is_eof() => return,
+            _ => match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(n) => {
+                    buf.extend_from_slice(&chunk[..n]);
+                    continue;
+                }
+            },
+        };
+
+        let method = request["method"].as_str().unwrap_or_default().to_string();
+        requests
+            .lock()
+            .unwrap()
+            .push((method.clone(), request["params"].clone()));
+
+        if responses.get(&method) == Some(&Value::Null) {
+            std::future::pending::<()>().await;
+        }
+        let mut response = responses.get(&method).cloned().unwrap_or_else(|| {
+            json!({ "error": { "code": -32601, "message": format!("Unknown command '{}'", method) } })
+        });
+        response["jsonrpc"] = json!("2.0");
+        response["id"] = request["id"].clone();
+
+        let mut bytes = serde_json::to_vec(&response).unwrap();
+        bytes.extend_from_slice(b"\n\n");
+        if stream.write_all(&bytes).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// A `getinfo` result with every field `GetinfoResponse` requires
+pub fn getinfo() -> Value {
+    json!({
+        "id": "02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619",
+        "alias": "botimint-test",
+        "color": "02eec7",
+        "num_peers": 1,
+        "num_pending_channels": 0,
+        "num_active_channels": 1,
+        "num_inactive_channels": 0,
+        "address": [],
+        "binding": [],
+        "version": "v23.08",
+        "blockheight": 800000,
+        "network": "regtest",
+        "fees_collected_msat": 0,
+        "lightning-dir": "/tmp/lightning/regtest"
+    })
+}
+
+/// A `listfunds` result with the given number of confirmed outputs and no
+/// channels
+pub fn listfunds(outputs: usize) -> Value {
+    let outputs = (0..outputs)
+        .map(|i| {
+            json!({
+                "txid": format!("{:064x}", i + 1),
+                "output": 0,
+                "amount_msat": 100_000_000,
+                "scriptpubkey": "0014d1d4c2a6a0e1a3e4f6b2a3ea5b8e5e6e7c8b9a0f",
+                "address": "bcrt1q68gv9f4qux37fa4j504tt3j7dm7ghxs0d3jzls",
+                "status": "confirmed",
+                "reserved": false,
+                "blockheight": 799990
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({ "outputs": outputs, "channels": [] })
+}
@@ -0,0 +1,70 @@
+use fedimint_client::ClientArc;
+use fedimint_ln_client::LightningClientInit;
+use fedimint_ln_common::config::LightningGenParams;
+use fedimint_ln_server::LightningInit;
+use fedimint_mint_client::MintClientInit;
+use fedimint_mint_common::config::MintGenParams;
+use fedimint_mint_server::MintInit;
+use fedimint_testing::federation::FederationTest;
+use fedimint_testing::fixtures::Fixtures;
+use fedimint_wallet_client::WalletClientInit;
+use fedimint_wallet_common::config::WalletGenParams;
+use fedimint_wallet_server::WalletInit;
+use tempfile::TempDir;
+
+use crate::federations::{Federation, Federations};
+use crate::ledger::Ledger;
+
+/// An in-process federation with the modules the bot uses, on a fake bitcoind
+pub struct TestFederation {
+    pub fed: FederationTest,
+    pub client: ClientArc,
+    pub ledger: Ledger,
+    federations: Federations,
+    _ledger_dir: TempDir,
+}
+
+impl TestFederation {
+    pub async fn new() -> Self {
+        let fixtures = Fixtures::new_primary(MintClientInit, MintInit, MintGenParams::default());
+        let bitcoin = fixtures.bitcoin_server();
+        let fixtures = fixtures
+            .with_module(
+                WalletClientInit(None),
+                WalletInit,
+                WalletGenParams::regtest(bitcoin.clone()),
+            )
+            .with_module(
+                LightningClientInit,
+                LightningInit,
+                LightningGenParams::regtest(bitcoin),
+            );
+
+        let fed = fixtures.new_fed().await;
+        let client = fed.new_client().await;
+
+        let ledger_dir = TempDir::new().expect("Cannot create ledger directory");
+        let ledger = Ledger::open(ledger_dir.path()).expect("Cannot open test ledger");
+
+        let federations = Federations::new(
+            ledger_dir.path().join("federations"),
+            Federation {
+                client: client.clone(),
+                ledger: ledger.clone(),
+            },
+        );
+
+        Self {
+            fed,
+            client,
+            ledger,
+            federations,
+            _ledger_dir: ledger_dir,
+        }
+    }
+
+    /// A registry with this federation as its default
+    pub fn federations(&self) -> Federations {
+        self.federations.clone()
+    }
+}
@@ -0,0 +1,17 @@
+//! Fakes for running command handlers in `cargo test`: a mock CLN node, an
+//! in-process federation and a builder for the options Discord would send
+
+use serde_json::Value;
+
+use crate::commands::CommandOptions;
+
+pub mod cln;
+pub mod fed;
+
+/// Builds the options of a command invocation
+pub fn options(options: &[(&str, Value)]) -> CommandOptions {
+    options
+        .iter()
+        .map(|(name, value)| (name.to_string(), Some(value.clone())))
+        .collect()
+}
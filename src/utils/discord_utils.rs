@@ -1,16 +1,99 @@
-use serenity::model::prelude::command::Command;
-use tracing::info;
+use std::collections::BTreeSet;
 
-pub async fn create_and_log_command<F>(http: &serenity::http::Http, command_register: F)
-where
-    F: FnOnce(
-            &mut serenity::builder::CreateApplicationCommand,
-        ) -> &mut serenity::builder::CreateApplicationCommand
-        + Send
-        + Sync,
-{
-    let command = Command::create_global_application_command(http, command_register)
+use serenity::builder::CreateApplicationCommand;
+use serenity::http::Http;
+use serenity::model::application::command::Command;
+use serenity::model::id::GuildId;
+use tracing::{error, info};
+
+/// Replaces the bot's slash commands with `commands` in a single bulk
+/// overwrite per scope, which also deletes commands that no longer exist.
+/// With guilds configured the commands are registered in each of them, where
+/// changes show up immediately, and any global commands are removed so they
+/// aren't listed twice, but only once every guild has the new ones. Failures
+/// are logged and leave the previous commands in place.
+pub async fn register_commands(
+    http: &Http,
+    commands: Vec<CreateApplicationCommand>,
+    guild_ids: &[GuildId],
+) {
+    if guild_ids.is_empty() {
+        let registered = Command::get_global_application_commands(http).await;
+        log_diff("global", &commands, registered);
+        match Command::set_global_application_commands(http, |c| {
+            c.set_application_commands(commands)
+        })
         .await
-        .unwrap();
-    info!("Created Slash Command: {:#?}", command.name);
+        {
+            Ok(registered) => info!("Registered {} global commands", registered.len()),
+            Err(e) => error!("Cannot register global commands: {:?}", e),
+        }
+        return;
+    }
+
+    let mut failed = 0;
+    for guild_id in guild_ids {
+        let scope = format!("guild {}", guild_id);
+        let registered = guild_id.get_application_commands(http).await;
+        log_diff(&scope, &commands, registered);
+        match guild_id
+            .set_application_commands(http, |c| c.set_application_commands(commands.clone()))
+            .await
+        {
+            Ok(registered) => info!("Registered {} commands in {}", registered.len(), scope),
+            Err(e) => {
+                error!("Cannot register commands in {}: {:?}", scope, e);
+                failed += 1;
+            }
+        }
+    }
+    // The global commands are all that guilds without the new ones have left
+    if failed > 0 {
+        info!(
+            "Keeping the global commands, registering failed in {} guilds",
+            failed
+        );
+        return;
+    }
+
+    match Command::get_global_application_commands(http).await {
+        Ok(global) if !global.is_empty() => {
+            match Command::set_global_application_commands(http, |c| c).await {
+                Ok(_) => info!("Removed {} global commands", global.len()),
+                Err(e) => error!("Cannot remove global commands: {:?}", e),
+            }
+        }
+        Ok(_) => {}
+        Err(e) => error!("Cannot list global commands: {:?}", e),
+    }
+}
+
+// Logs which commands the overwrite adds and deletes in `scope`
+fn log_diff(
+    scope: &str,
+    commands: &[CreateApplicationCommand],
+    registered: serenity::Result<Vec<Command>>,
+) {
+    let registered = match registered {
+        Ok(registered) => registered,
+        Err(e) => {
+            error!("Cannot list the commands in {}: {:?}", scope, e);
+            return;
+        }
+    };
+
+    let wanted = commands
+        .iter()
+        .filter_map(|command| command.0.get("name")?.as_str())
+        .collect::<BTreeSet<_>>();
+    let existing = registered
+        .iter()
+        .map(|command| command.name.as_str())
+        .collect::<BTreeSet<_>>();
+    for name in wanted.difference(&existing) {
+        info!("Adding command {} in {}", name, scope);
+    }
+    for name in existing.difference(&wanted) {
+        info!("Deleting stale command {} in {}", name, scope);
+    }
 }
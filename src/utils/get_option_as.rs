@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::str::FromStr;
 
 use anyhow::Result;
@@ -11,8 +10,11 @@ use cln_rpc::primitives::{
     Amount, AmountOrAll, AmountOrAny, Feerate, Outpoint, OutputDesc, PublicKey, Routehint,
     RoutehintList, Routehop, Secret, Sha256, ShortChannelId, TlvEntry, TlvStream,
 };
+use regex::Regex;
 use serde_json::Value;
 
+use crate::commands::{CommandError, CommandOptions};
+
 // Define a trait for types that can be created from an Option<Value>
 pub trait FromOptionValue: Sized {
     fn from_option_value(value: &Option<Value>) -> Result<Self, String>;
@@ -53,30 +55,38 @@ fn parse_vec<T: FromOptionValue>(value: &Option<Value>) -> Result<Vec<T>, String
     }
 }
 
+// Helper function for parsing unsigned integers, which Discord sends as JSON
+// numbers
+fn parse_uint<T: TryFrom<u64>>(value: Option<Value>, type_name: &str) -> Result<T, String> {
+    value
+        .as_ref()
+        .and_then(Value::as_u64)
+        .and_then(|n| T::try_from(n).ok())
+        .ok_or_else(|| err_msg(type_name))
+}
+
 // Implement FromOptionValue for various types using the macro
-impl_from_option_value!(bool, |value| {
-    bool::from_option_value(&value).map_err(|_| err_msg("bool"))
-});
-impl_from_option_value!(u8, |value| {
-    u8::from_option_value(&value).map_err(|_| err_msg("u8"))
-});
-impl_from_option_value!(u16, |value| {
-    u16::from_option_value(&value).map_err(|_| err_msg("u16"))
-});
-impl_from_option_value!(u32, |value| {
-    u32::from_option_value(&value).map_err(|_| err_msg("u32"))
-});
-impl_from_option_value!(u64, |value| {
-    u64::from_option_value(&value).map_err(|_| err_msg("u64"))
-});
-impl_from_option_value!(usize, |value| {
-    usize::from_option_value(&value).map_err(|_| err_msg("f32"))
-});
-impl_from_option_value!(f32, |value| {
-    f32::from_option_value(&value).map_err(|_| err_msg("f32"))
-});
-impl_from_option_value!(f64, |value| {
-    f64::from_option_value(&value).map_err(|_| err_msg("f64"))
+impl_from_option_value!(bool, |value| match value {
+    Some(Value::Bool(b)) => Ok(b),
+    _ => Err(err_msg("bool")),
+});
+impl_from_option_value!(u8, |value| parse_uint(value, "u8"));
+impl_from_option_value!(u16, |value| parse_uint(value, "u16"));
+impl_from_option_value!(u32, |value| parse_uint(value, "u32"));
+impl_from_option_value!(u64, |value| parse_uint(value, "u64"));
+impl_from_option_value!(usize, |value| parse_uint(value, "usize"));
+impl_from_option_value!(f32, |value: Option<Value>| {
+    value
+        .as_ref()
+        .and_then(Value::as_f64)
+        .map(|n| n as f32)
+        .ok_or_else(|| err_msg("f32"))
+});
+impl_from_option_value!(f64, |value: Option<Value>| {
+    value
+        .as_ref()
+        .and_then(Value::as_f64)
+        .ok_or_else(|| err_msg("f64"))
 });
 impl_from_option_value!(String, parse_string);
 impl_from_option_value!(PublicKey, |value| {
@@ -104,12 +114,13 @@ impl_from_option_value!(Amount, |value| {
                 .map(constructor_fn)
         };
 
+        // "msat" has to be checked before "sat", which it ends with
         let amount = if s.ends_with("btc") {
             parse_with_suffix("btc", Amount::from_btc)
-        } else if s.ends_with("sat") {
-            parse_with_suffix("sat", Amount::from_sat)
         } else if s.ends_with("msat") {
             parse_with_suffix("msat", Amount::from_msat)
+        } else if s.ends_with("sat") {
+            parse_with_suffix("sat", Amount::from_sat)
         } else {
             // Default to sat if no specific suffix is found
             s.parse::<u64>()
@@ -521,14 +532,34 @@ impl_from_option_value!(Vec<AmountOrAny>, |value| parse_vec(&value));
 impl_from_option_value!(Vec<NewaddrAddresstype>, |value| parse_vec(&value));
 impl_from_option_value!(Vec<Feerate>, |value| parse_vec(&value));
 
-// Generalized get_option_as function remains the same
+/// Parses an optional option, returning `None` when it wasn't provided and an
+/// error naming the expected type when it can't be parsed
 pub fn get_option_as<T: FromOptionValue>(
-    options_map: &HashMap<String, Option<Value>>,
+    options_map: &CommandOptions,
     key: &str,
-) -> Option<T> {
-    options_map
-        .get(key)
-        .and_then(|v| T::from_option_value(v).ok())
+) -> Result<Option<T>, CommandError> {
+    match options_map.get(key) {
+        None | Some(None) | Some(Some(Value::Null)) => Ok(None),
+        Some(value) => T::from_option_value(value)
+            .map(Some)
+            .map_err(|reason| CommandError::invalid_option(key, type_name::<T>(), reason)),
+    }
+}
+
+/// Parses an option the command can't run without
+pub fn get_required_option_as<T: FromOptionValue>(
+    options_map: &CommandOptions,
+    key: &str,
+) -> Result<T, CommandError> {
+    get_option_as(options_map, key)?.ok_or_else(|| CommandError::MissingOption(key.to_string()))
+}
+
+// The name of a type without its module paths, e.g. `Vec<Outpoint>`
+fn type_name<T>() -> String {
+    let name = std::any::type_name::<T>();
+    Regex::new(r"\w+::")
+        .map(|re| re.replace_all(name, "").into_owned())
+        .unwrap_or_else(|_| name.to_string())
 }
 
 fn parse_amount<F, T>(value: &Option<Value>, constructor: F) -> Result<T, String>
@@ -561,3 +592,88 @@ where
         None => Err("No value provided".to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::commands::CommandOptions;
+    use crate::testing::options;
+
+    #[test]
+    fn parses_discord_values() {
+        let options_map = options(&[
+            ("amount_msat", json!(1000)),
+            ("spent", json!(true)),
+            ("label", json!("coffee")),
+        ]);
+
+        assert_eq!(
+            get_required_option_as::<u64>(&options_map, "amount_msat").unwrap(),
+            1000
+        );
+        assert!(get_required_option_as::<bool>(&options_map, "spent").unwrap());
+        assert_eq!(
+            get_required_option_as::<String>(&options_map, "label").unwrap(),
+            "coffee"
+        );
+        assert_eq!(get_option_as::<u32>(&options_map, "expiry").unwrap(), None);
+    }
+
+    #[test]
+    fn parses_amount_units() {
+        let options_map = options(&[
+            ("msat", json!("1500msat")),
+            ("sat", json!("2sat")),
+            ("btc", json!("1btc")),
+            ("any", json!("any")),
+        ]);
+
+        let amount = |key| get_required_option_as::<Amount>(&options_map, key).unwrap();
+        assert_eq!(amount("msat").msat(), 1500);
+        assert_eq!(amount("sat").msat(), 2000);
+        assert_eq!(amount("btc").msat(), 100_000_000_000);
+        assert!(matches!(
+            get_required_option_as::<AmountOrAny>(&options_map, "any").unwrap(),
+            AmountOrAny::Any
+        ));
+    }
+
+    #[test]
+    fn reports_missing_required_options() {
+        let options_map = CommandOptions::new();
+
+        let err = get_required_option_as::<String>(&options_map, "bolt11").unwrap_err();
+        assert!(matches!(err, CommandError::MissingOption(name) if name == "bolt11"));
+    }
+
+    #[test]
+    fn reports_the_expected_type_of_invalid_options() {
+        let options_map = options(&[
+            ("amount_msat", json!("lots")),
+            ("id", json!("not a node id")),
+            ("exclude", json!(["a", 1])),
+        ]);
+
+        assert_eq!(
+            expected_type(get_option_as::<u64>(&options_map, "amount_msat").unwrap_err()),
+            "u64"
+        );
+        assert_eq!(
+            expected_type(get_option_as::<PublicKey>(&options_map, "id").unwrap_err()),
+            "PublicKey"
+        );
+        assert_eq!(
+            expected_type(get_option_as::<Vec<String>>(&options_map, "exclude").unwrap_err()),
+            "Vec<String>"
+        );
+    }
+
+    fn expected_type(err: CommandError) -> String {
+        match err {
+            CommandError::InvalidOption { expected, .. } => expected,
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+}
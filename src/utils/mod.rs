@@ -1,8 +1,10 @@
-use serde_json::json;
+use serde_json::{json, Value};
 
 pub mod address_utils;
 pub mod discord_utils;
 pub mod get_option_as;
+pub mod progress;
+pub mod response;
 
 pub fn format_json(res: cln_rpc::Response) -> String {
     let data = serde_json::to_string_pretty(&json!(res)).unwrap();
@@ -12,3 +14,12 @@ pub fn format_json(res: cln_rpc::Response) -> String {
 pub fn to_codeblock(val: String) -> String {
     format!("```json\n{}\n```", val)
 }
+
+// Renders a JSON field for a human, without quotes around strings
+pub fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "unknown".to_string(),
+        value => value.to_string(),
+    }
+}
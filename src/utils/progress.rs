@@ -0,0 +1,45 @@
+use tokio::sync::mpsc;
+
+use crate::pending::StartedOperation;
+
+/// Handle that long running commands use to report intermediate state
+/// transitions while the final result is still pending. Updates are dropped
+/// when nobody is listening.
+#[derive(Clone, Default)]
+pub struct Progress {
+    sender: Option<mpsc::UnboundedSender<String>>,
+    operations: Option<mpsc::UnboundedSender<StartedOperation>>,
+}
+
+impl Progress {
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<String>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (
+            Self {
+                sender: Some(sender),
+                operations: None,
+            },
+            receiver,
+        )
+    }
+
+    /// Also listens for the operations the command waits on, so they can be
+    /// resumed if the bot restarts before they complete
+    pub fn with_operations(mut self) -> (Self, mpsc::UnboundedReceiver<StartedOperation>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.operations = Some(sender);
+        (self, receiver)
+    }
+
+    pub fn update(&self, message: impl Into<String>) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(message.into());
+        }
+    }
+
+    pub fn operation_started(&self, operation: StartedOperation) {
+        if let Some(operations) = &self.operations {
+            let _ = operations.send(operation);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use serde_json::Value;
+
+// Discord rejects messages longer than this many characters
+pub const MESSAGE_LIMIT: usize = 2000;
+
+// JSON responses that would need more messages than this are attached as a
+// file instead
+const MAX_PAGES: usize = 3;
+
+// Room kept free on every page for closing and reopening a code block
+const PAGE_BUDGET: usize = MESSAGE_LIMIT - 16;
+const LINE_BUDGET: usize = PAGE_BUDGET - 16;
+
+const FENCE: &str = "```";
+
+/// A command response prepared to fit Discord's message limits
+pub enum Reply {
+    /// One or more messages, each within `MESSAGE_LIMIT`
+    Pages(Vec<String>),
+    /// A short summary with the full JSON payload sent as a file
+    Attachment {
+        summary: String,
+        filename: String,
+        data: Vec<u8>,
+    },
+}
+
+pub fn prepare_reply(command_name: &str, content: String) -> Reply {
+    if content.chars().count() <= MESSAGE_LIMIT {
+        return Reply::Pages(vec![content]);
+    }
+
+    let pages = paginate(&content);
+    if pages.len() > MAX_PAGES {
+        if let Some(json) = json_payload(&content) {
+            return Reply::Attachment {
+                summary: summarize(command_name, json),
+                filename: format!("{}.json", command_name.replace(' ', "_")),
+                data: json.as_bytes().to_vec(),
+            };
+        }
+    }
+
+    Reply::Pages(pages)
+}
+
+/// Splits the content into messages on line boundaries, closing any open code
+/// block at the end of a page and reopening it on the next one
+pub fn paginate(content: &str) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut page_len = 0;
+    let mut open_fence: Option<&str> = None;
+
+    for line in content.lines() {
+        for piece in split_line(line) {
+            let piece_len = piece.chars().count();
+            if page_len > 0 && page_len + piece_len + 1 > PAGE_BUDGET {
+                if open_fence.is_some() {
+                    page.push('\n');
+                    page.push_str(FENCE);
+                }
+                pages.push(std::mem::take(&mut page));
+                page_len = 0;
+                if let Some(fence) = open_fence {
+                    page.push_str(fence);
+                    page_len = fence.chars().count();
+                }
+            }
+            if page_len > 0 {
+                page.push('\n');
+                page_len += 1;
+            }
+            page.push_str(&piece);
+            page_len += piece_len;
+        }
+
+        if line.starts_with(FENCE) {
+            open_fence = match open_fence {
+                Some(_) => None,
+                None => Some(line),
+            };
+        }
+    }
+    if !page.is_empty() {
+        pages.push(page);
+    }
+
+    pages
+}
+
+// Breaks lines that wouldn't fit on a page by themselves
+fn split_line(line: &str) -> Vec<String> {
+    if line.is_empty() {
+        return vec![String::new()];
+    }
+
+    line.chars()
+        .collect::<Vec<_>>()
+        .chunks(LINE_BUDGET)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+// Returns the JSON inside a response that is a single json code block
+fn json_payload(content: &str) -> Option<&str> {
+    content.strip_prefix("```json\n")?.strip_suffix("\n```")
+}
+
+fn summarize(command_name: &str, json: &str) -> String {
+    let mut summary = format!(
+        "`{}` returned {} bytes of JSON, the full response is attached.",
+        command_name,
+        json.len()
+    );
+
+    match serde_json::from_str::<Value>(json) {
+        Ok(Value::Object(map)) => {
+            for (key, value) in map {
+                if let Value::Array(items) = value {
+                    summary.push_str(&format!("\n- `{}`: {} items", key, items.len()));
+                }
+            }
+        }
+        Ok(Value::Array(items)) => {
+            summary.push_str(&format!("\n- {} items", items.len()));
+        }
+        _ => {}
+    }
+
+    summary.chars().take(MESSAGE_LIMIT).collect()
+}